@@ -0,0 +1,548 @@
+use std::{
+    io::Read,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use borsh::BorshDeserialize;
+use clap::ArgMatches;
+use solana_clap_utils::input_parsers::{pubkey_of, pubkeys_of, value_of};
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use solana_sdk::{
+    borsh::try_from_slice_unchecked, instruction::Instruction, program_pack::Pack, pubkey::Pubkey,
+    signature::Signer, transaction::Transaction,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+use program::{
+    client::{
+        idl,
+        inspect::inspect_account,
+        instruction::{self, DepositLockup},
+        pda,
+    },
+    return_data::VoterWeight,
+    state::{
+        ExchangeRateEntry, LockupKind, LockupPolicy, Registrar, RewardPool, Voter,
+        VotingTokenPolicy,
+    },
+};
+use spl_governance_addin_api::voter_weight::VoterWeightRecord;
+
+use crate::{CommandResult, Config, Error};
+
+fn send(config: &Config, ix: Instruction) -> CommandResult {
+    let recent_blockhash = config.rpc_client.get_latest_blockhash()?;
+    let mut signers: Vec<&dyn Signer> = vec![&config.payer];
+    if config.authority.pubkey() != config.payer.pubkey() {
+        signers.push(&config.authority);
+    }
+
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&config.payer.pubkey()),
+        &signers,
+        recent_blockhash,
+    );
+    let signature = config.rpc_client.send_and_confirm_transaction(&tx)?;
+    println!("Signature: {}", signature);
+
+    Ok(())
+}
+
+fn pubkey(matches: &ArgMatches, name: &str) -> Result<Pubkey, Error> {
+    pubkey_of(matches, name).ok_or_else(|| format!("missing `--{}`", name).into())
+}
+
+fn value<T>(matches: &ArgMatches, name: &str) -> Result<T, Error>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Debug,
+{
+    value_of(matches, name).ok_or_else(|| format!("missing `--{}`", name).into())
+}
+
+fn deposit_token(config: &Config, matches: &ArgMatches, mint: &Pubkey) -> Pubkey {
+    pubkey_of(matches, "source").unwrap_or_else(|| {
+        spl_associated_token_account::get_associated_token_address(&config.authority.pubkey(), mint)
+    })
+}
+
+fn lockup_kind(name: &str) -> LockupKind {
+    match name {
+        "daily" => LockupKind::Daily,
+        "constant" => LockupKind::Constant,
+        "monthly" => LockupKind::Monthly,
+        _ => LockupKind::Cliff,
+    }
+}
+
+fn lockup_policy(matches: &ArgMatches) -> Result<LockupPolicy, Error> {
+    Ok(LockupPolicy {
+        min_lockup_days: value(matches, "min-lockup-days")?,
+        max_lockup_days: value(matches, "max-lockup-days")?,
+        allowed_kinds: matches
+            .values_of("lockup-kinds")
+            .into_iter()
+            .flatten()
+            .fold(0, |kinds, name| kinds | lockup_kind(name).mask()),
+    })
+}
+
+pub fn create_registrar(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let realm = pubkey(matches, "realm")?;
+
+    send(
+        config,
+        instruction::create_registrar(
+            &config.payer.pubkey(),
+            &config.authority.pubkey(),
+            &realm,
+            &pubkey(matches, "community-mint")?,
+            value(matches, "rate-decimals")?,
+            lockup_policy(matches)?,
+        ),
+    )?;
+    println!("Registrar: {}", pda::registrar_address(&realm));
+
+    Ok(())
+}
+
+pub fn add_exchange_rate(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let registrar = pubkey(matches, "registrar")?;
+    let mint = pubkey(matches, "mint")?;
+    let decimals = match value_of(matches, "decimals") {
+        Some(decimals) => decimals,
+        None => Mint::unpack(&config.rpc_client.get_account_data(&mint)?)?.decimals,
+    };
+
+    send(
+        config,
+        instruction::create_exchange_rate(
+            &config.authority.pubkey(),
+            &registrar,
+            value(matches, "idx")?,
+            ExchangeRateEntry {
+                mint,
+                rate: value(matches, "rate")?,
+                decimals,
+                voting_token: match matches.value_of("voting-token") {
+                    Some("transferable") => VotingTokenPolicy::Transferable,
+                    Some("disabled") => VotingTokenPolicy::Disabled,
+                    _ => VotingTokenPolicy::Frozen,
+                },
+                baseline_vote_weight_scaled_factor: value(
+                    matches,
+                    "baseline-vote-weight-scaled-factor",
+                )?,
+                max_extra_lockup_vote_weight_scaled_factor: value(
+                    matches,
+                    "max-extra-lockup-vote-weight-scaled-factor",
+                )?,
+                lockup_saturation_secs: value(matches, "lockup-saturation-secs")?,
+            },
+        ),
+    )?;
+    println!(
+        "Voting mint: {}",
+        pda::voting_mint_address(&registrar, &mint)
+    );
+
+    Ok(())
+}
+
+pub fn create_voter(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let registrar = pubkey(matches, "registrar")?;
+
+    send(
+        config,
+        instruction::create_voter(
+            &config.payer.pubkey(),
+            &registrar,
+            &config.authority.pubkey(),
+        ),
+    )?;
+    println!(
+        "Voter: {}",
+        pda::voter_address(&registrar, &config.authority.pubkey())
+    );
+
+    Ok(())
+}
+
+pub fn deposit(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let registrar = pubkey(matches, "registrar")?;
+    let mint = pubkey(matches, "mint")?;
+    let kind = matches
+        .value_of("kind")
+        .map_or(LockupKind::Cliff, lockup_kind);
+
+    let amount = value(matches, "amount")?;
+    let lockup = DepositLockup {
+        kind,
+        days: value(matches, "days")?,
+    };
+
+    send(
+        config,
+        if matches.is_present("funded-by-payer") {
+            //the authority co-signs for its new entry
+            let depositor = config.payer.pubkey();
+            let deposit_token = pubkey_of(matches, "source").unwrap_or_else(|| {
+                spl_associated_token_account::get_associated_token_address(&depositor, &mint)
+            });
+            instruction::deposit_for(
+                &registrar,
+                &depositor,
+                &config.authority.pubkey(),
+                &mint,
+                &deposit_token,
+                amount,
+                lockup,
+            )
+        } else {
+            instruction::deposit_from(
+                &registrar,
+                &config.authority.pubkey(),
+                &mint,
+                &deposit_token(config, matches, &mint),
+                amount,
+                lockup,
+            )
+        },
+    )
+}
+
+pub fn update_deposit(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let registrar = pubkey(matches, "registrar")?;
+    let mint = pubkey(matches, "mint")?;
+
+    let deposit_token = deposit_token(config, matches, &mint);
+    let deposit_idx = value(matches, "deposit-idx")?;
+    let amount = value(matches, "amount")?;
+
+    send(
+        config,
+        match pubkey_of(matches, "voter-authority") {
+            Some(voter_authority) => instruction::update_deposit_for(
+                &registrar,
+                &config.authority.pubkey(),
+                &voter_authority,
+                &mint,
+                &deposit_token,
+                deposit_idx,
+                amount,
+            ),
+            None => instruction::update_deposit_from(
+                &registrar,
+                &config.authority.pubkey(),
+                &mint,
+                &deposit_token,
+                deposit_idx,
+                amount,
+            ),
+        },
+    )
+}
+
+pub fn withdraw(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let registrar = pubkey(matches, "registrar")?;
+    let mint = pubkey(matches, "mint")?;
+    let voting_token = pubkey_of(matches, "voting-token");
+    // transferable voting tokens pay their holder
+    let holder = match voting_token {
+        Some(voting_token) => {
+            TokenAccount::unpack(&config.rpc_client.get_account_data(&voting_token)?)?.owner
+        }
+        None => config.authority.pubkey(),
+    };
+    let destination = pubkey_of(matches, "destination").unwrap_or_else(|| {
+        spl_associated_token_account::get_associated_token_address(&holder, &mint)
+    });
+
+    let mut keys =
+        instruction::withdraw_keys(&registrar, &config.authority.pubkey(), &mint, &destination);
+    if let Some(voting_token) = voting_token {
+        keys.voting_token = voting_token;
+    }
+
+    send(
+        config,
+        program::instruction::withdraw(
+            &keys,
+            value(matches, "deposit-idx")?,
+            value(matches, "amount")?,
+        ),
+    )
+}
+
+pub fn start_unlock(config: &Config, matches: &ArgMatches) -> CommandResult {
+    send(
+        config,
+        instruction::start_unlock(
+            &pubkey(matches, "registrar")?,
+            &config.authority.pubkey(),
+            value(matches, "deposit-idx")?,
+        ),
+    )
+}
+
+pub fn set_early_unlock_policy(config: &Config, matches: &ArgMatches) -> CommandResult {
+    send(
+        config,
+        instruction::set_early_unlock_policy(
+            &config.authority.pubkey(),
+            &pubkey(matches, "registrar")?,
+            value(matches, "penalty-bps")?,
+            &pubkey_of(matches, "treasury").unwrap_or_default(),
+        ),
+    )
+}
+
+pub fn early_unlock(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let registrar = pubkey(matches, "registrar")?;
+    let treasury =
+        Registrar::try_from_slice(&config.rpc_client.get_account_data(&registrar)?)?.treasury;
+
+    send(
+        config,
+        instruction::early_unlock(
+            &registrar,
+            &config.authority.pubkey(),
+            &pubkey(matches, "mint")?,
+            &treasury,
+            value(matches, "deposit-idx")?,
+        ),
+    )
+}
+
+fn reward_mint(config: &Config, registrar: &Pubkey) -> Result<Pubkey, Error> {
+    let reward_pool = pda::reward_pool_address(registrar);
+    Ok(RewardPool::try_from_slice(&config.rpc_client.get_account_data(&reward_pool)?)?.reward_mint)
+}
+
+pub fn create_reward_pool(config: &Config, matches: &ArgMatches) -> CommandResult {
+    send(
+        config,
+        instruction::create_reward_pool(
+            &config.authority.pubkey(),
+            &pubkey(matches, "registrar")?,
+            &pubkey(matches, "mint")?,
+        ),
+    )
+}
+
+pub fn fund_rewards(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let registrar = pubkey(matches, "registrar")?;
+    let mint = reward_mint(config, &registrar)?;
+
+    send(
+        config,
+        instruction::fund_rewards(
+            &config.authority.pubkey(),
+            &registrar,
+            &mint,
+            &deposit_token(config, matches, &mint),
+            value(matches, "amount")?,
+        ),
+    )
+}
+
+pub fn claim_rewards(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let registrar = pubkey(matches, "registrar")?;
+    let mint = reward_mint(config, &registrar)?;
+
+    send(
+        config,
+        instruction::claim_rewards(&registrar, &config.authority.pubkey(), &mint),
+    )
+}
+
+pub fn refresh_reward_weight(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let voter_authority =
+        pubkey_of(matches, "voter-authority").unwrap_or_else(|| config.authority.pubkey());
+
+    send(
+        config,
+        instruction::refresh_reward_weight(&pubkey(matches, "registrar")?, &voter_authority),
+    )
+}
+
+pub fn set_lockup_policy(config: &Config, matches: &ArgMatches) -> CommandResult {
+    send(
+        config,
+        instruction::set_lockup_policy(
+            &config.authority.pubkey(),
+            &pubkey(matches, "registrar")?,
+            lockup_policy(matches)?,
+        ),
+    )
+}
+
+pub fn set_delegation_cooldown(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let registrar_address = pubkey(matches, "registrar")?;
+    let registrar =
+        Registrar::try_from_slice(&config.rpc_client.get_account_data(&registrar_address)?)?;
+    let governances = pubkeys_of(matches, "governance").unwrap_or_default();
+
+    send(
+        config,
+        instruction::set_delegation_cooldown(
+            &config.authority.pubkey(),
+            &registrar_address,
+            &registrar.realm,
+            &governances,
+            value(matches, "cooldown-secs")?,
+        ),
+    )
+}
+
+pub fn set_governing_snapshot(config: &Config, matches: &ArgMatches) -> CommandResult {
+    send(
+        config,
+        instruction::set_governing_snapshot(
+            &config.authority.pubkey(),
+            &pubkey(matches, "registrar")?,
+            value(matches, "epoch")?,
+        ),
+    )
+}
+
+pub fn create_snapshot(config: &Config, matches: &ArgMatches) -> CommandResult {
+    let registrar_address = pubkey(matches, "registrar")?;
+    let registrar =
+        Registrar::try_from_slice(&config.rpc_client.get_account_data(&registrar_address)?)?;
+    let epoch = registrar.snapshot_epoch + 1;
+    println!(
+        "Snapshot {}: epoch {}",
+        pda::snapshot_address(&registrar_address, epoch),
+        epoch
+    );
+
+    send(
+        config,
+        instruction::create_snapshot(&config.authority.pubkey(), &registrar_address, epoch),
+    )
+}
+
+pub fn show(rpc_client: &RpcClient, matches: &ArgMatches) -> CommandResult {
+    let registrar_address = pubkey(matches, "registrar")?;
+    let registrar = Registrar::try_from_slice(&rpc_client.get_account_data(&registrar_address)?)?;
+    println!("Registrar {}: {:#?}", registrar_address, registrar);
+
+    let reward_pool_address = pda::reward_pool_address(&registrar_address);
+    if let Ok(data) = rpc_client.get_account_data(&reward_pool_address) {
+        let reward_pool = RewardPool::try_from_slice(&data)?;
+        println!("RewardPool {}: {:#?}", reward_pool_address, reward_pool);
+    }
+
+    if let Some(voter_authority) = pubkey_of(matches, "voter-authority") {
+        let voter_address = pda::voter_address(&registrar_address, &voter_authority);
+        let voter = Voter::try_from_slice(&rpc_client.get_account_data(&voter_address)?)?;
+        println!("Voter {}: {:#?}", voter_address, voter);
+
+        let record_address = pda::voter_weight_record_address(&registrar_address, &voter_authority);
+        let record: VoterWeightRecord =
+            try_from_slice_unchecked(&rpc_client.get_account_data(&record_address)?)?;
+        println!("VoterWeightRecord {}: {:#?}", record_address, record);
+    }
+
+    Ok(())
+}
+
+fn now() -> Result<i64, Error> {
+    Ok(i64::try_from(
+        SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    )?)
+}
+
+pub fn voter_weight(rpc_client: &RpcClient, matches: &ArgMatches) -> CommandResult {
+    let registrar = pubkey(matches, "registrar")?;
+    let voter_authority = pubkey(matches, "voter-authority")?;
+    let delegators = pubkeys_of(matches, "delegator").unwrap_or_default();
+    let at_ts = match value_of(matches, "unix-timestamp") {
+        Some(at_ts) => at_ts,
+        None => now()?,
+    };
+
+    //read-only, so the unsigned transaction is only simulated
+    let ix = instruction::get_voter_weight(&registrar, &voter_authority, &delegators, at_ts);
+    let tx = Transaction::new_with_payer(&[ix], Some(&voter_authority));
+    let result = rpc_client
+        .simulate_transaction_with_config(
+            &tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?
+        .value;
+    if let Some(err) = result.err {
+        return Err(format!("simulation failed: {err}, logs: {:#?}", result.logs).into());
+    }
+
+    let weight = VoterWeight::from_logs(&result.logs.unwrap_or_default())
+        .ok_or("no return data in the simulation logs")?;
+    println!("{:#?}", weight);
+
+    Ok(())
+}
+
+/// Account data of a `solana account --output json` dump, base64 text or raw bytes
+fn account_data(input: &[u8]) -> Result<Vec<u8>, Error> {
+    let text = match std::str::from_utf8(input) {
+        Ok(text) => text.trim(),
+        Err(_) => return Ok(input.to_vec()),
+    };
+
+    if text.starts_with('{') {
+        let json: serde_json::Value = serde_json::from_str(text)?;
+        let data = &json["account"]["data"];
+        return match (data[0].as_str(), data[1].as_str()) {
+            (Some(encoded), Some("base64")) => Ok(base64::decode(encoded)?),
+            _ => Err("expected `account.data` as [\"<base64>\", \"base64\"]".into()),
+        };
+    }
+
+    Ok(base64::decode(text).unwrap_or_else(|_| input.to_vec()))
+}
+
+pub fn inspect(rpc_client: &RpcClient, matches: &ArgMatches) -> CommandResult {
+    let address = pubkey_of(matches, "address");
+    let data = if let Some(address) = address {
+        rpc_client.get_account_data(&address)?
+    } else {
+        let mut input = Vec::new();
+        match matches.value_of("input") {
+            Some("-") | None => std::io::stdin().read_to_end(&mut input)?,
+            Some(path) => std::fs::File::open(path)?.read_to_end(&mut input)?,
+        };
+        account_data(&input)?
+    };
+
+    let curr_ts = match value_of(matches, "unix-timestamp") {
+        Some(curr_ts) => curr_ts,
+        None => now()?,
+    };
+    // the weight factors of a voter's deposits live in its registrar
+    let registrar = match Voter::try_from_slice(&data) {
+        Ok(voter) if address.is_some() => Some(Registrar::try_from_slice(
+            &rpc_client.get_account_data(&voter.registrar)?,
+        )?),
+        _ => None,
+    };
+    let view = inspect_account(&data, curr_ts, registrar.as_ref())
+        .map_err(|err| format!("unknown account type: {}", err))?;
+
+    match matches.value_of("output") {
+        Some("yaml") => print!("{}", serde_yaml::to_string(&view)?),
+        _ => println!("{}", serde_json::to_string_pretty(&view)?),
+    }
+
+    Ok(())
+}
+
+pub fn idl() -> CommandResult {
+    println!("{}", serde_json::to_string_pretty(&idl::idl())?);
+    Ok(())
+}
@@ -0,0 +1,553 @@
+use clap::{crate_description, crate_name, crate_version, App, AppSettings, Arg, SubCommand};
+use solana_clap_utils::input_validators::{
+    is_keypair, is_parsable, is_url_or_moniker, is_valid_pubkey, normalize_to_url_if_moniker,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, Keypair},
+};
+
+mod command;
+
+pub type Error = Box<dyn std::error::Error>;
+pub type CommandResult = Result<(), Error>;
+
+pub struct Config {
+    pub rpc_client: RpcClient,
+    // pays the fees and rent of every transaction
+    pub payer: Keypair,
+    // registrar authority, or voter authority for voter commands
+    pub authority: Keypair,
+}
+
+fn pubkey_arg<'a, 'b>(name: &'a str, help: &'a str) -> Arg<'a, 'b> {
+    Arg::with_name(name)
+        .long(name)
+        .value_name("PUBKEY")
+        .takes_value(true)
+        .required(true)
+        .validator(is_valid_pubkey)
+        .help(help)
+}
+
+fn parsable_arg<'a, 'b, T>(name: &'a str, value_name: &'a str, help: &'a str) -> Arg<'a, 'b>
+where
+    T: std::str::FromStr + 'static,
+    T::Err: std::fmt::Display,
+{
+    Arg::with_name(name)
+        .long(name)
+        .value_name(value_name)
+        .takes_value(true)
+        .required(true)
+        .validator(is_parsable::<T>)
+        .help(help)
+}
+
+fn source_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("source")
+        .long("source")
+        .value_name("TOKEN_ACCOUNT")
+        .takes_value(true)
+        .validator(is_valid_pubkey)
+        .help("Token account to deposit from [default: ATA of the authority]")
+}
+
+const LOCKUP_KINDS: [&str; 4] = ["daily", "cliff", "constant", "monthly"];
+
+fn lockup_policy_args<'a, 'b>() -> [Arg<'a, 'b>; 3] {
+    [
+        parsable_arg::<u16>("min-lockup-days", "DAYS", "Shortest lockup of a deposit")
+            .required(false)
+            .default_value("0"),
+        parsable_arg::<u16>("max-lockup-days", "DAYS", "Longest lockup of a deposit")
+            .required(false)
+            .default_value("1095"),
+        Arg::with_name("lockup-kinds")
+            .long("lockup-kinds")
+            .value_name("KINDS")
+            .takes_value(true)
+            .multiple(true)
+            .use_delimiter(true)
+            .possible_values(&LOCKUP_KINDS)
+            .default_value("daily,cliff,constant,monthly")
+            .help("Lockup kinds a deposit may use"),
+    ]
+}
+
+/// Weight factors of an exchange rate, scaled by 10^9
+fn vote_weight_args<'a, 'b>() -> [Arg<'a, 'b>; 3] {
+    [
+        parsable_arg::<u64>(
+            "baseline-vote-weight-scaled-factor",
+            "FACTOR",
+            "Weight of every deposited token, locked or not",
+        )
+        .required(false)
+        .default_value("0"),
+        parsable_arg::<u64>(
+            "max-extra-lockup-vote-weight-scaled-factor",
+            "FACTOR",
+            "Extra weight of a token locked for the lockup saturation",
+        )
+        .required(false)
+        .default_value("1000000000"),
+        parsable_arg::<u64>(
+            "lockup-saturation-secs",
+            "SECS",
+            "Lockup earning the whole extra weight, at most 1095 days",
+        )
+        .required(false)
+        .default_value("94608000"),
+    ]
+}
+
+fn voter_authority_arg<'a, 'b>() -> Arg<'a, 'b> {
+    pubkey_arg(
+        "voter-authority",
+        "Deposit on behalf of this voter, funded by the authority [default: authority]",
+    )
+    .required(false)
+}
+
+fn app<'a, 'b>() -> App<'a, 'b> {
+    App::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("config_file")
+                .short("C")
+                .long("config")
+                .value_name("PATH")
+                .takes_value(true)
+                .global(true)
+                .help("Solana CLI configuration file"),
+        )
+        .arg(
+            Arg::with_name("url")
+                .short("u")
+                .long("url")
+                .value_name("URL_OR_MONIKER")
+                .takes_value(true)
+                .global(true)
+                .validator(is_url_or_moniker)
+                .help("JSON RPC URL, or moniker such as `localhost` for solana-test-validator"),
+        )
+        .arg(
+            Arg::with_name("keypair")
+                .short("k")
+                .long("keypair")
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .global(true)
+                .validator(is_keypair)
+                .help("Fee payer keypair file [default: keypair of the CLI config]"),
+        )
+        .arg(
+            Arg::with_name("authority")
+                .long("authority")
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .global(true)
+                .validator(is_keypair)
+                .help("Authority keypair file [default: fee payer]"),
+        )
+        .subcommand(
+            SubCommand::with_name("create-registrar")
+                .about("Create the registrar of a realm")
+                .arg(pubkey_arg("realm", "Governance realm"))
+                .arg(pubkey_arg("community-mint", "Community mint of the realm"))
+                .arg(parsable_arg::<u8>(
+                    "rate-decimals",
+                    "DECIMALS",
+                    "Decimals of the common currency deposits are converted into",
+                ))
+                .args(&lockup_policy_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("add-exchange-rate")
+                .about("Accept deposits of a mint at the given exchange rate")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(pubkey_arg("mint", "Deposit mint"))
+                .arg(parsable_arg::<u64>(
+                    "rate",
+                    "RATE",
+                    "Exchange rate into the common currency, scaled by 10^9",
+                ))
+                .arg(parsable_arg::<u16>(
+                    "idx",
+                    "INDEX",
+                    "Slot of the exchange rate",
+                ))
+                .arg(
+                    parsable_arg::<u8>("decimals", "DECIMALS", "Decimals of the deposit mint")
+                        .required(false)
+                        .help("Decimals of the deposit mint [default: read from the mint]"),
+                )
+                .arg(
+                    Arg::with_name("voting-token")
+                        .long("voting-token")
+                        .value_name("POLICY")
+                        .takes_value(true)
+                        .possible_values(&["frozen", "transferable", "disabled"])
+                        .default_value("frozen")
+                        .help(
+                            "Voting token minted for deposits: frozen in the voter's account, \
+                             transferable (deposits carry no voter weight), or none",
+                        ),
+                )
+                .args(&vote_weight_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("create-voter")
+                .about("Create the voter and voter weight record of the authority")
+                .arg(pubkey_arg("registrar", "Registrar address")),
+        )
+        .subcommand(
+            SubCommand::with_name("deposit")
+                .about("Lock tokens into a new deposit entry")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(pubkey_arg("mint", "Deposit mint"))
+                .arg(parsable_arg::<u64>(
+                    "amount",
+                    "AMOUNT",
+                    "Amount in native units",
+                ))
+                .arg(
+                    Arg::with_name("kind")
+                        .long("kind")
+                        .value_name("KIND")
+                        .takes_value(true)
+                        .possible_values(&LOCKUP_KINDS)
+                        .default_value("cliff")
+                        .help("Lockup kind"),
+                )
+                .arg(parsable_arg::<i32>("days", "DAYS", "Lockup period in days"))
+                .arg(source_arg())
+                .arg(
+                    Arg::with_name("funded-by-payer")
+                        .long("funded-by-payer")
+                        .takes_value(false)
+                        .help("Fund the deposit from the fee payer's tokens, the authority co-signing as the voter"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("update-deposit")
+                .about("Add tokens to an existing deposit entry")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(pubkey_arg("mint", "Deposit mint"))
+                .arg(parsable_arg::<u8>(
+                    "deposit-idx",
+                    "INDEX",
+                    "Index of the deposit entry",
+                ))
+                .arg(parsable_arg::<u64>(
+                    "amount",
+                    "AMOUNT",
+                    "Amount in native units",
+                ))
+                .arg(source_arg())
+                .arg(voter_authority_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("withdraw")
+                .about("Withdraw unlocked tokens of a deposit entry")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(pubkey_arg("mint", "Deposit mint"))
+                .arg(parsable_arg::<u8>(
+                    "deposit-idx",
+                    "INDEX",
+                    "Index of the deposit entry",
+                ))
+                .arg(parsable_arg::<u64>(
+                    "amount",
+                    "AMOUNT",
+                    "Amount in native units",
+                ))
+                .arg(
+                    Arg::with_name("destination")
+                        .long("destination")
+                        .value_name("TOKEN_ACCOUNT")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help(
+                            "Token account to withdraw to, owned by the holder of \
+                             transferable voting tokens [default: ATA of the authority, \
+                             or of the voting token owner]",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("voting-token")
+                        .long("voting-token")
+                        .value_name("TOKEN_ACCOUNT")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help(
+                            "Account to burn transferable voting tokens from, \
+                             its owner having approved the authority as delegate \
+                             [default: ATA of the authority]",
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("start-unlock")
+                .about("Start the countdown of a constant lockup deposit")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(parsable_arg::<u8>(
+                    "deposit-idx",
+                    "INDEX",
+                    "Index of the deposit entry",
+                )),
+        )
+        .subcommand(
+            SubCommand::with_name("set-early-unlock-policy")
+                .about("Set the early unlock penalty and the treasury receiving it")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(parsable_arg::<u16>(
+                    "penalty-bps",
+                    "BPS",
+                    "Penalty at the maximum lockup, in basis points",
+                ))
+                .arg(
+                    pubkey_arg(
+                        "treasury",
+                        "Owner of the token accounts receiving penalties",
+                    )
+                    .required(false)
+                    .help(
+                        "Owner of the token accounts receiving penalties \
+                             [default: none, early unlock disabled]",
+                    ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("early-unlock")
+                .about("Release a whole deposit entry now, paying the early unlock penalty")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(pubkey_arg("mint", "Deposit mint"))
+                .arg(parsable_arg::<u8>(
+                    "deposit-idx",
+                    "INDEX",
+                    "Index of the deposit entry",
+                )),
+        )
+        .subcommand(
+            SubCommand::with_name("create-reward-pool")
+                .about("Share fees of a mint among the voters by weight")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(pubkey_arg("mint", "Fee mint")),
+        )
+        .subcommand(
+            SubCommand::with_name("fund-rewards")
+                .about("Transfer fees into the reward pool")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(parsable_arg::<u64>(
+                    "amount",
+                    "AMOUNT",
+                    "Amount in native units",
+                ))
+                .arg(
+                    source_arg().help("Token account to fund from [default: ATA of the authority]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("claim-rewards")
+                .about("Claim the rewards of the authority to its ATA of the fee mint")
+                .arg(pubkey_arg("registrar", "Registrar address")),
+        )
+        .subcommand(
+            SubCommand::with_name("refresh-reward-weight")
+                .about("Stake the current weight of a voter in the reward pool")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(
+                    pubkey_arg(
+                        "voter-authority",
+                        "Authority of the voter [default: authority]",
+                    )
+                    .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("create-snapshot")
+                .about("Open the next snapshot epoch, proposals count the voter weight as of now")
+                .arg(pubkey_arg("registrar", "Registrar address")),
+        )
+        .subcommand(
+            SubCommand::with_name("set-governing-snapshot")
+                .about("Count the voter weight as of a snapshot, or live again with epoch 0")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(parsable_arg::<u64>(
+                    "epoch",
+                    "EPOCH",
+                    "Epoch of one of the latest snapshots, 0 for the live weight",
+                )),
+        )
+        .subcommand(
+            SubCommand::with_name("set-lockup-policy")
+                .about("Set the lockups accepted for new deposits")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .args(&lockup_policy_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("set-delegation-cooldown")
+                .about("Set the time a delegation change takes to move the weight of a voter")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(parsable_arg::<u32>(
+                    "cooldown-secs",
+                    "SECONDS",
+                    "Cooldown, at least the max voting time of every governance of the realm",
+                ))
+                .arg(
+                    pubkey_arg("governance", "Governance of the realm")
+                        .multiple(true)
+                        .number_of_values(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show")
+                .about("Print the registrar and, optionally, a voter")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(
+                    pubkey_arg("voter-authority", "Authority of the voter to print")
+                        .required(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("voter-weight")
+                .about("Simulate GetVoterWeight: the voter weight now and at a given time")
+                .arg(pubkey_arg("registrar", "Registrar address"))
+                .arg(pubkey_arg("voter-authority", "Authority of the voter"))
+                .arg(
+                    pubkey_arg("delegator", "Authority of a voter delegating to it")
+                        .required(false)
+                        .multiple(true)
+                        .number_of_values(1),
+                )
+                .arg(
+                    parsable_arg::<i64>(
+                        "unix-timestamp",
+                        "UNIX_TIMESTAMP",
+                        "Time to project the weight at",
+                    )
+                    .required(false)
+                    .help("Time to project the weight at [default: now]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("inspect")
+                .about("Decode a Registrar, Voter or VoterWeightRecord account")
+                .arg(
+                    Arg::with_name("input")
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .required_unless("address")
+                        .help(
+                            "Account dump: `solana account --output json`, base64, \
+                             or raw bytes. `-` reads stdin",
+                        ),
+                )
+                .arg(
+                    pubkey_arg("address", "Fetch the account from the RPC instead")
+                        .required(false)
+                        .conflicts_with("input"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(&["json", "yaml"])
+                        .default_value("json")
+                        .help("Output format"),
+                )
+                .arg(
+                    parsable_arg::<i64>(
+                        "unix-timestamp",
+                        "UNIX_TIMESTAMP",
+                        "Time to derive days left and voting power at",
+                    )
+                    .required(false)
+                    .help("Time to derive days left and voting power at [default: now]"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("idl").about(
+                "Print the JSON IDL of the program: accounts, instructions, events and errors",
+            ),
+        )
+}
+
+fn cli_config(matches: &clap::ArgMatches) -> solana_cli_config::Config {
+    matches
+        .value_of("config_file")
+        .or_else(|| solana_cli_config::CONFIG_FILE.as_deref())
+        .and_then(|path| solana_cli_config::Config::load(path).ok())
+        .unwrap_or_default()
+}
+
+fn rpc_client(matches: &clap::ArgMatches, cli_config: &solana_cli_config::Config) -> RpcClient {
+    let url =
+        normalize_to_url_if_moniker(matches.value_of("url").unwrap_or(&cli_config.json_rpc_url));
+    RpcClient::new_with_commitment(url, CommitmentConfig::confirmed())
+}
+
+fn config(matches: &clap::ArgMatches) -> Result<Config, Error> {
+    let cli_config = cli_config(matches);
+
+    let payer_path = matches
+        .value_of("keypair")
+        .unwrap_or(&cli_config.keypair_path);
+    let authority_path = matches.value_of("authority").unwrap_or(payer_path);
+
+    Ok(Config {
+        rpc_client: rpc_client(matches, &cli_config),
+        payer: read_keypair_file(payer_path)
+            .map_err(|err| format!("payer keypair {}: {}", payer_path, err))?,
+        authority: read_keypair_file(authority_path)
+            .map_err(|err| format!("authority keypair {}: {}", authority_path, err))?,
+    })
+}
+
+fn main() {
+    let matches = app().get_matches();
+    let (sub_command, sub_matches) = matches.subcommand();
+    let sub_matches = sub_matches.expect("subcommand is required");
+
+    let result = match sub_command {
+        // read-only, no keypair required
+        "show" => command::show(&rpc_client(&matches, &cli_config(&matches)), sub_matches),
+        "voter-weight" => {
+            command::voter_weight(&rpc_client(&matches, &cli_config(&matches)), sub_matches)
+        }
+        "inspect" => command::inspect(&rpc_client(&matches, &cli_config(&matches)), sub_matches),
+        "idl" => command::idl(),
+        _ => config(&matches).and_then(|config| match sub_command {
+            "create-registrar" => command::create_registrar(&config, sub_matches),
+            "add-exchange-rate" => command::add_exchange_rate(&config, sub_matches),
+            "create-voter" => command::create_voter(&config, sub_matches),
+            "deposit" => command::deposit(&config, sub_matches),
+            "update-deposit" => command::update_deposit(&config, sub_matches),
+            "withdraw" => command::withdraw(&config, sub_matches),
+            "start-unlock" => command::start_unlock(&config, sub_matches),
+            "set-early-unlock-policy" => command::set_early_unlock_policy(&config, sub_matches),
+            "early-unlock" => command::early_unlock(&config, sub_matches),
+            "create-reward-pool" => command::create_reward_pool(&config, sub_matches),
+            "fund-rewards" => command::fund_rewards(&config, sub_matches),
+            "claim-rewards" => command::claim_rewards(&config, sub_matches),
+            "refresh-reward-weight" => command::refresh_reward_weight(&config, sub_matches),
+            "create-snapshot" => command::create_snapshot(&config, sub_matches),
+            "set-governing-snapshot" => command::set_governing_snapshot(&config, sub_matches),
+            "set-lockup-policy" => command::set_lockup_policy(&config, sub_matches),
+            "set-delegation-cooldown" => command::set_delegation_cooldown(&config, sub_matches),
+            _ => unreachable!(),
+        }),
+    };
+
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
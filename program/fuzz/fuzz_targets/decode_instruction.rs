@@ -0,0 +1,17 @@
+#![no_main]
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use libfuzzer_sys::fuzz_target;
+use program::{error::GovError, instruction::GovInstruction};
+
+// every decodable input is the exact encoding of its instruction
+fuzz_target!(|data: &[u8]| {
+    program_fuzz::install_syscall_stubs();
+    match GovInstruction::try_from_slice(data) {
+        Ok(ix) => assert_eq!(ix.try_to_vec().unwrap(), data),
+        Err(_) => assert!(matches!(
+            GovInstruction::unpack(data),
+            Err(GovError::InvalidInstruction)
+        )),
+    }
+});
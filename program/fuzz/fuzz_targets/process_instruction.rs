@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use program_fuzz::FuzzInput;
+
+// no panic for any instruction data and any set of accounts
+fuzz_target!(|input: FuzzInput| {
+    let _ = input.process();
+});
@@ -0,0 +1,573 @@
+//! Mock `AccountInfo` harness of the fuzz targets.
+//!
+//! Accounts are drawn from a small pool of keys (the program PDAs of a fixed
+//! realm, a few authorities and mints), so generated inputs pass the PDA and
+//! owner checks often enough to reach the processors. Account data is either
+//! raw bytes or a well-formed Registrar, Voter, VoterWeightRecord, RewardPool,
+//! Snapshot, mint or token account. Every CPI fails, as no other program is loaded.
+use std::sync::{
+    atomic::{AtomicI64, AtomicU64, Ordering},
+    Once, OnceLock,
+};
+
+use arbitrary::Arbitrary;
+use borsh::BorshSerialize;
+use program::{
+    client::{idl::INSTRUCTION_ACCOUNTS, pda},
+    state::{
+        Checkpoint, DepositEntry, ExchangeRateEntry, Lockup, LockupKind, LockupPolicy, Registrar,
+        RewardPool, Snapshot, Voter, VotingTokenPolicy, SNAPSHOT_HISTORY,
+    },
+    utils::account_info_util::Acc,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program, sysvar,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_governance_addin_api::voter_weight::VoterWeightRecord;
+
+const REALM: Pubkey = Pubkey::new_from_array([1; 32]);
+const COMMUNITY_MINT: Pubkey = Pubkey::new_from_array([2; 32]);
+const AUTHORITIES: [Pubkey; 3] = [
+    Pubkey::new_from_array([3; 32]),
+    Pubkey::new_from_array([4; 32]),
+    Pubkey::new_from_array([5; 32]),
+];
+const MINTS: [Pubkey; 2] = [
+    Pubkey::new_from_array([6; 32]),
+    Pubkey::new_from_array([7; 32]),
+];
+
+#[derive(Arbitrary, Debug, Clone, Copy)]
+pub enum FuzzKey {
+    Program,
+    SystemProgram,
+    TokenProgram,
+    AssociatedTokenProgram,
+    Rent,
+    Realm,
+    CommunityMint,
+    Registrar,
+    RewardPool,
+    // snapshot of the epoch `1 + e % SNAPSHOT_HISTORY`
+    Snapshot(u8),
+    Authority(u8),
+    Voter(u8),
+    VoterWeightRecord(u8),
+    Mint(u8),
+    VotingMint(u8),
+    ExchangeVault(u8),
+    // ATA of the reward pool for the mint
+    RewardVault(u8),
+    // ATA of the authority for the mint
+    DepositToken(u8, u8),
+    // ATA of the authority for the voting mint of the mint
+    VotingToken(u8, u8),
+    Other([u8; 32]),
+}
+
+// PDAs and ATAs of the pool, derived once as `find_program_address` dominates a run
+struct Derived {
+    registrar: Pubkey,
+    reward_pool: Pubkey,
+    snapshots: Vec<Pubkey>,
+    voters: Vec<Pubkey>,
+    voter_weight_records: Vec<Pubkey>,
+    voting_mints: Vec<Pubkey>,
+    exchange_vaults: Vec<Pubkey>,
+    reward_vaults: Vec<Pubkey>,
+    deposit_tokens: Vec<Vec<Pubkey>>,
+    voting_tokens: Vec<Vec<Pubkey>>,
+}
+
+fn derived() -> &'static Derived {
+    static DERIVED: OnceLock<Derived> = OnceLock::new();
+    DERIVED.get_or_init(|| {
+        let registrar = pda::registrar_address(&REALM);
+        let per_mint = |f: &dyn Fn(&Pubkey) -> Pubkey| MINTS.iter().map(f).collect::<Vec<_>>();
+        Derived {
+            registrar,
+            reward_pool: pda::reward_pool_address(&registrar),
+            snapshots: (1..=SNAPSHOT_HISTORY as u64)
+                .map(|epoch| pda::snapshot_address(&registrar, epoch))
+                .collect(),
+            voters: AUTHORITIES
+                .iter()
+                .map(|a| pda::voter_address(&registrar, a))
+                .collect(),
+            voter_weight_records: AUTHORITIES
+                .iter()
+                .map(|a| pda::voter_weight_record_address(&registrar, a))
+                .collect(),
+            voting_mints: per_mint(&|m| pda::voting_mint_address(&registrar, m)),
+            exchange_vaults: per_mint(&|m| pda::exchange_vault_address(&registrar, m)),
+            reward_vaults: per_mint(&|m| pda::reward_vault_address(&registrar, m)),
+            deposit_tokens: AUTHORITIES
+                .iter()
+                .map(|a| per_mint(&|m| get_associated_token_address(a, m)))
+                .collect(),
+            voting_tokens: AUTHORITIES
+                .iter()
+                .map(|a| per_mint(&|m| pda::voting_token_address(&registrar, a, m)))
+                .collect(),
+        }
+    })
+}
+
+impl FuzzKey {
+    pub fn pubkey(self) -> Pubkey {
+        let derived = derived();
+        let authority = |a: u8| usize::from(a) % AUTHORITIES.len();
+        let mint = |m: u8| usize::from(m) % MINTS.len();
+        match self {
+            Self::Program => program::id(),
+            Self::SystemProgram => system_program::id(),
+            Self::TokenProgram => spl_token::id(),
+            Self::AssociatedTokenProgram => spl_associated_token_account::id(),
+            Self::Rent => sysvar::rent::id(),
+            Self::Realm => REALM,
+            Self::CommunityMint => COMMUNITY_MINT,
+            Self::Registrar => derived.registrar,
+            Self::RewardPool => derived.reward_pool,
+            Self::Snapshot(e) => derived.snapshots[usize::from(e) % SNAPSHOT_HISTORY],
+            Self::Authority(a) => AUTHORITIES[authority(a)],
+            Self::Voter(a) => derived.voters[authority(a)],
+            Self::VoterWeightRecord(a) => derived.voter_weight_records[authority(a)],
+            Self::Mint(m) => MINTS[mint(m)],
+            Self::VotingMint(m) => derived.voting_mints[mint(m)],
+            Self::ExchangeVault(m) => derived.exchange_vaults[mint(m)],
+            Self::RewardVault(m) => derived.reward_vaults[mint(m)],
+            Self::DepositToken(a, m) => derived.deposit_tokens[authority(a)][mint(m)],
+            Self::VotingToken(a, m) => derived.voting_tokens[authority(a)][mint(m)],
+            Self::Other(bytes) => Pubkey::new_from_array(bytes),
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug, Clone, Copy)]
+pub enum FuzzOwner {
+    Program,
+    System,
+    Token,
+    Other(FuzzKey),
+}
+
+impl FuzzOwner {
+    fn pubkey(self) -> Pubkey {
+        match self {
+            Self::Program => program::id(),
+            Self::System => system_program::id(),
+            Self::Token => spl_token::id(),
+            Self::Other(key) => key.pubkey(),
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug, Clone, Copy)]
+pub struct FuzzRate {
+    pub mint: FuzzKey,
+    pub rate: u64,
+    pub decimals: u8,
+    pub voting_token: u8,
+    pub baseline_vote_weight_scaled_factor: u64,
+    pub max_extra_lockup_vote_weight_scaled_factor: u64,
+    pub lockup_saturation_secs: u64,
+}
+
+#[derive(Arbitrary, Debug, Clone, Copy)]
+pub struct FuzzDeposit {
+    pub is_used: bool,
+    pub rate_idx: u8,
+    pub amount_deposited: u64,
+    pub amount_withdrawn: u64,
+    pub amount_scaled: u64,
+    pub kind: u8,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub unlocking: bool,
+}
+
+#[derive(Arbitrary, Debug, Clone)]
+pub enum FuzzData {
+    Raw(Vec<u8>),
+    Registrar {
+        authority: FuzzKey,
+        bump: u8,
+        rates: [Option<FuzzRate>; 2],
+        rate_decimals: u8,
+        early_unlock_penalty_bps: u16,
+        treasury: Option<FuzzKey>,
+        snapshot_epoch: u8,
+        snapshot_ts: [i64; SNAPSHOT_HISTORY],
+        min_lockup_days: u16,
+        max_lockup_days: u16,
+        allowed_kinds: u8,
+        delegation_cooldown_secs: u32,
+        governing_snapshot_epoch: u8,
+    },
+    Voter {
+        authority: FuzzKey,
+        delegate: Option<FuzzKey>,
+        deposits: Vec<FuzzDeposit>,
+        reward_weight: u64,
+        reward_index: u128,
+        rewards_owed: u64,
+        checkpoint_epoch: u8,
+        checkpoints: [(u8, u64, Option<FuzzKey>); SNAPSHOT_HISTORY],
+        delegated_at: i64,
+    },
+    VoterWeightRecord {
+        owner: FuzzKey,
+        voter_weight: u64,
+    },
+    RewardPool {
+        reward_mint: FuzzKey,
+        bump: u8,
+        total_weight: u64,
+        reward_index: u128,
+        undistributed: u64,
+    },
+    Snapshot {
+        epoch: u8,
+        ts: i64,
+        bump: u8,
+    },
+    Mint {
+        authority: Option<FuzzKey>,
+        supply: u64,
+        decimals: u8,
+    },
+    Token {
+        mint: FuzzKey,
+        owner: FuzzKey,
+        amount: u64,
+        frozen: bool,
+    },
+}
+
+impl FuzzData {
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::Raw(data) => data.clone(),
+            Self::Registrar {
+                authority,
+                bump,
+                rates,
+                rate_decimals,
+                early_unlock_penalty_bps,
+                treasury,
+                snapshot_epoch,
+                snapshot_ts,
+                min_lockup_days,
+                max_lockup_days,
+                allowed_kinds,
+                delegation_cooldown_secs,
+                governing_snapshot_epoch,
+            } => Registrar {
+                authority: authority.pubkey(),
+                realm: REALM,
+                realm_community_mint: COMMUNITY_MINT,
+                bump: *bump,
+                rates: rates.map(|rate| rate.map(ExchangeRateEntry::from).unwrap_or_default()),
+                rate_decimals: *rate_decimals,
+                early_unlock_penalty_bps: *early_unlock_penalty_bps,
+                treasury: treasury.map(FuzzKey::pubkey).unwrap_or_default(),
+                snapshot_epoch: u64::from(*snapshot_epoch),
+                snapshot_ts: *snapshot_ts,
+                lockup_policy: LockupPolicy {
+                    min_lockup_days: *min_lockup_days,
+                    max_lockup_days: *max_lockup_days,
+                    allowed_kinds: *allowed_kinds,
+                },
+                delegation_cooldown_secs: *delegation_cooldown_secs,
+                governing_snapshot_epoch: u64::from(*governing_snapshot_epoch),
+            }
+            .try_to_vec()
+            .unwrap(),
+            Self::Voter {
+                authority,
+                delegate,
+                deposits,
+                reward_weight,
+                reward_index,
+                rewards_owed,
+                checkpoint_epoch,
+                checkpoints,
+                delegated_at,
+            } => {
+                let mut voter = Voter {
+                    authority: authority.pubkey(),
+                    registrar: FuzzKey::Registrar.pubkey(),
+                    delegate: delegate.map(FuzzKey::pubkey).unwrap_or_default(),
+                    reward_weight: *reward_weight,
+                    reward_index: *reward_index,
+                    rewards_owed: *rewards_owed,
+                    checkpoint_epoch: u64::from(*checkpoint_epoch),
+                    checkpoints: checkpoints.map(|(epoch, weight, holder)| Checkpoint {
+                        epoch: u64::from(epoch),
+                        weight,
+                        holder: holder.map(FuzzKey::pubkey).unwrap_or_default(),
+                    }),
+                    delegated_at: *delegated_at,
+                    ..Voter::default()
+                };
+                for (d_er, deposit) in voter.deposits.iter_mut().zip(deposits) {
+                    *d_er = DepositEntry::from(*deposit);
+                }
+                voter.try_to_vec().unwrap()
+            }
+            Self::VoterWeightRecord {
+                owner,
+                voter_weight,
+            } => {
+                let record = VoterWeightRecord {
+                    account_discriminator: VoterWeightRecord::ACCOUNT_DISCRIMINATOR,
+                    realm: REALM,
+                    governing_token_mint: COMMUNITY_MINT,
+                    governing_token_owner: owner.pubkey(),
+                    voter_weight: *voter_weight,
+                    voter_weight_expiry: None,
+                    weight_action: None,
+                    weight_action_target: None,
+                    reserved: [0; 8],
+                };
+                let mut data = vec![0; record.get_max_size().unwrap()];
+                record.serialize(&mut data.as_mut_slice()).unwrap();
+                data
+            }
+            Self::RewardPool {
+                reward_mint,
+                bump,
+                total_weight,
+                reward_index,
+                undistributed,
+            } => RewardPool {
+                registrar: FuzzKey::Registrar.pubkey(),
+                reward_mint: reward_mint.pubkey(),
+                bump: *bump,
+                total_weight: *total_weight,
+                reward_index: *reward_index,
+                undistributed: *undistributed,
+            }
+            .try_to_vec()
+            .unwrap(),
+            Self::Snapshot { epoch, ts, bump } => Snapshot {
+                registrar: FuzzKey::Registrar.pubkey(),
+                epoch: u64::from(*epoch),
+                ts: *ts,
+                bump: *bump,
+            }
+            .try_to_vec()
+            .unwrap(),
+            Self::Mint {
+                authority,
+                supply,
+                decimals,
+            } => {
+                let mint = spl_token::state::Mint {
+                    mint_authority: authority.map(FuzzKey::pubkey).into(),
+                    supply: *supply,
+                    decimals: *decimals,
+                    is_initialized: true,
+                    freeze_authority: authority.map(FuzzKey::pubkey).into(),
+                };
+                let mut data = vec![0; spl_token::state::Mint::LEN];
+                mint.pack_into_slice(&mut data);
+                data
+            }
+            Self::Token {
+                mint,
+                owner,
+                amount,
+                frozen,
+            } => {
+                let account = spl_token::state::Account {
+                    mint: mint.pubkey(),
+                    owner: owner.pubkey(),
+                    amount: *amount,
+                    delegate: COption::None,
+                    state: if *frozen {
+                        spl_token::state::AccountState::Frozen
+                    } else {
+                        spl_token::state::AccountState::Initialized
+                    },
+                    is_native: COption::None,
+                    delegated_amount: 0,
+                    close_authority: COption::None,
+                };
+                let mut data = vec![0; spl_token::state::Account::LEN];
+                account.pack_into_slice(&mut data);
+                data
+            }
+        }
+    }
+}
+
+impl From<FuzzRate> for ExchangeRateEntry {
+    fn from(rate: FuzzRate) -> Self {
+        Self {
+            mint: rate.mint.pubkey(),
+            rate: rate.rate,
+            decimals: rate.decimals,
+            voting_token: match rate.voting_token % 3 {
+                0 => VotingTokenPolicy::Frozen,
+                1 => VotingTokenPolicy::Transferable,
+                _ => VotingTokenPolicy::Disabled,
+            },
+            baseline_vote_weight_scaled_factor: rate.baseline_vote_weight_scaled_factor,
+            max_extra_lockup_vote_weight_scaled_factor: rate
+                .max_extra_lockup_vote_weight_scaled_factor,
+            lockup_saturation_secs: rate.lockup_saturation_secs,
+        }
+    }
+}
+
+impl From<FuzzDeposit> for DepositEntry {
+    fn from(deposit: FuzzDeposit) -> Self {
+        Self {
+            is_used: deposit.is_used,
+            rate_idx: deposit.rate_idx,
+            amount_deposited: deposit.amount_deposited,
+            amount_withdrawn: deposit.amount_withdrawn,
+            amount_scaled: deposit.amount_scaled,
+            lockup: Lockup {
+                kind: match deposit.kind % 4 {
+                    0 => LockupKind::Daily,
+                    1 => LockupKind::Cliff,
+                    2 => LockupKind::Constant,
+                    _ => LockupKind::Monthly,
+                },
+                start_ts: deposit.start_ts,
+                end_ts: deposit.end_ts,
+                unlocking: deposit.unlocking,
+                padding: [0; 15],
+            },
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzAccount {
+    pub key: FuzzKey,
+    pub owner: FuzzOwner,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub lamports: u64,
+    pub data: FuzzData,
+}
+
+#[derive(Arbitrary, Debug, Clone)]
+pub struct FuzzInput {
+    pub unix_timestamp: i64,
+    pub slot: u64,
+    pub accounts: Vec<FuzzAccount>,
+    // Borsh tag of the instruction, wrapped into the known variants.
+    // Unknown tags are covered by the `decode_instruction` target
+    pub tag: u8,
+    pub args: Vec<u8>,
+}
+
+struct FuzzStubs;
+
+static STUBS: Once = Once::new();
+static SLOT: AtomicU64 = AtomicU64::new(0);
+static UNIX_TIMESTAMP: AtomicI64 = AtomicI64::new(0);
+
+impl SyscallStubs for FuzzStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_invoke_signed(
+        &self,
+        _instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        Err(ProgramError::IncorrectProgramId)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: SLOT.load(Ordering::Relaxed),
+            unix_timestamp: UNIX_TIMESTAMP.load(Ordering::Relaxed),
+            ..Clock::default()
+        };
+        // SAFETY: `Clock::get` passes a pointer to a `Clock`
+        unsafe { *var_addr.cast::<Clock>() = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        // SAFETY: `Rent::get` passes a pointer to a `Rent`
+        unsafe { *var_addr.cast::<Rent>() = Rent::default() };
+        SUCCESS
+    }
+}
+
+/// Replace the default syscall stubs: quiet logs, a clock driven by the input
+/// and failing CPIs
+pub fn install_syscall_stubs() {
+    STUBS.call_once(|| {
+        set_syscall_stubs(Box::new(FuzzStubs));
+    });
+}
+
+impl FuzzInput {
+    /// Run the instruction through `processor::process`.
+    /// A key passed several times is the same account, as in the runtime
+    pub fn process(&self) -> ProgramResult {
+        install_syscall_stubs();
+        SLOT.store(self.slot, Ordering::Relaxed);
+        UNIX_TIMESTAMP.store(self.unix_timestamp, Ordering::Relaxed);
+
+        let keys: Vec<Pubkey> = self.accounts.iter().map(|a| a.key.pubkey()).collect();
+        let mut storage: Vec<(Pubkey, Pubkey, u64, Vec<u8>, &FuzzAccount)> = Vec::new();
+        for (key, account) in keys.iter().zip(&self.accounts) {
+            if !storage.iter().any(|(k, ..)| k == key) {
+                storage.push((
+                    *key,
+                    account.owner.pubkey(),
+                    account.lamports,
+                    account.data.to_bytes(),
+                    account,
+                ));
+            }
+        }
+        let unique: Vec<AccountInfo> = storage
+            .iter_mut()
+            .map(|(key, owner, lamports, data, account)| {
+                AccountInfo::new(
+                    key,
+                    account.is_signer,
+                    account.is_writable,
+                    lamports,
+                    data,
+                    owner,
+                    false,
+                    0,
+                )
+            })
+            .collect();
+        let accounts: Vec<AccountInfo> = keys
+            .iter()
+            .map(|key| unique.iter().find(|a| a.key == key).unwrap().clone())
+            .collect();
+
+        let variants = u8::try_from(INSTRUCTION_ACCOUNTS.len()).unwrap();
+        let mut data = vec![self.tag % variants];
+        data.extend_from_slice(&self.args);
+
+        program::processor::process(&program::id(), &accounts, &data)
+    }
+}
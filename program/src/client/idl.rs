@@ -0,0 +1,653 @@
+//! JSON IDL of the program, generated from the `BorshSchema` derives.
+//!
+//! Instruction arguments, accounts and events are described by their Borsh
+//! declarations, every declaration that is not a primitive is defined in
+//! `types`. Account lists of the instructions are kept in
+//! `INSTRUCTION_ACCOUNTS` and checked against the instruction builders.
+use std::collections::{BTreeMap, HashMap};
+
+use borsh::{
+    schema::{Declaration, Definition, Fields},
+    BorshSchema, BorshSerialize,
+};
+use num_traits::FromPrimitive;
+use serde_derive::Serialize;
+use solana_program::pubkey::Pubkey;
+use spl_governance_addin_api::voter_weight::VoterWeightRecord;
+
+use crate::{
+    error::GovError,
+    events::GovEvent,
+    instruction::GovInstruction,
+    state::{Registrar, RewardPool, Snapshot, Voter},
+    utils::account_info_util::Acc,
+};
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Idl {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub program_id: String,
+    pub instructions: Vec<IdlInstruction>,
+    pub accounts: Vec<IdlAccount>,
+    pub events: Vec<IdlEvent>,
+    pub errors: Vec<IdlError>,
+    // definition of every non-primitive declaration, keyed by the declaration
+    pub types: BTreeMap<Declaration, IdlType>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct IdlInstruction {
+    pub name: String,
+    // Borsh tag, first byte of the instruction data
+    pub discriminant: u8,
+    pub args: Vec<IdlField>,
+    pub accounts: Vec<IdlAccountMeta>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdlAccountMeta {
+    pub name: &'static str,
+    pub writable: bool,
+    pub signer: bool,
+    // any number of accounts may be passed from this position on
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub remaining: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct IdlAccount {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub ty: Declaration,
+    // allocated size in bytes
+    pub size: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct IdlEvent {
+    pub name: String,
+    // Borsh tag, first byte of the `Program data:` payload
+    pub discriminant: u8,
+    pub fields: Vec<IdlField>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct IdlError {
+    pub code: u32,
+    pub name: String,
+    pub msg: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct IdlField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: Declaration,
+}
+
+/// `borsh::schema::Definition`, with tuple struct fields named by their position
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IdlType {
+    Array { length: u32, elements: Declaration },
+    Sequence { elements: Declaration },
+    Tuple { elements: Vec<Declaration> },
+    Enum { variants: Vec<IdlField> },
+    Struct { fields: Vec<IdlField> },
+}
+
+const fn signer(name: &'static str, writable: bool) -> IdlAccountMeta {
+    IdlAccountMeta {
+        name,
+        writable,
+        signer: true,
+        remaining: false,
+    }
+}
+
+const fn writable(name: &'static str) -> IdlAccountMeta {
+    IdlAccountMeta {
+        name,
+        writable: true,
+        signer: false,
+        remaining: false,
+    }
+}
+
+const fn readonly(name: &'static str) -> IdlAccountMeta {
+    IdlAccountMeta {
+        name,
+        writable: false,
+        signer: false,
+        remaining: false,
+    }
+}
+
+/// Accounts of every `GovInstruction`, indexed by discriminant
+pub const INSTRUCTION_ACCOUNTS: &[&[IdlAccountMeta]] = &[
+    // CreateRegistrar
+    &[
+        signer("payer", true),
+        signer("authority", true),
+        readonly("realm"),
+        readonly("realm_community_mint"),
+        writable("registrar"),
+        readonly("system_program"),
+    ],
+    // CreateExchangeRate
+    &[
+        signer("authority", true),
+        writable("registrar"),
+        readonly("deposit_mint"),
+        writable("exchange_vault"),
+        writable("voting_mint"),
+        readonly("token_program"),
+        readonly("system_program"),
+        readonly("associated_token_program"),
+        readonly("rent"),
+    ],
+    // CreateVoter
+    &[
+        signer("payer", true),
+        signer("authority", true),
+        readonly("registrar"),
+        writable("voter"),
+        writable("voter_weight_record"),
+        readonly("system_program"),
+        readonly("token_program"),
+        readonly("associated_token_program"),
+        readonly("rent"),
+    ],
+    // CreateDeposit
+    &[
+        signer("authority", true),
+        readonly("registrar"),
+        writable("voter"),
+        readonly("deposit_mint"),
+        writable("voting_mint"),
+        writable("deposit_token"),
+        writable("exchange_vault"),
+        writable("voting_token"),
+        readonly("system_program"),
+        readonly("token_program"),
+        readonly("associated_token_program"),
+        readonly("rent"),
+        writable("reward_pool"),
+    ],
+    // UpdateDeposit
+    &[
+        signer("authority", true),
+        readonly("registrar"),
+        writable("voter"),
+        readonly("deposit_mint"),
+        writable("voting_mint"),
+        writable("deposit_token"),
+        writable("exchange_vault"),
+        writable("voting_token"),
+        readonly("system_program"),
+        readonly("token_program"),
+        readonly("associated_token_program"),
+        readonly("rent"),
+        writable("reward_pool"),
+    ],
+    // SetDelegate
+    &[
+        signer("authority", false),
+        readonly("registrar"),
+        writable("voter"),
+    ],
+    // UpdateVoterWeightRecord
+    &[
+        readonly("registrar"),
+        readonly("voter"),
+        writable("voter_weight_record"),
+        IdlAccountMeta {
+            name: "delegators",
+            writable: false,
+            signer: false,
+            remaining: true,
+        },
+    ],
+    // CreateDepositFor
+    &[
+        signer("depositor", true),
+        readonly("registrar"),
+        writable("voter"),
+        readonly("deposit_mint"),
+        writable("voting_mint"),
+        writable("deposit_token"),
+        writable("exchange_vault"),
+        writable("voting_token"),
+        readonly("system_program"),
+        readonly("token_program"),
+        readonly("associated_token_program"),
+        readonly("rent"),
+        writable("reward_pool"),
+        signer("voter_authority", false),
+    ],
+    // UpdateDepositFor
+    &[
+        signer("depositor", true),
+        readonly("registrar"),
+        writable("voter"),
+        readonly("deposit_mint"),
+        writable("voting_mint"),
+        writable("deposit_token"),
+        writable("exchange_vault"),
+        writable("voting_token"),
+        readonly("system_program"),
+        readonly("token_program"),
+        readonly("associated_token_program"),
+        readonly("rent"),
+        writable("reward_pool"),
+        readonly("voter_authority"),
+    ],
+    // Withdraw
+    &[
+        signer("authority", false),
+        readonly("registrar"),
+        writable("voter"),
+        readonly("deposit_mint"),
+        writable("voting_mint"),
+        writable("destination"),
+        writable("exchange_vault"),
+        writable("voting_token"),
+        readonly("token_program"),
+        writable("reward_pool"),
+    ],
+    // GetVoterWeight
+    &[
+        readonly("registrar"),
+        readonly("voter"),
+        IdlAccountMeta {
+            name: "delegators",
+            writable: false,
+            signer: false,
+            remaining: true,
+        },
+    ],
+    // StartUnlock
+    &[
+        signer("authority", false),
+        readonly("registrar"),
+        writable("voter"),
+        writable("reward_pool"),
+    ],
+    // SetEarlyUnlockPolicy
+    &[signer("authority", false), writable("registrar")],
+    // EarlyUnlock
+    &[
+        signer("authority", false),
+        readonly("registrar"),
+        writable("voter"),
+        readonly("deposit_mint"),
+        writable("voting_mint"),
+        writable("destination"),
+        writable("exchange_vault"),
+        writable("voting_token"),
+        readonly("token_program"),
+        writable("reward_pool"),
+        writable("treasury_token"),
+    ],
+    // CreateRewardPool
+    &[
+        signer("authority", true),
+        readonly("registrar"),
+        writable("reward_pool"),
+        readonly("reward_mint"),
+        writable("reward_vault"),
+        readonly("system_program"),
+        readonly("token_program"),
+        readonly("associated_token_program"),
+        readonly("rent"),
+    ],
+    // FundRewards
+    &[
+        signer("funder", false),
+        readonly("registrar"),
+        writable("reward_pool"),
+        writable("source"),
+        writable("reward_vault"),
+        readonly("token_program"),
+    ],
+    // ClaimRewards
+    &[
+        signer("authority", false),
+        readonly("registrar"),
+        writable("voter"),
+        writable("reward_pool"),
+        writable("reward_vault"),
+        writable("destination"),
+        readonly("token_program"),
+    ],
+    // CreateSnapshot
+    &[
+        signer("authority", true),
+        writable("registrar"),
+        writable("snapshot"),
+        readonly("system_program"),
+    ],
+    // UpdateSnapshotVoterWeightRecord
+    &[
+        readonly("registrar"),
+        readonly("snapshot"),
+        readonly("voter"),
+        writable("voter_weight_record"),
+        IdlAccountMeta {
+            name: "delegators",
+            writable: false,
+            signer: false,
+            remaining: true,
+        },
+    ],
+    // SetLockupPolicy
+    &[signer("authority", false), writable("registrar")],
+    // RefreshRewardWeight
+    &[
+        readonly("registrar"),
+        writable("voter"),
+        writable("reward_pool"),
+    ],
+    // SetDelegationCooldown
+    &[
+        signer("authority", false),
+        writable("registrar"),
+        readonly("realm"),
+        IdlAccountMeta {
+            name: "governances",
+            writable: false,
+            signer: false,
+            remaining: true,
+        },
+    ],
+    // SetGoverningSnapshot
+    &[signer("authority", false), writable("registrar")],
+    // SetTimeOffset
+    #[cfg(feature = "time-offset")]
+    &[signer("authority", false), writable("registrar")],
+];
+
+/// Build the IDL of the program
+pub fn idl() -> Idl {
+    let mut definitions = HashMap::new();
+    GovInstruction::add_definitions_recursively(&mut definitions);
+    GovEvent::add_definitions_recursively(&mut definitions);
+    Registrar::add_definitions_recursively(&mut definitions);
+    Voter::add_definitions_recursively(&mut definitions);
+    RewardPool::add_definitions_recursively(&mut definitions);
+    Snapshot::add_definitions_recursively(&mut definitions);
+    VoterWeightRecord::add_definitions_recursively(&mut definitions);
+
+    let instructions = variants(&GovInstruction::declaration(), &definitions)
+        .into_iter()
+        .zip(INSTRUCTION_ACCOUNTS)
+        .map(|((name, discriminant, args), accounts)| IdlInstruction {
+            name,
+            discriminant,
+            args,
+            accounts: accounts.to_vec(),
+        })
+        .collect();
+    let events = variants(&GovEvent::declaration(), &definitions)
+        .into_iter()
+        .map(|(name, discriminant, fields)| IdlEvent {
+            name,
+            discriminant,
+            fields,
+        })
+        .collect();
+
+    Idl {
+        name: "pol_voting",
+        version: env!("CARGO_PKG_VERSION"),
+        program_id: crate::id().to_string(),
+        instructions,
+        accounts: accounts(),
+        events,
+        errors: (0..)
+            .map_while(GovError::from_u32)
+            .map(|error| IdlError {
+                code: error as u32,
+                name: format!("{error:?}"),
+                msg: error.to_string(),
+            })
+            .collect(),
+        types: definitions
+            .into_iter()
+            .map(|(declaration, definition)| (declaration, definition.into()))
+            .collect(),
+    }
+}
+
+fn accounts() -> Vec<IdlAccount> {
+    let record = VoterWeightRecord {
+        account_discriminator: VoterWeightRecord::ACCOUNT_DISCRIMINATOR,
+        realm: Pubkey::default(),
+        governing_token_mint: Pubkey::default(),
+        governing_token_owner: Pubkey::default(),
+        voter_weight: 0,
+        voter_weight_expiry: None,
+        weight_action: None,
+        weight_action_target: None,
+        reserved: [0; 8],
+    };
+
+    vec![
+        IdlAccount {
+            name: "Registrar",
+            ty: Registrar::declaration(),
+            size: borsh_len(&Registrar::default()),
+        },
+        IdlAccount {
+            name: "Voter",
+            ty: Voter::declaration(),
+            size: borsh_len(&Voter::default()),
+        },
+        IdlAccount {
+            name: "VoterWeightRecord",
+            ty: VoterWeightRecord::declaration(),
+            size: record.get_max_size().unwrap_or_else(|| borsh_len(&record)),
+        },
+        IdlAccount {
+            name: "RewardPool",
+            ty: RewardPool::declaration(),
+            size: borsh_len(&RewardPool::default()),
+        },
+        IdlAccount {
+            name: "Snapshot",
+            ty: Snapshot::declaration(),
+            size: borsh_len(&Snapshot::default()),
+        },
+    ]
+}
+
+fn borsh_len<T: BorshSerialize>(account: &T) -> usize {
+    account.try_to_vec().map_or(0, |data| data.len())
+}
+
+/// Name, tag and fields of every variant of the enum `declaration`
+fn variants(
+    declaration: &Declaration,
+    definitions: &HashMap<Declaration, Definition>,
+) -> Vec<(String, u8, Vec<IdlField>)> {
+    let variants = match definitions.get(declaration) {
+        Some(Definition::Enum { variants }) => variants.as_slice(),
+        _ => &[],
+    };
+
+    variants
+        .iter()
+        .zip(0..=u8::MAX)
+        .map(|((name, variant), tag)| {
+            let fields = match definitions.get(variant) {
+                // `Variant(Event)`: describe the fields of the wrapped struct
+                Some(Definition::Struct {
+                    fields: Fields::UnnamedFields(inner),
+                }) if inner.len() == 1 => match definitions.get(&inner[0]) {
+                    Some(Definition::Struct { fields }) => idl_fields(fields),
+                    _ => idl_fields(&Fields::UnnamedFields(inner.clone())),
+                },
+                Some(Definition::Struct { fields }) => idl_fields(fields),
+                _ => vec![],
+            };
+            (name.clone(), tag, fields)
+        })
+        .collect()
+}
+
+fn idl_fields(fields: &Fields) -> Vec<IdlField> {
+    match fields {
+        Fields::NamedFields(fields) => fields
+            .iter()
+            .map(|(name, ty)| IdlField {
+                name: name.clone(),
+                ty: ty.clone(),
+            })
+            .collect(),
+        Fields::UnnamedFields(fields) => fields
+            .iter()
+            .enumerate()
+            .map(|(idx, ty)| IdlField {
+                name: idx.to_string(),
+                ty: ty.clone(),
+            })
+            .collect(),
+        Fields::Empty => vec![],
+    }
+}
+
+impl From<Definition> for IdlType {
+    fn from(definition: Definition) -> Self {
+        match definition {
+            Definition::Array { length, elements } => Self::Array { length, elements },
+            Definition::Sequence { elements } => Self::Sequence { elements },
+            Definition::Tuple { elements } => Self::Tuple { elements },
+            Definition::Enum { variants } => Self::Enum {
+                variants: variants
+                    .into_iter()
+                    .map(|(name, ty)| IdlField { name, ty })
+                    .collect(),
+            },
+            Definition::Struct { fields } => Self::Struct {
+                fields: idl_fields(&fields),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::instruction::{self, DepositLockup},
+        state::{ExchangeRateEntry, LockupKind, LockupPolicy},
+    };
+
+    #[test]
+    fn accounts_match_instruction_builders() {
+        let (payer, registrar, authority, mint) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let lockup = DepositLockup {
+            kind: LockupKind::Cliff,
+            days: 10,
+        };
+        let ixs = [
+            instruction::create_registrar(
+                &payer,
+                &authority,
+                &Pubkey::new_unique(),
+                &mint,
+                6,
+                LockupPolicy::default(),
+            ),
+            instruction::create_exchange_rate(
+                &authority,
+                &registrar,
+                0,
+                ExchangeRateEntry::default(),
+            ),
+            instruction::create_voter(&payer, &registrar, &authority),
+            instruction::deposit(&registrar, &authority, &mint, 10, lockup),
+            instruction::update_deposit(&registrar, &authority, &mint, 0, 10),
+            instruction::set_delegate(&registrar, &authority, &payer),
+            instruction::update_voter_weight_record(&registrar, &authority, &[payer, mint]),
+            instruction::deposit_for(&registrar, &payer, &authority, &mint, &payer, 10, lockup),
+            instruction::update_deposit_for(&registrar, &payer, &authority, &mint, &payer, 0, 10),
+            instruction::withdraw(&registrar, &authority, &mint, 0, 10),
+            instruction::get_voter_weight(&registrar, &authority, &[payer], 0),
+            instruction::start_unlock(&registrar, &authority, 0),
+            instruction::set_early_unlock_policy(&authority, &registrar, 100, &payer),
+            instruction::early_unlock(&registrar, &authority, &mint, &payer, 0),
+            instruction::create_reward_pool(&authority, &registrar, &mint),
+            instruction::fund_rewards(&payer, &registrar, &mint, &payer, 10),
+            instruction::claim_rewards(&registrar, &authority, &mint),
+            instruction::create_snapshot(&authority, &registrar, 1),
+            instruction::update_snapshot_voter_weight_record(&registrar, &authority, 1, &[payer]),
+            instruction::set_lockup_policy(&authority, &registrar, LockupPolicy::default()),
+            instruction::refresh_reward_weight(&registrar, &authority),
+            instruction::set_delegation_cooldown(&authority, &registrar, &mint, &[payer], 1),
+            instruction::set_governing_snapshot(&authority, &registrar, 1),
+            #[cfg(feature = "time-offset")]
+            instruction::set_time_offset(&authority, &registrar, 0),
+        ];
+
+        let idl = idl();
+        assert_eq!(idl.instructions.len(), ixs.len());
+        assert_eq!(INSTRUCTION_ACCOUNTS.len(), ixs.len());
+        for ix in &ixs {
+            let idl_ix = &idl.instructions[usize::from(ix.data[0])];
+            for (idx, meta) in ix.accounts.iter().enumerate() {
+                let idl_meta = idl_ix.accounts[idx.min(idl_ix.accounts.len() - 1)];
+                assert!(idx < idl_ix.accounts.len() || idl_meta.remaining);
+                assert_eq!(
+                    (meta.is_writable, meta.is_signer),
+                    (idl_meta.writable, idl_meta.signer),
+                    "{} account {}",
+                    idl_ix.name,
+                    idl_meta.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn describes_args_events_and_errors() {
+        let idl = idl();
+
+        let create_deposit = &idl.instructions[3];
+        assert_eq!(create_deposit.name, "CreateDeposit");
+        assert_eq!(
+            create_deposit
+                .args
+                .iter()
+                .map(|arg| (arg.name.as_str(), arg.ty.as_str()))
+                .collect::<Vec<_>>(),
+            [("kind", "LockupKind"), ("amount", "u64"), ("days", "i32")]
+        );
+        assert!(matches!(
+            idl.types.get("LockupKind"),
+            Some(IdlType::Enum { .. })
+        ));
+        assert!(idl.types.contains_key("DepositEntry"));
+        match idl.types.get("ExchangeRateEntry") {
+            Some(IdlType::Struct { fields }) => {
+                assert_eq!(fields.len(), 7);
+                assert_eq!(fields[6].name, "lockup_saturation_secs");
+            }
+            other => panic!("expected a struct, got {other:?}"),
+        }
+
+        assert_eq!(idl.events[0].name, "RegistrarCreated");
+        assert_eq!(idl.events[0].fields[0].name, "registrar");
+        assert_eq!(idl.errors.len(), GovError::SnapshotGoverns as usize + 1);
+        assert_eq!(idl.errors[7].name, "InvalidDepositId");
+        assert_eq!(idl.accounts[2].size, 8 + 32 * 3 + 8 + 9 + 2 + 33 + 8);
+        assert_eq!(idl.accounts[3].size, 32 * 2 + 1 + 8 + 16 + 8);
+        assert_eq!(idl.accounts[4].size, 32 + 8 + 8 + 1);
+    }
+}
@@ -0,0 +1,342 @@
+use borsh::BorshDeserialize;
+use serde_derive::Serialize;
+use solana_program::{
+    borsh::try_from_slice_unchecked, program_error::ProgramError, pubkey::Pubkey,
+};
+use spl_governance_addin_api::voter_weight::VoterWeightRecord;
+
+use crate::{
+    error::GovError,
+    state::{DepositEntry, ExchangeRateEntry, Registrar, RewardPool, Snapshot, Voter},
+};
+
+/// Decoded account of the program, with values derived at a given timestamp
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum AccountView {
+    Registrar(RegistrarView),
+    Voter(VoterView),
+    VoterWeightRecord(VoterWeightRecordView),
+    RewardPool(RewardPoolView),
+    Snapshot(SnapshotView),
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RegistrarView {
+    pub authority: String,
+    pub realm: String,
+    pub realm_community_mint: String,
+    pub bump: u8,
+    pub rate_decimals: u8,
+    pub rates: Vec<ExchangeRateView>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExchangeRateView {
+    pub idx: usize,
+    pub mint: String,
+    pub rate: u64,
+    pub decimals: u8,
+    pub voting_token: String,
+    pub baseline_vote_weight_scaled_factor: u64,
+    pub max_extra_lockup_vote_weight_scaled_factor: u64,
+    pub lockup_saturation_secs: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VoterView {
+    pub authority: String,
+    pub registrar: String,
+    pub delegate: Option<String>,
+    pub voter_bump: u8,
+    pub voter_weight_record_bump: u8,
+    // sum of the voting power of every deposit, whatever its voting token policy
+    pub voting_power: u64,
+    // used entries only
+    pub deposits: Vec<DepositView>,
+    pub reward_weight: u64,
+    pub rewards_owed: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DepositView {
+    pub idx: usize,
+    pub rate_idx: u8,
+    pub amount_deposited: u64,
+    pub amount_withdrawn: u64,
+    pub amount_scaled: u64,
+    pub lockup: LockupView,
+    pub voting_power: u64,
+    pub vested: u64,
+    pub withdrawable: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct LockupView {
+    pub kind: String,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub unlocking: bool,
+    pub total_days: u64,
+    pub days_left: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VoterWeightRecordView {
+    pub realm: String,
+    pub governing_token_mint: String,
+    pub governing_token_owner: String,
+    pub voter_weight: u64,
+    pub voter_weight_expiry: Option<u64>,
+    pub weight_action: Option<String>,
+    pub weight_action_target: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RewardPoolView {
+    pub registrar: String,
+    pub reward_mint: String,
+    pub bump: u8,
+    pub total_weight: u64,
+    // u128, as a string for every output format
+    pub reward_index: String,
+    pub undistributed: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotView {
+    pub registrar: String,
+    pub epoch: u64,
+    pub ts: i64,
+    pub bump: u8,
+}
+
+/// Detect the type of `data` and decode it, deriving time based values at `curr_ts`.
+/// The voting power of a voter follows the exchange rates of `registrar`,
+/// the default weight factors without it
+pub fn inspect_account(
+    data: &[u8],
+    curr_ts: i64,
+    registrar: Option<&Registrar>,
+) -> Result<AccountView, ProgramError> {
+    if data.starts_with(&VoterWeightRecord::ACCOUNT_DISCRIMINATOR) {
+        let record: VoterWeightRecord = try_from_slice_unchecked(data)?;
+        return Ok(AccountView::VoterWeightRecord(record.into()));
+    }
+    // these accounts are sized to their exact Borsh length
+    if let Ok(registrar) = Registrar::try_from_slice(data) {
+        return Ok(AccountView::Registrar(registrar.into()));
+    }
+    if let Ok(voter) = Voter::try_from_slice(data) {
+        let registrar = registrar.copied().unwrap_or_default();
+        return Ok(AccountView::Voter(VoterView::new(
+            &voter, &registrar, curr_ts,
+        )?));
+    }
+    if let Ok(reward_pool) = RewardPool::try_from_slice(data) {
+        return Ok(AccountView::RewardPool(reward_pool.into()));
+    }
+    if let Ok(snapshot) = Snapshot::try_from_slice(data) {
+        return Ok(AccountView::Snapshot(snapshot.into()));
+    }
+
+    Err(ProgramError::InvalidAccountData)
+}
+
+fn optional_key(key: &Pubkey) -> Option<String> {
+    (*key != Pubkey::default()).then(|| key.to_string())
+}
+
+impl From<Registrar> for RegistrarView {
+    fn from(registrar: Registrar) -> Self {
+        Self {
+            authority: registrar.authority.to_string(),
+            realm: registrar.realm.to_string(),
+            realm_community_mint: registrar.realm_community_mint.to_string(),
+            bump: registrar.bump,
+            rate_decimals: registrar.rate_decimals,
+            rates: registrar
+                .rates
+                .iter()
+                .enumerate()
+                .filter(|(_, er)| **er != ExchangeRateEntry::default())
+                .map(|(idx, er)| ExchangeRateView {
+                    idx,
+                    mint: er.mint.to_string(),
+                    rate: er.rate,
+                    decimals: er.decimals,
+                    voting_token: format!("{:?}", er.voting_token),
+                    baseline_vote_weight_scaled_factor: er.baseline_vote_weight_scaled_factor,
+                    max_extra_lockup_vote_weight_scaled_factor: er
+                        .max_extra_lockup_vote_weight_scaled_factor,
+                    lockup_saturation_secs: er.lockup_saturation_secs,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl VoterView {
+    pub fn new(voter: &Voter, registrar: &Registrar, curr_ts: i64) -> Result<Self, ProgramError> {
+        let deposits: Vec<DepositView> = voter
+            .deposits
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| d.is_used)
+            .map(|(idx, d)| DepositView::new(idx, d, registrar.rate(d.rate_idx)?, curr_ts))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            authority: voter.authority.to_string(),
+            registrar: voter.registrar.to_string(),
+            delegate: optional_key(&voter.delegate),
+            voter_bump: voter.voter_bump,
+            voter_weight_record_bump: voter.voter_weight_record_bump,
+            voting_power: deposits
+                .iter()
+                .try_fold(0_u64, |acc, d| acc.checked_add(d.voting_power))
+                .ok_or(GovError::MathOverflow)?,
+            deposits,
+            reward_weight: voter.reward_weight,
+            rewards_owed: voter.rewards_owed,
+        })
+    }
+}
+
+impl From<RewardPool> for RewardPoolView {
+    fn from(reward_pool: RewardPool) -> Self {
+        Self {
+            registrar: reward_pool.registrar.to_string(),
+            reward_mint: reward_pool.reward_mint.to_string(),
+            bump: reward_pool.bump,
+            total_weight: reward_pool.total_weight,
+            reward_index: reward_pool.reward_index.to_string(),
+            undistributed: reward_pool.undistributed,
+        }
+    }
+}
+
+impl From<Snapshot> for SnapshotView {
+    fn from(snapshot: Snapshot) -> Self {
+        Self {
+            registrar: snapshot.registrar.to_string(),
+            epoch: snapshot.epoch,
+            ts: snapshot.ts,
+            bump: snapshot.bump,
+        }
+    }
+}
+
+impl DepositView {
+    pub fn new(
+        idx: usize,
+        d: &DepositEntry,
+        er: &ExchangeRateEntry,
+        curr_ts: i64,
+    ) -> Result<Self, ProgramError> {
+        Ok(Self {
+            idx,
+            rate_idx: d.rate_idx,
+            amount_deposited: d.amount_deposited,
+            amount_withdrawn: d.amount_withdrawn,
+            amount_scaled: d.amount_scaled,
+            lockup: LockupView {
+                kind: format!("{:?}", d.lockup.kind),
+                start_ts: d.lockup.start_ts,
+                end_ts: d.lockup.end_ts,
+                unlocking: d.lockup.unlocking,
+                total_days: d.lockup.total_days(),
+                days_left: d.lockup.days_left(curr_ts),
+            },
+            voting_power: d.voting_power(er, curr_ts)?,
+            vested: d.vested(curr_ts),
+            withdrawable: d.withdrawable(curr_ts),
+        })
+    }
+}
+
+impl From<VoterWeightRecord> for VoterWeightRecordView {
+    fn from(record: VoterWeightRecord) -> Self {
+        Self {
+            realm: record.realm.to_string(),
+            governing_token_mint: record.governing_token_mint.to_string(),
+            governing_token_owner: record.governing_token_owner.to_string(),
+            voter_weight: record.voter_weight,
+            voter_weight_expiry: record.voter_weight_expiry,
+            weight_action: record.weight_action.map(|action| format!("{action:?}")),
+            weight_action_target: record.weight_action_target.map(|key| key.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{Lockup, LockupKind, SCALED_FACTOR_BASE, SECS_PER_DAY};
+    use borsh::BorshSerialize;
+
+    #[test]
+    fn detects_voter_and_derives_values() {
+        let mut voter = Voter {
+            authority: Pubkey::new_unique(),
+            registrar: Pubkey::new_unique(),
+            ..Voter::default()
+        };
+        voter.deposits[2] = DepositEntry {
+            is_used: true,
+            amount_deposited: 10,
+            amount_scaled: 1_095,
+            lockup: Lockup {
+                kind: LockupKind::Cliff,
+                start_ts: 0,
+                end_ts: 100 * SECS_PER_DAY,
+                unlocking: false,
+                padding: [0; 15],
+            },
+            ..DepositEntry::default()
+        };
+
+        let view = inspect_account(&voter.try_to_vec().unwrap(), 10 * SECS_PER_DAY, None).unwrap();
+        let view = match view {
+            AccountView::Voter(view) => view,
+            other => panic!("expected a voter, got {other:?}"),
+        };
+        assert_eq!(view.delegate, None);
+        assert_eq!(view.voting_power, 90);
+        assert_eq!(view.deposits.len(), 1);
+        assert_eq!(view.deposits[0].idx, 2);
+        assert_eq!(view.deposits[0].lockup.days_left, 90);
+
+        // a baseline of one counts every scaled token on top of the lockup curve
+        let mut registrar = Registrar::default();
+        registrar.rates[0].baseline_vote_weight_scaled_factor = SCALED_FACTOR_BASE;
+        let data = voter.try_to_vec().unwrap();
+        match inspect_account(&data, 10 * SECS_PER_DAY, Some(&registrar)).unwrap() {
+            AccountView::Voter(view) => assert_eq!(view.voting_power, 1_095 + 90),
+            other => panic!("expected a voter, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn detects_registrar_and_rejects_garbage() {
+        let registrar = Registrar::default().try_to_vec().unwrap();
+        assert!(matches!(
+            inspect_account(&registrar, 0, None),
+            Ok(AccountView::Registrar(_))
+        ));
+        let reward_pool = RewardPool::default().try_to_vec().unwrap();
+        assert!(matches!(
+            inspect_account(&reward_pool, 0, None),
+            Ok(AccountView::RewardPool(_))
+        ));
+        let snapshot = Snapshot::default().try_to_vec().unwrap();
+        assert!(matches!(
+            inspect_account(&snapshot, 0, None),
+            Ok(AccountView::Snapshot(_))
+        ));
+        assert_eq!(
+            inspect_account(&[1, 2, 3], 0, None),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
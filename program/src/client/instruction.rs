@@ -0,0 +1,456 @@
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+
+use super::pda::{
+    exchange_vault_address, find_registrar_address, find_voter_address,
+    find_voter_weight_record_address, find_voting_mint_address, reward_pool_address,
+    reward_vault_address, snapshot_address, voter_address, voter_weight_record_address,
+    voting_token_address,
+};
+use crate::{
+    instruction::{self, DepositKeys, WithdrawKeys},
+    state::{ExchangeRateEntry, LockupKind, LockupPolicy},
+};
+
+/// Lockup requested for a new deposit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepositLockup {
+    pub kind: LockupKind,
+    pub days: i32,
+}
+
+pub fn create_registrar(
+    payer: &Pubkey,
+    authority: &Pubkey,
+    realm: &Pubkey,
+    realm_community_mint: &Pubkey,
+    rate_decimals: u8,
+    lockup_policy: LockupPolicy,
+) -> Instruction {
+    let (registrar, registrar_bump) = find_registrar_address(realm);
+    instruction::create_registrar(
+        payer,
+        authority,
+        realm,
+        realm_community_mint,
+        rate_decimals,
+        lockup_policy,
+        &registrar,
+        registrar_bump,
+    )
+}
+
+/// Deposit mint is taken from `er.mint`
+pub fn create_exchange_rate(
+    authority: &Pubkey,
+    registrar: &Pubkey,
+    idx: u16,
+    er: ExchangeRateEntry,
+) -> Instruction {
+    let (voting_mint, voting_mint_bump) = find_voting_mint_address(registrar, &er.mint);
+    instruction::create_exchange_rate(
+        authority,
+        registrar,
+        &er.mint,
+        &exchange_vault_address(registrar, &er.mint),
+        &voting_mint,
+        voting_mint_bump,
+        idx,
+        er,
+    )
+}
+
+pub fn create_voter(payer: &Pubkey, registrar: &Pubkey, voter_authority: &Pubkey) -> Instruction {
+    let (voter, voter_bump) = find_voter_address(registrar, voter_authority);
+    let (voter_weight_record, voter_weight_record_bump) =
+        find_voter_weight_record_address(registrar, voter_authority);
+    instruction::create_voter(
+        payer,
+        voter_authority,
+        registrar,
+        &voter,
+        voter_bump,
+        &voter_weight_record,
+        voter_weight_record_bump,
+    )
+}
+
+/// New deposit funded from the authority's ATA of `mint`
+pub fn deposit(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    lockup: DepositLockup,
+) -> Instruction {
+    let deposit_token = get_associated_token_address(voter_authority, mint);
+    deposit_from(
+        registrar,
+        voter_authority,
+        mint,
+        &deposit_token,
+        amount,
+        lockup,
+    )
+}
+
+/// New deposit funded from any token account of `mint` owned by the authority
+pub fn deposit_from(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    deposit_token: &Pubkey,
+    amount: u64,
+    lockup: DepositLockup,
+) -> Instruction {
+    let keys = deposit_keys(
+        registrar,
+        voter_authority,
+        voter_authority,
+        mint,
+        deposit_token,
+    );
+    instruction::create_deposit(&keys, lockup.kind, amount, lockup.days)
+}
+
+/// Top up the deposit entry `deposit_idx` from the authority's ATA of `mint`
+pub fn update_deposit(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    deposit_idx: u8,
+    amount: u64,
+) -> Instruction {
+    let deposit_token = get_associated_token_address(voter_authority, mint);
+    update_deposit_from(
+        registrar,
+        voter_authority,
+        mint,
+        &deposit_token,
+        deposit_idx,
+        amount,
+    )
+}
+
+pub fn update_deposit_from(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    deposit_token: &Pubkey,
+    deposit_idx: u8,
+    amount: u64,
+) -> Instruction {
+    let keys = deposit_keys(
+        registrar,
+        voter_authority,
+        voter_authority,
+        mint,
+        deposit_token,
+    );
+    instruction::update_deposit(&keys, deposit_idx, amount)
+}
+
+/// New deposit of `voter_authority`, funded from a token account of `mint` owned by
+/// `depositor`. Both sign
+pub fn deposit_for(
+    registrar: &Pubkey,
+    depositor: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    deposit_token: &Pubkey,
+    amount: u64,
+    lockup: DepositLockup,
+) -> Instruction {
+    let keys = deposit_keys(registrar, depositor, voter_authority, mint, deposit_token);
+    instruction::create_deposit_for(&keys, voter_authority, lockup.kind, amount, lockup.days)
+}
+
+/// Top up the deposit entry `deposit_idx` of `voter_authority` on its behalf
+pub fn update_deposit_for(
+    registrar: &Pubkey,
+    depositor: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    deposit_token: &Pubkey,
+    deposit_idx: u8,
+    amount: u64,
+) -> Instruction {
+    let keys = deposit_keys(registrar, depositor, voter_authority, mint, deposit_token);
+    instruction::update_deposit_for(&keys, voter_authority, deposit_idx, amount)
+}
+
+/// Accounts of a deposit of `voter_authority` in `mint`, funded by `depositor`
+fn deposit_keys(
+    registrar: &Pubkey,
+    depositor: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    deposit_token: &Pubkey,
+) -> DepositKeys {
+    DepositKeys {
+        depositor: *depositor,
+        registrar: *registrar,
+        voter: voter_address(registrar, voter_authority),
+        deposit_mint: *mint,
+        voting_mint: find_voting_mint_address(registrar, mint).0,
+        deposit_token: *deposit_token,
+        exchange_vault: exchange_vault_address(registrar, mint),
+        voting_token: voting_token_address(registrar, voter_authority, mint),
+        reward_pool: reward_pool_address(registrar),
+    }
+}
+
+/// Withdraw unlocked tokens of the entry `deposit_idx` to the authority's ATA of `mint`
+pub fn withdraw(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    deposit_idx: u8,
+    amount: u64,
+) -> Instruction {
+    let destination = get_associated_token_address(voter_authority, mint);
+    withdraw_to(
+        registrar,
+        voter_authority,
+        mint,
+        &destination,
+        deposit_idx,
+        amount,
+    )
+}
+
+pub fn withdraw_to(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    deposit_idx: u8,
+    amount: u64,
+) -> Instruction {
+    let keys = withdraw_keys(registrar, voter_authority, mint, destination);
+    instruction::withdraw(&keys, deposit_idx, amount)
+}
+
+/// Accounts of a withdrawal of `voter_authority` in `mint` to `destination`,
+/// burning from its voting token ATA.
+///
+/// Replace `voting_token` to redeem transferable voting tokens held elsewhere,
+/// with a `destination` of their owner
+pub fn withdraw_keys(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+) -> WithdrawKeys {
+    WithdrawKeys {
+        authority: *voter_authority,
+        registrar: *registrar,
+        voter: voter_address(registrar, voter_authority),
+        deposit_mint: *mint,
+        voting_mint: find_voting_mint_address(registrar, mint).0,
+        destination: *destination,
+        exchange_vault: exchange_vault_address(registrar, mint),
+        voting_token: voting_token_address(registrar, voter_authority, mint),
+        reward_pool: reward_pool_address(registrar),
+    }
+}
+
+pub fn set_delegate(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    delegate: &Pubkey,
+) -> Instruction {
+    instruction::set_delegate(
+        voter_authority,
+        registrar,
+        &voter_address(registrar, voter_authority),
+        delegate,
+    )
+}
+
+/// `delegators` are the authorities of the voters delegating to `voter_authority`
+pub fn update_voter_weight_record(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    delegators: &[Pubkey],
+) -> Instruction {
+    let delegators: Vec<Pubkey> = delegators
+        .iter()
+        .map(|delegator| voter_address(registrar, delegator))
+        .collect();
+    instruction::update_voter_weight_record(
+        registrar,
+        &voter_address(registrar, voter_authority),
+        &voter_weight_record_address(registrar, voter_authority),
+        &delegators,
+    )
+}
+
+/// Simulate to read the weight of `voter_authority` now and at `at_ts`
+pub fn get_voter_weight(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    delegators: &[Pubkey],
+    at_ts: i64,
+) -> Instruction {
+    let delegators: Vec<Pubkey> = delegators
+        .iter()
+        .map(|delegator| voter_address(registrar, delegator))
+        .collect();
+    instruction::get_voter_weight(
+        registrar,
+        &voter_address(registrar, voter_authority),
+        &delegators,
+        at_ts,
+    )
+}
+
+pub fn start_unlock(registrar: &Pubkey, voter_authority: &Pubkey, deposit_idx: u8) -> Instruction {
+    instruction::start_unlock(
+        voter_authority,
+        registrar,
+        &voter_address(registrar, voter_authority),
+        &reward_pool_address(registrar),
+        deposit_idx,
+    )
+}
+
+pub fn set_early_unlock_policy(
+    authority: &Pubkey,
+    registrar: &Pubkey,
+    penalty_bps: u16,
+    treasury: &Pubkey,
+) -> Instruction {
+    instruction::set_early_unlock_policy(authority, registrar, penalty_bps, treasury)
+}
+
+/// Release the entry `deposit_idx` to the authority's ATA of `mint`,
+/// the penalty going to the `treasury` ATA of `mint`
+pub fn early_unlock(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    mint: &Pubkey,
+    treasury: &Pubkey,
+    deposit_idx: u8,
+) -> Instruction {
+    let destination = get_associated_token_address(voter_authority, mint);
+    let keys = withdraw_keys(registrar, voter_authority, mint, &destination);
+    instruction::early_unlock(
+        &keys,
+        &get_associated_token_address(treasury, mint),
+        deposit_idx,
+    )
+}
+
+pub fn create_reward_pool(
+    authority: &Pubkey,
+    registrar: &Pubkey,
+    reward_mint: &Pubkey,
+) -> Instruction {
+    instruction::create_reward_pool(
+        authority,
+        registrar,
+        &reward_pool_address(registrar),
+        reward_mint,
+        &reward_vault_address(registrar, reward_mint),
+    )
+}
+
+/// Fund the reward pool from `source`, a token account of `reward_mint` owned by `funder`
+pub fn fund_rewards(
+    funder: &Pubkey,
+    registrar: &Pubkey,
+    reward_mint: &Pubkey,
+    source: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    instruction::fund_rewards(
+        funder,
+        registrar,
+        &reward_pool_address(registrar),
+        source,
+        &reward_vault_address(registrar, reward_mint),
+        amount,
+    )
+}
+
+/// Claim the rewards of the voter to the authority's ATA of `reward_mint`
+pub fn claim_rewards(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    reward_mint: &Pubkey,
+) -> Instruction {
+    instruction::claim_rewards(
+        voter_authority,
+        registrar,
+        &voter_address(registrar, voter_authority),
+        &reward_pool_address(registrar),
+        &reward_vault_address(registrar, reward_mint),
+        &get_associated_token_address(voter_authority, reward_mint),
+    )
+}
+
+/// Open the snapshot `epoch`, the one after `Registrar::snapshot_epoch`
+pub fn create_snapshot(authority: &Pubkey, registrar: &Pubkey, epoch: u64) -> Instruction {
+    instruction::create_snapshot(authority, registrar, &snapshot_address(registrar, epoch))
+}
+
+pub fn update_snapshot_voter_weight_record(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    epoch: u64,
+    delegators: &[Pubkey],
+) -> Instruction {
+    let delegators: Vec<Pubkey> = delegators
+        .iter()
+        .map(|delegator| voter_address(registrar, delegator))
+        .collect();
+    instruction::update_snapshot_voter_weight_record(
+        registrar,
+        &snapshot_address(registrar, epoch),
+        &voter_address(registrar, voter_authority),
+        &voter_weight_record_address(registrar, voter_authority),
+        &delegators,
+    )
+}
+
+pub fn set_lockup_policy(
+    authority: &Pubkey,
+    registrar: &Pubkey,
+    lockup_policy: LockupPolicy,
+) -> Instruction {
+    instruction::set_lockup_policy(authority, registrar, lockup_policy)
+}
+
+pub fn refresh_reward_weight(registrar: &Pubkey, voter_authority: &Pubkey) -> Instruction {
+    instruction::refresh_reward_weight(
+        registrar,
+        &voter_address(registrar, voter_authority),
+        &reward_pool_address(registrar),
+    )
+}
+
+pub fn set_delegation_cooldown(
+    authority: &Pubkey,
+    registrar: &Pubkey,
+    realm: &Pubkey,
+    governances: &[Pubkey],
+    delegation_cooldown_secs: u32,
+) -> Instruction {
+    instruction::set_delegation_cooldown(
+        authority,
+        registrar,
+        realm,
+        governances,
+        delegation_cooldown_secs,
+    )
+}
+
+pub fn set_governing_snapshot(authority: &Pubkey, registrar: &Pubkey, epoch: u64) -> Instruction {
+    instruction::set_governing_snapshot(authority, registrar, epoch)
+}
+
+#[cfg(feature = "time-offset")]
+pub fn set_time_offset(authority: &Pubkey, registrar: &Pubkey, time_offset: i64) -> Instruction {
+    instruction::set_time_offset(authority, registrar, time_offset)
+}
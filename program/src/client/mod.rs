@@ -0,0 +1,7 @@
+//! Off-chain helpers: PDA derivation, instruction builders
+//! which fill in every derived account, account inspection
+//! and the JSON IDL of the program.
+pub mod idl;
+pub mod inspect;
+pub mod instruction;
+pub mod pda;
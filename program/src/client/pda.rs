@@ -0,0 +1,181 @@
+use solana_program::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::state::{ExchangeRateEntry, Registrar, RewardPool, Snapshot, Voter};
+
+/// Registrar PDA of the realm, with its bump
+pub fn find_registrar_address(realm: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&Registrar::get_seeds(realm), &crate::id())
+}
+
+pub fn registrar_address(realm: &Pubkey) -> Pubkey {
+    find_registrar_address(realm).0
+}
+
+/// Voting mint PDA minted against deposits of `deposit_mint`, with its bump
+pub fn find_voting_mint_address(registrar: &Pubkey, deposit_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &ExchangeRateEntry::get_voting_mint_seeds(registrar, deposit_mint),
+        &crate::id(),
+    )
+}
+
+pub fn voting_mint_address(registrar: &Pubkey, deposit_mint: &Pubkey) -> Pubkey {
+    find_voting_mint_address(registrar, deposit_mint).0
+}
+
+/// Voter PDA of the authority, with its bump
+pub fn find_voter_address(registrar: &Pubkey, voter_authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &Voter::get_voter_seeds(registrar, voter_authority),
+        &crate::id(),
+    )
+}
+
+pub fn voter_address(registrar: &Pubkey, voter_authority: &Pubkey) -> Pubkey {
+    find_voter_address(registrar, voter_authority).0
+}
+
+/// VoterWeightRecord PDA of the authority, with its bump
+pub fn find_voter_weight_record_address(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &Voter::get_voter_weight_seeds(registrar, voter_authority),
+        &crate::id(),
+    )
+}
+
+pub fn voter_weight_record_address(registrar: &Pubkey, voter_authority: &Pubkey) -> Pubkey {
+    find_voter_weight_record_address(registrar, voter_authority).0
+}
+
+/// ATA of the registrar holding every deposit of `deposit_mint`
+pub fn exchange_vault_address(registrar: &Pubkey, deposit_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(registrar, deposit_mint)
+}
+
+/// ATA of the voter holding the voting tokens minted for `deposit_mint`
+pub fn voting_token_address(
+    registrar: &Pubkey,
+    voter_authority: &Pubkey,
+    deposit_mint: &Pubkey,
+) -> Pubkey {
+    get_associated_token_address(
+        voter_authority,
+        &voting_mint_address(registrar, deposit_mint),
+    )
+}
+
+/// RewardPool PDA of the registrar, with its bump
+pub fn find_reward_pool_address(registrar: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&RewardPool::get_seeds(registrar), &crate::id())
+}
+
+pub fn reward_pool_address(registrar: &Pubkey) -> Pubkey {
+    find_reward_pool_address(registrar).0
+}
+
+/// ATA of the reward pool holding the fees of `reward_mint`
+pub fn reward_vault_address(registrar: &Pubkey, reward_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(&reward_pool_address(registrar), reward_mint)
+}
+
+/// Snapshot PDA of the registrar epoch, with its bump
+pub fn find_snapshot_address(registrar: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &Snapshot::get_seeds(registrar, &epoch.to_le_bytes()),
+        &crate::id(),
+    )
+}
+
+pub fn snapshot_address(registrar: &Pubkey, epoch: u64) -> Pubkey {
+    find_snapshot_address(registrar, epoch).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::account_info_util::Acc;
+
+    #[test]
+    fn addresses_match_program_seeds() {
+        let realm = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let registrar = registrar_address(&realm);
+
+        Registrar::verify_pda(&Registrar::get_seeds(&realm), &registrar).unwrap();
+        Voter::verify_pda(
+            &ExchangeRateEntry::get_voting_mint_seeds(&registrar, &mint),
+            &voting_mint_address(&registrar, &mint),
+        )
+        .unwrap();
+        Voter::verify_pda(
+            &Voter::get_voter_seeds(&registrar, &authority),
+            &voter_address(&registrar, &authority),
+        )
+        .unwrap();
+        Voter::verify_pda(
+            &Voter::get_voter_weight_seeds(&registrar, &authority),
+            &voter_weight_record_address(&registrar, &authority),
+        )
+        .unwrap();
+        RewardPool::verify_pda(
+            &RewardPool::get_seeds(&registrar),
+            &reward_pool_address(&registrar),
+        )
+        .unwrap();
+        Snapshot::verify_pda(
+            &Snapshot::get_seeds(&registrar, &7_u64.to_le_bytes()),
+            &snapshot_address(&registrar, 7),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn addresses_match_raw_seeds() {
+        let realm = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let find = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &crate::id());
+
+        let registrar = find(&[realm.as_ref()]);
+        assert_eq!(find_registrar_address(&realm), registrar);
+        let registrar = registrar.0;
+        let voting_mint = find(&[registrar.as_ref(), mint.as_ref()]);
+        assert_eq!(find_voting_mint_address(&registrar, &mint), voting_mint);
+        assert_eq!(
+            find_voter_address(&registrar, &authority),
+            find(&[registrar.as_ref(), authority.as_ref()])
+        );
+        assert_eq!(
+            find_voter_weight_record_address(&registrar, &authority),
+            find(&[
+                b"voter-weight-record",
+                registrar.as_ref(),
+                authority.as_ref()
+            ])
+        );
+        let reward_pool = find(&[b"reward-pool", registrar.as_ref()]);
+        assert_eq!(find_reward_pool_address(&registrar), reward_pool);
+        assert_eq!(
+            find_snapshot_address(&registrar, 7),
+            find(&[b"snapshot", registrar.as_ref(), &7_u64.to_le_bytes()])
+        );
+
+        assert_eq!(
+            exchange_vault_address(&registrar, &mint),
+            get_associated_token_address(&registrar, &mint)
+        );
+        assert_eq!(
+            voting_token_address(&registrar, &authority, &mint),
+            get_associated_token_address(&authority, &voting_mint.0)
+        );
+        assert_eq!(
+            reward_vault_address(&registrar, &mint),
+            get_associated_token_address(&reward_pool.0, &mint)
+        );
+    }
+}
@@ -1,28 +1,213 @@
-use solana_program::program_error::ProgramError;
+use num_traits::FromPrimitive;
+use solana_program::{
+    decode_error::DecodeError, msg, program_error::PrintProgramError, program_error::ProgramError,
+};
 use thiserror::Error;
 
-#[derive(Error, Debug, Copy, Clone)]
+/// Errors of the program, surfaced as `ProgramError::Custom(code)`.
+///
+/// Codes are explicit and must never be reused or renumbered,
+/// new variants are appended with the next free code.
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum GovError {
+    /// 0x0 - instruction data could not be decoded
     #[error("Invalid Instruction")]
-    InvalidInstruction,
+    InvalidInstruction = 0,
+    /// 0x1 - exchange rate must be positive
     #[error("Invalid Rate")]
-    InvalidRate,
+    InvalidRate = 1,
+    /// 0x2 - decimals of the exchange rate do not match the mint
     #[error("Invalid Decimals")]
-    InvalidDecimals,
+    InvalidDecimals = 2,
+    /// 0x3 - signer is not the authority of the account
     #[error("Authority Mismatch")]
-    AuthorityMismatch,
+    AuthorityMismatch = 3,
+    /// 0x4 - account belongs to another registrar
     #[error("Registrar Mismatch")]
-    RegistrarMismatch,
+    RegistrarMismatch = 4,
+    /// 0x5 - mint has no exchange rate in the registrar
     #[error("ExchangeRateEntry Not Found")]
-    ExchangeRateEntryNotFound,
+    ExchangeRateEntryNotFound = 5,
+    /// 0x6 - every deposit entry of the voter is used
     #[error("DepositEntry Full")]
-    DepositEntryFull,
+    DepositEntryFull = 6,
+    /// 0x7 - deposit entry index is out of range or unused
     #[error("Invalid Deposit Id")]
-    InvalidDepositId,
+    InvalidDepositId = 7,
+    /// 0x8 - delegating voter does not delegate to the voter
+    #[error("Delegate Mismatch")]
+    DelegateMismatch = 8,
+    /// 0x9 - delegating voter is passed more than once
+    #[error("Duplicate Delegator")]
+    DuplicateDelegator = 9,
+    /// 0xa - converted amount does not fit in u64
+    #[error("Conversion Overflow")]
+    ConversionOverflow = 10,
+    /// 0xb - lockup days are negative or exceed the maximum
+    #[error("Invalid Lockup Days")]
+    InvalidLockupDays = 11,
+    /// 0xc - account is not the PDA derived from the expected seeds
+    #[error("PDA Mismatch")]
+    PdaMismatch = 12,
+    /// 0xd - token account or exchange rate holds another mint
+    #[error("Token Mint Mismatch")]
+    TokenMintMismatch = 13,
+    /// 0xe - token account is owned by someone else
+    #[error("Token Owner Mismatch")]
+    TokenOwnerMismatch = 14,
+    /// 0xf - exchange rate index exceeds the registrar capacity
+    #[error("Invalid Exchange Rate Index")]
+    InvalidExchangeRateIndex = 15,
+    /// 0x10 - arithmetic overflow in deposit accounting
+    #[error("Math Overflow")]
+    MathOverflow = 16,
+    /// 0x11 - PDA bump supplied by the client is not the canonical one
+    #[error("Non-Canonical Bump")]
+    NonCanonicalBump = 17,
+    /// 0x12 - withdrawal exceeds the unlocked amount of the deposit
+    #[error("Insufficient Unlocked Tokens")]
+    InsufficientUnlockedTokens = 18,
+    /// 0x13 - only a constant lockup, not unlocking yet, can start unlocking
+    #[error("Invalid Lockup Kind")]
+    InvalidLockupKind = 19,
+    /// 0x14 - the registrar has no treasury to send early unlock penalties to
+    #[error("Early Unlock Disabled")]
+    EarlyUnlockDisabled = 20,
+    /// 0x15 - penalty exceeds `MAX_PENALTY_BPS`
+    #[error("Invalid Penalty")]
+    InvalidPenalty = 21,
+    /// 0x16 - the snapshot is older than the checkpoints kept by the voter
+    #[error("Checkpoint Not Found")]
+    CheckpointNotFound = 22,
+    /// 0x17 - the registrar lockup policy does not allow this lockup kind
+    #[error("Lockup Kind Not Allowed")]
+    LockupKindNotAllowed = 23,
+    /// 0x18 - min days exceed max days or `MAX_DAYS_LOCKED`, or no kind is allowed
+    #[error("Invalid Lockup Policy")]
+    InvalidLockupPolicy = 24,
+    /// 0x19 - the lockup saturation of an exchange rate is zero or over `MAX_SECS_LOCKED`
+    #[error("Invalid Vote Weight Factors")]
+    InvalidVoteWeightFactors = 25,
+    /// 0x1a - the delegation changed less than `Registrar::delegation_cooldown_secs` ago
+    #[error("Delegation Cooldown")]
+    DelegationCooldown = 26,
+    /// 0x1b - only a Cliff or Constant lockup with `min_lockup_days` left can be topped up
+    #[error("Top Up Not Allowed")]
+    TopUpNotAllowed = 27,
+    /// 0x1c - the registrar has no delegation cooldown yet
+    #[error("Delegation Disabled")]
+    DelegationDisabled = 28,
+    /// 0x1d - the delegation cooldown is shorter than the max voting time of a governance
+    #[error("Invalid Delegation Cooldown")]
+    InvalidDelegationCooldown = 29,
+    /// 0x1e - the realm account is not the realm of the registrar
+    #[error("Realm Mismatch")]
+    RealmMismatch = 30,
+    /// 0x1f - the snapshot is not one of the latest `SNAPSHOT_HISTORY` of the registrar
+    #[error("Snapshot Not Kept")]
+    SnapshotNotKept = 31,
+    /// 0x20 - the snapshot is not the governing snapshot of the registrar
+    #[error("Snapshot Not Governing")]
+    SnapshotNotGoverning = 32,
+    /// 0x21 - a snapshot governs: no live weight, and it must stay kept
+    #[error("Snapshot Governs")]
+    SnapshotGoverns = 33,
 }
 
 impl From<GovError> for ProgramError {
-    fn from(e: GovError) -> ProgramError {
-        ProgramError::Custom(e as u32)
+    fn from(e: GovError) -> Self {
+        Self::Custom(e as u32)
+    }
+}
+
+// written out rather than derived, the num-derive 0.3 derive trips `non_local_definitions`
+impl FromPrimitive for GovError {
+    fn from_i64(n: i64) -> Option<Self> {
+        u64::try_from(n).ok().and_then(Self::from_u64)
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(match n {
+            0 => Self::InvalidInstruction,
+            1 => Self::InvalidRate,
+            2 => Self::InvalidDecimals,
+            3 => Self::AuthorityMismatch,
+            4 => Self::RegistrarMismatch,
+            5 => Self::ExchangeRateEntryNotFound,
+            6 => Self::DepositEntryFull,
+            7 => Self::InvalidDepositId,
+            8 => Self::DelegateMismatch,
+            9 => Self::DuplicateDelegator,
+            10 => Self::ConversionOverflow,
+            11 => Self::InvalidLockupDays,
+            12 => Self::PdaMismatch,
+            13 => Self::TokenMintMismatch,
+            14 => Self::TokenOwnerMismatch,
+            15 => Self::InvalidExchangeRateIndex,
+            16 => Self::MathOverflow,
+            17 => Self::NonCanonicalBump,
+            18 => Self::InsufficientUnlockedTokens,
+            19 => Self::InvalidLockupKind,
+            20 => Self::EarlyUnlockDisabled,
+            21 => Self::InvalidPenalty,
+            22 => Self::CheckpointNotFound,
+            23 => Self::LockupKindNotAllowed,
+            24 => Self::InvalidLockupPolicy,
+            25 => Self::InvalidVoteWeightFactors,
+            26 => Self::DelegationCooldown,
+            27 => Self::TopUpNotAllowed,
+            28 => Self::DelegationDisabled,
+            29 => Self::InvalidDelegationCooldown,
+            30 => Self::RealmMismatch,
+            31 => Self::SnapshotNotKept,
+            32 => Self::SnapshotNotGoverning,
+            33 => Self::SnapshotGoverns,
+            _ => return None,
+        })
+    }
+}
+
+impl<T> DecodeError<T> for GovError {
+    fn type_of() -> &'static str {
+        "GovError"
+    }
+}
+
+impl PrintProgramError for GovError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!("GovError({:#x}): {}", *self as u32, self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_stable() {
+        assert_eq!(
+            ProgramError::from(GovError::InvalidInstruction),
+            ProgramError::Custom(0)
+        );
+        assert_eq!(
+            ProgramError::from(GovError::ConversionOverflow),
+            ProgramError::Custom(10)
+        );
+        assert_eq!(
+            ProgramError::from(GovError::MathOverflow),
+            ProgramError::Custom(16)
+        );
+    }
+
+    #[test]
+    fn codes_decode_back() {
+        for code in 0..=33 {
+            let err = GovError::from_u32(code).unwrap();
+            assert_eq!(err as u32, code);
+        }
+        assert_eq!(GovError::from_u32(34), None);
     }
 }
@@ -0,0 +1,374 @@
+//! Borsh events emitted through `sol_log_data`.
+//!
+//! Every instruction but the read-only `GetVoterWeight` logs one `GovEvent`,
+//! which shows up in the transaction logs as `Program data: <base64>`. Variants are append-only, so the Borsh
+//! tag of an existing event never changes.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, log::sol_log_data, pubkey::Pubkey};
+
+use crate::state::{Lockup, LockupPolicy};
+
+/// Prefix of the log line written by `sol_log_data`
+pub const PROGRAM_DATA: &str = "Program data: ";
+
+/// Prefix of the `invoke`, `success` and `failed` lines of the runtime
+const PROGRAM: &str = "Program ";
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct RegistrarCreated {
+    pub registrar: Pubkey,
+    pub realm: Pubkey,
+    pub realm_community_mint: Pubkey,
+    pub authority: Pubkey,
+    pub rate_decimals: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct ExchangeRateCreated {
+    pub registrar: Pubkey,
+    pub idx: u16,
+    pub mint: Pubkey,
+    pub voting_mint: Pubkey,
+    pub rate: u64,
+    pub decimals: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct VoterCreated {
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub authority: Pubkey,
+    pub voter_weight_record: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq)]
+pub struct DepositCreated {
+    pub voter: Pubkey,
+    pub deposit_idx: u8,
+    pub rate_idx: u8,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub amount_scaled: u64,
+    pub lockup: Lockup,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct DepositUpdated {
+    pub voter: Pubkey,
+    pub deposit_idx: u8,
+    pub mint: Pubkey,
+    // amount added by this instruction
+    pub amount: u64,
+    // totals of the deposit entry after the update
+    pub amount_deposited: u64,
+    pub amount_scaled: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct DelegateSet {
+    pub voter: Pubkey,
+    pub authority: Pubkey,
+    // `Pubkey::default()` when revoked
+    pub delegate: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct VoterWeightUpdated {
+    pub voter: Pubkey,
+    pub voter_weight_record: Pubkey,
+    pub voter_weight: u64,
+    pub delegators: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct Withdrawn {
+    pub voter: Pubkey,
+    pub deposit_idx: u8,
+    pub mint: Pubkey,
+    pub amount: u64,
+    // native amount still locked in the entry, freed when zero
+    pub remaining: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq)]
+pub struct UnlockStarted {
+    pub voter: Pubkey,
+    pub deposit_idx: u8,
+    // lockup after the unlock started
+    pub lockup: Lockup,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct EarlyUnlockPolicySet {
+    pub registrar: Pubkey,
+    pub penalty_bps: u16,
+    pub treasury: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct EarlyUnlocked {
+    pub voter: Pubkey,
+    pub deposit_idx: u8,
+    pub mint: Pubkey,
+    // native amount released to the voter, and sent to the treasury
+    pub amount: u64,
+    pub penalty: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct RewardPoolCreated {
+    pub registrar: Pubkey,
+    pub reward_pool: Pubkey,
+    pub reward_mint: Pubkey,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct RewardsFunded {
+    pub reward_pool: Pubkey,
+    pub amount: u64,
+    // pool state after the funding
+    pub total_weight: u64,
+    pub reward_index: u128,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct RewardsClaimed {
+    pub voter: Pubkey,
+    pub amount: u64,
+    // weight staked from now on
+    pub reward_weight: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct RewardWeightRefreshed {
+    pub voter: Pubkey,
+    // weight staked from now on
+    pub reward_weight: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotCreated {
+    pub registrar: Pubkey,
+    pub snapshot: Pubkey,
+    pub epoch: u64,
+    pub ts: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotVoterWeightUpdated {
+    pub voter: Pubkey,
+    pub voter_weight_record: Pubkey,
+    pub epoch: u64,
+    pub voter_weight: u64,
+    pub delegators: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct LockupPolicySet {
+    pub registrar: Pubkey,
+    pub lockup_policy: LockupPolicy,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct TimeOffsetSet {
+    pub registrar: Pubkey,
+    pub time_offset: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct DelegationCooldownSet {
+    pub registrar: Pubkey,
+    pub delegation_cooldown_secs: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct GoverningSnapshotSet {
+    pub registrar: Pubkey,
+    pub epoch: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq)]
+pub enum GovEvent {
+    RegistrarCreated(RegistrarCreated),
+    ExchangeRateCreated(ExchangeRateCreated),
+    VoterCreated(VoterCreated),
+    DepositCreated(DepositCreated),
+    DepositUpdated(DepositUpdated),
+    DelegateSet(DelegateSet),
+    VoterWeightUpdated(VoterWeightUpdated),
+    Withdrawn(Withdrawn),
+    UnlockStarted(UnlockStarted),
+    EarlyUnlockPolicySet(EarlyUnlockPolicySet),
+    EarlyUnlocked(EarlyUnlocked),
+    RewardPoolCreated(RewardPoolCreated),
+    RewardsFunded(RewardsFunded),
+    RewardsClaimed(RewardsClaimed),
+    SnapshotCreated(SnapshotCreated),
+    SnapshotVoterWeightUpdated(SnapshotVoterWeightUpdated),
+    LockupPolicySet(LockupPolicySet),
+    RewardWeightRefreshed(RewardWeightRefreshed),
+    TimeOffsetSet(TimeOffsetSet),
+    DelegationCooldownSet(DelegationCooldownSet),
+    GoverningSnapshotSet(GoverningSnapshotSet),
+}
+
+impl GovEvent {
+    pub fn emit(&self) -> ProgramResult {
+        sol_log_data(&[&self.try_to_vec()?]);
+        Ok(())
+    }
+
+    /// Decode the raw bytes passed to `sol_log_data`
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        Self::try_from_slice(data).ok()
+    }
+
+    /// Decode the base64 field of a `Program data: <base64>` log line
+    fn from_data_log(encoded: &str) -> Option<Self> {
+        // a single field is logged, more fields would be space separated
+        if encoded.contains(' ') {
+            return None;
+        }
+        Self::decode(&base64::decode(encoded).ok()?)
+    }
+
+    /// Decode every event of the program found in the transaction logs.
+    /// The `invoke` and `success`/`failed` lines are followed so that only the
+    /// data logged while this program runs is decoded, not that of other
+    /// programs, be they top level or invoked through CPI
+    pub fn from_logs<S: AsRef<str>>(logs: &[S]) -> Vec<Self> {
+        let program_id = crate::id().to_string();
+        let mut invoked: Vec<&str> = Vec::new();
+        let mut events = Vec::new();
+        for log in logs {
+            let log = log.as_ref();
+            if let Some(encoded) = log.strip_prefix(PROGRAM_DATA) {
+                if invoked.last() == Some(&program_id.as_str()) {
+                    events.extend(Self::from_data_log(encoded));
+                }
+            } else if let Some(line) = log.strip_prefix(PROGRAM) {
+                let mut words = line.split(' ');
+                match (words.next(), words.next()) {
+                    (Some(id), Some("invoke")) => invoked.push(id),
+                    (Some(_), Some("success" | "failed:")) => {
+                        invoked.pop();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        events
+    }
+}
+
+macro_rules! impl_into_event {
+    ($($event:ident),*) => {
+        $(
+            impl From<$event> for GovEvent {
+                fn from(event: $event) -> Self {
+                    Self::$event(event)
+                }
+            }
+        )*
+    };
+}
+
+impl_into_event!(
+    RegistrarCreated,
+    ExchangeRateCreated,
+    VoterCreated,
+    DepositCreated,
+    DepositUpdated,
+    DelegateSet,
+    VoterWeightUpdated,
+    Withdrawn,
+    UnlockStarted,
+    EarlyUnlockPolicySet,
+    EarlyUnlocked,
+    RewardPoolCreated,
+    RewardsFunded,
+    RewardsClaimed,
+    SnapshotCreated,
+    SnapshotVoterWeightUpdated,
+    LockupPolicySet,
+    RewardWeightRefreshed,
+    TimeOffsetSet,
+    DelegationCooldownSet,
+    GoverningSnapshotSet
+);
+
+/// Log `event` through `sol_log_data`
+pub fn emit<E: Into<GovEvent>>(event: E) -> ProgramResult {
+    event.into().emit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_from_log_lines() {
+        let event = GovEvent::DepositUpdated(DepositUpdated {
+            voter: Pubkey::new_unique(),
+            deposit_idx: 3,
+            mint: Pubkey::new_unique(),
+            amount: 10,
+            amount_deposited: 20,
+            amount_scaled: 20_000_000,
+        });
+        let data = format!(
+            "{}{}",
+            PROGRAM_DATA,
+            base64::encode(event.try_to_vec().unwrap())
+        );
+        let invoke = format!("Program {} invoke [1]", crate::id());
+        let success = format!("Program {} success", crate::id());
+
+        assert_eq!(
+            GovEvent::from_logs(&[
+                &invoke,
+                "Program log: Instruction: update deposit",
+                &data,
+                &success,
+            ]),
+            vec![event]
+        );
+        assert_eq!(
+            GovEvent::from_logs(&[&invoke, "Program data: bm90IGFuIGV2ZW50", &success]),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn decode_only_the_data_of_this_program() {
+        let event = GovEvent::DelegateSet(DelegateSet {
+            voter: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            delegate: Pubkey::new_unique(),
+        });
+        let data = format!(
+            "{}{}",
+            PROGRAM_DATA,
+            base64::encode(event.try_to_vec().unwrap())
+        );
+        let other = Pubkey::new_unique();
+
+        let logs = [
+            // another top level program logging the same bytes
+            format!("Program {other} invoke [1]"),
+            data.clone(),
+            format!("Program {other} success"),
+            format!("Program {} invoke [1]", crate::id()),
+            // a CPI from this program logging data of its own
+            format!("Program {other} invoke [2]"),
+            data.clone(),
+            format!("Program {other} failed: custom program error: 0x0"),
+            data.clone(),
+            format!("Program {} success", crate::id()),
+            // data outside of any invocation
+            data,
+        ];
+
+        assert_eq!(GovEvent::from_logs(&logs), vec![event]);
+    }
+}
@@ -1,6 +1,6 @@
 #![allow(clippy::use_self)]
 
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     msg,
@@ -10,13 +10,14 @@ use solana_program::{
 
 use crate::{
     error::*,
-    state::{ExchangeRateEntry, LockupKind},
+    state::{ExchangeRateEntry, LockupKind, LockupPolicy},
 };
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug)]
 pub enum GovInstruction {
     ///Createa a new voting registrar. There can only be single registrar per governance realm.
-    ///
+    ///`registrar_bump` must be the canonical bump of the registrar PDA.
+    ///`lockup_policy` bounds the lockup of every new deposit
     ///
     /// Accounts expected:
     ///
@@ -29,11 +30,16 @@ pub enum GovInstruction {
     CreateRegistrar {
         rate_decimals: u8,
         registrar_bump: u8,
+        lockup_policy: LockupPolicy,
     },
     /// Creates a new exchange rate for a given mint.
     /// Calculated by vault.  This allows a voter to
     /// deposit the mint in exchange for vTokens.
     /// There can only be a single exchange rate per mint
+    /// `voting_mint_bump` must be the canonical bump of the voting mint PDA
+    /// The weight factors of `er` shape the voting power of its deposits,
+    /// its lockup saturation must be within 1..=`MAX_SECS_LOCKED`
+    /// and its decimals those of `depositMint`
     ///
     ///
     /// Accounts expected:
@@ -53,6 +59,7 @@ pub enum GovInstruction {
         er: ExchangeRateEntry,
     },
     /// Create and init 2 PDA (voter & voter_weight_record)
+    /// Both bumps must be the canonical ones
     ///
     /// Accounts expected:
     ///
@@ -85,15 +92,226 @@ pub enum GovInstruction {
     /// 9. `[]` token_program
     /// 10. `[]` associated_token_program
     /// 11. `[sysvar]` rent
+    /// 12. `[writable; PDA]` reward_pool<RewardPool>, may not be created yet
     CreateDeposit {
         kind: LockupKind,
         amount: u64,
         days: i32,
     },
-    UpdateDeposit {
-        update_idx: u8,
+    /// Add tokens to an existing DepositEntry
+    ///
+    /// Accounts expected: same accounts as `CreateDeposit`
+    UpdateDeposit { update_idx: u8, amount: u64 },
+    /// Hand the voting power of a voter to another voter of the same registrar.
+    /// Passing the authority itself revokes the delegation. The weight moves after
+    /// the delegation cooldown of the registrar, during which the delegation cannot
+    /// change again. Fails until `SetDelegationCooldown` set one
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` authority
+    /// 1. `[readonly]` registrar
+    /// 2. `[writable; PDA]` voter<Voter>
+    SetDelegate { delegate: Pubkey },
+    /// Refresh the voter_weight_record of the voter.
+    /// Weight of every voter delegating to `voter.authority` is aggregated
+    /// when they are passed as remaining accounts. Fails while a snapshot governs
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[readonly]` registrar
+    /// 1. `[readonly; PDA]` voter<Voter>
+    /// 2. `[writable; PDA]` voter_weight_record<VoterWeightRecord>
+    /// 3. `[readonly; PDA]` ..remaining delegating voters<Voter>
+    UpdateVoterWeightRecord,
+    /// Same as `CreateDeposit`, funded by another signer on behalf of the voter.
+    /// The voter authority co-signs, as the entry takes one of its slots.
+    /// Voting tokens are minted to the voter authority's ATA
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` depositor, owner of deposit_token
+    /// 1. ..12. same accounts as `CreateDeposit`
+    /// 13. `[signer]` voter_authority
+    CreateDepositFor {
+        kind: LockupKind,
         amount: u64,
+        days: i32,
     },
+    /// Same as `UpdateDeposit`, funded by any signer on behalf of the voter.
+    /// Only tops up an entry the voter already opened
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` depositor, owner of deposit_token
+    /// 1. ..12. same accounts as `UpdateDeposit`
+    /// 13. `[readonly]` voter_authority
+    UpdateDepositFor { update_idx: u8, amount: u64 },
+    /// Withdraw unlocked tokens of a deposit entry.
+    /// Voting tokens are burnt according to the exchange rate's policy:
+    /// frozen ones from the authority's ATA, transferable ones from any
+    /// account of the voting mint whose owner approved the authority as delegate.
+    /// Transferable ones pay `destination` of that owner, who holds the receipt
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` authority
+    /// 1. `[readonly]` registrar
+    /// 2. `[writable; PDA]` voter<Voter>
+    /// 3. `[readonly]` deposit_mint<Mint>
+    /// 4. `[writable; PDA]` voting_mint<Mint>
+    /// 5. `[writable]` destination<Token> of deposit_mint, owned by the voting_token
+    ///    owner for transferable voting tokens
+    /// 6. `[writable]` exchange_vault<ATA>
+    /// 7. `[writable]` voting_token<Token> of voting_mint
+    /// 8. `[]` token_program
+    /// 9. `[writable; PDA]` reward_pool<RewardPool>, may not be created yet
+    Withdraw { deposit_idx: u8, amount: u64 },
+    /// Read-only: compute the weight `UpdateVoterWeightRecord` would record,
+    /// now and at `at_ts`, with a breakdown of the voter's deposits.
+    /// Returned as `return_data::VoterWeight` through `set_return_data`.
+    /// An `at_ts` in the past evaluates the current deposits at that time,
+    /// not the weight the voter had then: snapshots record past weights
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[readonly]` registrar
+    /// 1. `[readonly; PDA]` voter<Voter>
+    /// 2. `[readonly; PDA]` ..remaining delegating voters<Voter>
+    GetVoterWeight { at_ts: i64 },
+    /// Start the countdown of a Constant lockup: its whole period runs again
+    /// from now, decaying as Cliff
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` authority
+    /// 1. `[readonly]` registrar
+    /// 2. `[writable; PDA]` voter<Voter>
+    /// 3. `[writable; PDA]` reward_pool<RewardPool>, may not be created yet
+    StartUnlock { deposit_idx: u8 },
+    /// Set the penalty of `EarlyUnlock` at the maximum lockup, in basis points,
+    /// and the owner of the token accounts receiving it.
+    /// `Pubkey::default()` as treasury disables early unlocks
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` authority
+    /// 1. `[writable]` registrar<Registrar>
+    SetEarlyUnlockPolicy { penalty_bps: u16, treasury: Pubkey },
+    /// Release the whole deposit entry before its lockup ends, burning its
+    /// voting tokens. The penalty on the locked part goes to the treasury
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. ..9. same accounts as `Withdraw`
+    /// 10. `[writable]` treasury_token<Token> of deposit_mint, owned by `registrar.treasury`
+    EarlyUnlock { deposit_idx: u8 },
+    /// Create the reward pool of the registrar, sharing fees of `reward_mint`
+    /// among the voters by weight, and its vault
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable; signer]` authority, pays for both accounts
+    /// 1. `[readonly]` registrar
+    /// 2. `[writable; PDA]` reward_pool<RewardPool>
+    /// 3. `[readonly]` reward_mint<Mint>
+    /// 4. `[writable]` reward_vault<ATA> of reward_pool
+    /// 5. `[]` system_program
+    /// 6. `[]` token_program
+    /// 7. `[]` associated_token_program
+    /// 8. `[sysvar]` rent
+    CreateRewardPool,
+    /// Transfer fees into the reward vault, shared by the weight staked so far
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` funder, owner of source
+    /// 1. `[readonly]` registrar
+    /// 2. `[writable; PDA]` reward_pool<RewardPool>
+    /// 3. `[writable]` source<Token> of reward_mint
+    /// 4. `[writable]` reward_vault<ATA> of reward_pool
+    /// 5. `[]` token_program
+    FundRewards { amount: u64 },
+    /// Pay the rewards of the voter, settled at the lower of its staked and
+    /// current weight, and stake its current weight from now on
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` authority
+    /// 1. `[readonly]` registrar
+    /// 2. `[writable; PDA]` voter<Voter>
+    /// 3. `[writable; PDA]` reward_pool<RewardPool>
+    /// 4. `[writable]` reward_vault<ATA> of reward_pool
+    /// 5. `[writable]` destination<Token> of reward_mint
+    /// 6. `[]` token_program
+    ClaimRewards,
+    /// Open the next snapshot epoch of the registrar at the current time.
+    /// Each voter checkpoints its weight as of it before its deposits or delegation change
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable; signer]` authority, pays for the snapshot
+    /// 1. `[writable]` registrar<Registrar>
+    /// 2. `[writable; PDA]` snapshot<Snapshot> of the next epoch
+    /// 3. `[]` system_program
+    CreateSnapshot,
+    /// Same as `UpdateVoterWeightRecord`, with the weight as of the governing snapshot
+    /// of the registrar. Delegators are the voters that delegated to the voter as of it
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[readonly]` registrar
+    /// 1. `[readonly; PDA]` snapshot<Snapshot>
+    /// 2. `[readonly; PDA]` voter<Voter>
+    /// 3. `[writable; PDA]` voter_weight_record<VoterWeightRecord>
+    /// 4. `[readonly; PDA]` ..remaining voters<Voter> delegating as of the snapshot
+    UpdateSnapshotVoterWeightRecord,
+    /// Replace the lockup policy bounding new deposits.
+    /// Existing deposits keep their lockup
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` authority
+    /// 1. `[writable]` registrar<Registrar>
+    SetLockupPolicy { lockup_policy: LockupPolicy },
+    /// Stake the current weight of the voter in the reward pool. Permissionless,
+    /// so a decayed weight stops earning and earlier voters start to
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[readonly]` registrar
+    /// 1. `[writable; PDA]` voter<Voter>
+    /// 2. `[writable; PDA]` reward_pool<RewardPool>
+    RefreshRewardWeight,
+    /// Set the time a delegation change takes to move the weight of a voter.
+    /// It must be at least the max voting time of every governance passed,
+    /// which should be every governance of the realm
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` authority
+    /// 1. `[writable]` registrar<Registrar>
+    /// 2. `[readonly]` realm of the registrar, owned by the governance program
+    /// 3. `[readonly]` ..remaining governances of the realm, at least one
+    SetDelegationCooldown { delegation_cooldown_secs: u32 },
+    /// Count the voter weight as of the snapshot `epoch`, one of the kept ones,
+    /// or live again with 0. Take the snapshot before the proposals it governs
+    /// start voting, and keep it until they end
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` authority
+    /// 1. `[writable]` registrar<Registrar>
+    SetGoverningSnapshot { epoch: u64 },
+    /// Shift the clock of every lockup computation by `time_offset` seconds.
+    /// Only built with the `time-offset` feature, for tests
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` authority
+    /// 1. `[writable]` registrar<Registrar>
+    #[cfg(feature = "time-offset")]
+    SetTimeOffset { time_offset: i64 },
 }
 
 impl GovInstruction {
@@ -112,6 +330,7 @@ pub fn create_registrar(
     realm: &Pubkey,
     community_mint: &Pubkey,
     rate_decimals: u8,
+    lockup_policy: LockupPolicy,
     registrar_pda: &Pubkey,
     registrar_bump: u8,
 ) -> Instruction {
@@ -128,6 +347,7 @@ pub fn create_registrar(
         &GovInstruction::CreateRegistrar {
             rate_decimals,
             registrar_bump,
+            lockup_policy,
         },
         accounts,
     )
@@ -197,75 +417,451 @@ pub fn create_voter(
     )
 }
 
-pub fn create_deposit(
+/// Accounts of the deposit instructions, in the order of `CreateDeposit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepositKeys {
+    pub depositor: Pubkey, // the voter authority but for the `..For` variants
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub deposit_mint: Pubkey,
+    pub voting_mint: Pubkey,
+    pub deposit_token: Pubkey,
+    pub exchange_vault: Pubkey,
+    pub voting_token: Pubkey,
+    pub reward_pool: Pubkey,
+}
+
+impl DepositKeys {
+    //notice that some accounts reuqire become writable for CPI invoke
+    fn account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.depositor, true),
+            AccountMeta::new_readonly(self.registrar, false),
+            AccountMeta::new(self.voter, false),
+            AccountMeta::new_readonly(self.deposit_mint, false),
+            AccountMeta::new(self.voting_mint, false), /*voting_mint require writable as we will mint for CPI */
+            AccountMeta::new(self.deposit_token, false),
+            AccountMeta::new(self.exchange_vault, false),
+            //PDA be created
+            AccountMeta::new(self.voting_token, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new(self.reward_pool, false),
+        ]
+    }
+}
+
+pub fn create_deposit(keys: &DepositKeys, kind: LockupKind, amount: u64, days: i32) -> Instruction {
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::CreateDeposit { kind, amount, days },
+        keys.account_metas(),
+    )
+}
+
+pub fn update_deposit(keys: &DepositKeys, update_idx: u8, amount: u64) -> Instruction {
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::UpdateDeposit { update_idx, amount },
+        keys.account_metas(),
+    )
+}
+
+pub fn set_delegate(
     authority: &Pubkey,
     registrar_pda: &Pubkey,
     voter: &Pubkey,
-    deposit_mint: &Pubkey,
-    voting_mint_pda: &Pubkey,
-    deposit_token: &Pubkey,
-    exchange_vault_pda: &Pubkey,
-    voting_token: &Pubkey,
+    delegate: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*registrar_pda, false),
+        AccountMeta::new(*voter, false),
+    ];
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::SetDelegate {
+            delegate: *delegate,
+        },
+        accounts,
+    )
+}
+
+pub fn update_voter_weight_record(
+    registrar_pda: &Pubkey,
+    voter: &Pubkey,
+    voter_weight_record: &Pubkey,
+    delegators: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*registrar_pda, false),
+        AccountMeta::new_readonly(*voter, false),
+        AccountMeta::new(*voter_weight_record, false),
+    ];
+    accounts.extend(
+        delegators
+            .iter()
+            .map(|delegator| AccountMeta::new_readonly(*delegator, false)),
+    );
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::UpdateVoterWeightRecord,
+        accounts,
+    )
+}
+
+pub fn create_deposit_for(
+    keys: &DepositKeys,
+    voter_authority: &Pubkey,
     kind: LockupKind,
     amount: u64,
     days: i32,
 ) -> Instruction {
-    //notice that some accounts reuqire become writable for CPI invoke
+    let mut accounts = keys.account_metas();
+    accounts.push(AccountMeta::new_readonly(*voter_authority, true));
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::CreateDepositFor { kind, amount, days },
+        accounts,
+    )
+}
+
+pub fn update_deposit_for(
+    keys: &DepositKeys,
+    voter_authority: &Pubkey,
+    update_idx: u8,
+    amount: u64,
+) -> Instruction {
+    let mut accounts = keys.account_metas();
+    accounts.push(AccountMeta::new_readonly(*voter_authority, false));
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::UpdateDepositFor { update_idx, amount },
+        accounts,
+    )
+}
+
+/// Accounts of the withdraw instructions, in the order of `Withdraw`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WithdrawKeys {
+    pub authority: Pubkey,
+    pub registrar: Pubkey,
+    pub voter: Pubkey,
+    pub deposit_mint: Pubkey,
+    pub voting_mint: Pubkey,
+    pub destination: Pubkey,
+    pub exchange_vault: Pubkey,
+    pub voting_token: Pubkey, // any holder's account of transferable voting tokens
+    pub reward_pool: Pubkey,
+}
+
+impl WithdrawKeys {
+    fn account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.authority, true),
+            AccountMeta::new_readonly(self.registrar, false),
+            AccountMeta::new(self.voter, false),
+            AccountMeta::new_readonly(self.deposit_mint, false),
+            AccountMeta::new(self.voting_mint, false),
+            AccountMeta::new(self.destination, false),
+            AccountMeta::new(self.exchange_vault, false),
+            AccountMeta::new(self.voting_token, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(self.reward_pool, false),
+        ]
+    }
+}
+
+pub fn withdraw(keys: &WithdrawKeys, deposit_idx: u8, amount: u64) -> Instruction {
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::Withdraw {
+            deposit_idx,
+            amount,
+        },
+        keys.account_metas(),
+    )
+}
+
+pub fn get_voter_weight(
+    registrar_pda: &Pubkey,
+    voter: &Pubkey,
+    delegators: &[Pubkey],
+    at_ts: i64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*registrar_pda, false),
+        AccountMeta::new_readonly(*voter, false),
+    ];
+    accounts.extend(
+        delegators
+            .iter()
+            .map(|delegator| AccountMeta::new_readonly(*delegator, false)),
+    );
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::GetVoterWeight { at_ts },
+        accounts,
+    )
+}
+
+pub fn start_unlock(
+    authority: &Pubkey,
+    registrar_pda: &Pubkey,
+    voter: &Pubkey,
+    reward_pool_pda: &Pubkey,
+    deposit_idx: u8,
+) -> Instruction {
     let accounts = vec![
-        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(*authority, true),
         AccountMeta::new_readonly(*registrar_pda, false),
         AccountMeta::new(*voter, false),
-        AccountMeta::new_readonly(*deposit_mint, false),
-        AccountMeta::new(*voting_mint_pda, false), /*voting_mint require writable as we will mint for CPI */
-        AccountMeta::new(*deposit_token, false),
-        AccountMeta::new(*exchange_vault_pda, false),
-        //PDA be created
-        AccountMeta::new(*voting_token, false),
+        AccountMeta::new(*reward_pool_pda, false),
+    ];
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::StartUnlock { deposit_idx },
+        accounts,
+    )
+}
+
+pub fn set_early_unlock_policy(
+    authority: &Pubkey,
+    registrar_pda: &Pubkey,
+    penalty_bps: u16,
+    treasury: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*registrar_pda, false),
+    ];
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::SetEarlyUnlockPolicy {
+            penalty_bps,
+            treasury: *treasury,
+        },
+        accounts,
+    )
+}
+
+pub fn early_unlock(keys: &WithdrawKeys, treasury_token: &Pubkey, deposit_idx: u8) -> Instruction {
+    let mut accounts = keys.account_metas();
+    accounts.push(AccountMeta::new(*treasury_token, false));
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::EarlyUnlock { deposit_idx },
+        accounts,
+    )
+}
+
+pub fn create_reward_pool(
+    authority: &Pubkey,
+    registrar_pda: &Pubkey,
+    reward_pool_pda: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_vault: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(*registrar_pda, false),
+        AccountMeta::new(*reward_pool_pda, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new(*reward_vault, false),
         AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(spl_token::id(), false),
         AccountMeta::new_readonly(spl_associated_token_account::id(), false),
         AccountMeta::new_readonly(sysvar::rent::id(), false),
     ];
 
+    Instruction::new_with_borsh(crate::id(), &GovInstruction::CreateRewardPool, accounts)
+}
+
+pub fn fund_rewards(
+    funder: &Pubkey,
+    registrar_pda: &Pubkey,
+    reward_pool_pda: &Pubkey,
+    source: &Pubkey,
+    reward_vault: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*funder, true),
+        AccountMeta::new_readonly(*registrar_pda, false),
+        AccountMeta::new(*reward_pool_pda, false),
+        AccountMeta::new(*source, false),
+        AccountMeta::new(*reward_vault, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
     Instruction::new_with_borsh(
         crate::id(),
-        &GovInstruction::CreateDeposit { kind, amount, days },
+        &GovInstruction::FundRewards { amount },
         accounts,
     )
 }
 
-pub fn update_deposit(
+pub fn claim_rewards(
     authority: &Pubkey,
     registrar_pda: &Pubkey,
     voter: &Pubkey,
-    deposit_mint: &Pubkey,
-    voting_mint_pda: &Pubkey,
-    deposit_token: &Pubkey,
-    exchange_vault_pda: &Pubkey,
-    voting_token: &Pubkey,
-    update_idx: u8,
-    amount: u64,
+    reward_pool_pda: &Pubkey,
+    reward_vault: &Pubkey,
+    destination: &Pubkey,
 ) -> Instruction {
-    //notice that some accounts reuqire become writable for CPI invoke
     let accounts = vec![
-        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(*authority, true),
         AccountMeta::new_readonly(*registrar_pda, false),
         AccountMeta::new(*voter, false),
-        AccountMeta::new_readonly(*deposit_mint, false),
-        AccountMeta::new(*voting_mint_pda, false),
-        AccountMeta::new(*deposit_token, false),
-        AccountMeta::new(*exchange_vault_pda, false),
-        //PDA be created
-        AccountMeta::new(*voting_token, false),
-        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*reward_pool_pda, false),
+        AccountMeta::new(*reward_vault, false),
+        AccountMeta::new(*destination, false),
         AccountMeta::new_readonly(spl_token::id(), false),
-        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
-        AccountMeta::new_readonly(sysvar::rent::id(), false),
     ];
 
+    Instruction::new_with_borsh(crate::id(), &GovInstruction::ClaimRewards, accounts)
+}
+
+pub fn create_snapshot(
+    authority: &Pubkey,
+    registrar_pda: &Pubkey,
+    snapshot_pda: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(*registrar_pda, false),
+        AccountMeta::new(*snapshot_pda, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction::new_with_borsh(crate::id(), &GovInstruction::CreateSnapshot, accounts)
+}
+
+pub fn update_snapshot_voter_weight_record(
+    registrar_pda: &Pubkey,
+    snapshot_pda: &Pubkey,
+    voter: &Pubkey,
+    voter_weight_record: &Pubkey,
+    delegators: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*registrar_pda, false),
+        AccountMeta::new_readonly(*snapshot_pda, false),
+        AccountMeta::new_readonly(*voter, false),
+        AccountMeta::new(*voter_weight_record, false),
+    ];
+    accounts.extend(
+        delegators
+            .iter()
+            .map(|delegator| AccountMeta::new_readonly(*delegator, false)),
+    );
+
     Instruction::new_with_borsh(
         crate::id(),
-        &GovInstruction::UpdateDeposit { update_idx, amount },
+        &GovInstruction::UpdateSnapshotVoterWeightRecord,
+        accounts,
+    )
+}
+
+pub fn set_lockup_policy(
+    authority: &Pubkey,
+    registrar_pda: &Pubkey,
+    lockup_policy: LockupPolicy,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*registrar_pda, false),
+    ];
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::SetLockupPolicy { lockup_policy },
+        accounts,
+    )
+}
+
+pub fn refresh_reward_weight(
+    registrar_pda: &Pubkey,
+    voter: &Pubkey,
+    reward_pool_pda: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*registrar_pda, false),
+        AccountMeta::new(*voter, false),
+        AccountMeta::new(*reward_pool_pda, false),
+    ];
+
+    Instruction::new_with_borsh(crate::id(), &GovInstruction::RefreshRewardWeight, accounts)
+}
+
+pub fn set_delegation_cooldown(
+    authority: &Pubkey,
+    registrar_pda: &Pubkey,
+    realm: &Pubkey,
+    governances: &[Pubkey],
+    delegation_cooldown_secs: u32,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*registrar_pda, false),
+        AccountMeta::new_readonly(*realm, false),
+    ];
+    accounts.extend(
+        governances
+            .iter()
+            .map(|governance| AccountMeta::new_readonly(*governance, false)),
+    );
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::SetDelegationCooldown {
+            delegation_cooldown_secs,
+        },
+        accounts,
+    )
+}
+
+pub fn set_governing_snapshot(
+    authority: &Pubkey,
+    registrar_pda: &Pubkey,
+    epoch: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*registrar_pda, false),
+    ];
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::SetGoverningSnapshot { epoch },
+        accounts,
+    )
+}
+
+#[cfg(feature = "time-offset")]
+pub fn set_time_offset(
+    authority: &Pubkey,
+    registrar_pda: &Pubkey,
+    time_offset: i64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*registrar_pda, false),
+    ];
+
+    Instruction::new_with_borsh(
+        crate::id(),
+        &GovInstruction::SetTimeOffset { time_offset },
         accounts,
     )
 }
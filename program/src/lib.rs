@@ -12,11 +12,15 @@
 #![allow(unused_variables, unused_imports, dead_code)]
 use solana_program::declare_id;
 
+#[cfg(not(target_arch = "bpf"))]
+pub mod client;
 pub mod entrypoint;
-mod error;
+pub mod error;
+pub mod events;
 pub mod instruction;
 pub mod oracle;
 pub mod processor;
+pub mod return_data;
 pub mod state;
 pub mod utils;
 
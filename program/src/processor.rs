@@ -4,13 +4,34 @@ use solana_program::{
     program_error::ProgramError, pubkey::Pubkey,
 };
 
+mod claim_rewards;
 mod create_deposit;
 mod create_exchange_rate;
 mod create_registrar;
+mod create_reward_pool;
+mod create_snapshot;
 pub mod create_voter;
+mod deposit;
+mod early_unlock;
+mod fund_rewards;
+mod get_voter_weight;
+mod refresh_reward_weight;
+mod reward_pool;
+mod set_delegate;
+mod set_delegation_cooldown;
+mod set_early_unlock_policy;
+mod set_governing_snapshot;
+mod set_lockup_policy;
+#[cfg(feature = "time-offset")]
+mod set_time_offset;
+mod start_unlock;
 mod update_deposit;
+mod update_snapshot_voter_weight_record;
+mod update_voter_weight_record;
+mod withdraw;
 
 #[cfg_attr(feature = "no-entrypoint", allow(dead_code))]
+#[allow(clippy::too_many_lines)] // one arm per instruction
 pub fn process(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -22,9 +43,16 @@ pub fn process(
         GovInstruction::CreateRegistrar {
             rate_decimals,
             registrar_bump,
+            lockup_policy,
         } => {
             msg!("Instruction: create registrar");
-            create_registrar::process(program_id, accounts, rate_decimals, registrar_bump)
+            create_registrar::process(
+                program_id,
+                accounts,
+                rate_decimals,
+                registrar_bump,
+                lockup_policy,
+            )
         }
         GovInstruction::CreateExchangeRate {
             voting_mint_bump,
@@ -43,11 +71,96 @@ pub fn process(
         }
         GovInstruction::CreateDeposit { kind, amount, days } => {
             msg!("Instruction: create deposit");
-            create_deposit::process(program_id, accounts, kind, amount, days)
+            create_deposit::process(program_id, accounts, kind, amount, days, false)
         }
         GovInstruction::UpdateDeposit { update_idx, amount } => {
             msg!("Instruction: update deposit");
-            update_deposit::process(program_id, accounts, update_idx, amount)
+            update_deposit::process(program_id, accounts, update_idx, amount, false)
+        }
+        GovInstruction::SetDelegate { delegate } => {
+            msg!("Instruction: set delegate");
+            set_delegate::process(program_id, accounts, delegate)
+        }
+        GovInstruction::UpdateVoterWeightRecord => {
+            msg!("Instruction: update voter weight record");
+            update_voter_weight_record::process(program_id, accounts)
+        }
+        GovInstruction::CreateDepositFor { kind, amount, days } => {
+            msg!("Instruction: create deposit for");
+            create_deposit::process(program_id, accounts, kind, amount, days, true)
+        }
+        GovInstruction::UpdateDepositFor { update_idx, amount } => {
+            msg!("Instruction: update deposit for");
+            update_deposit::process(program_id, accounts, update_idx, amount, true)
+        }
+        GovInstruction::Withdraw {
+            deposit_idx,
+            amount,
+        } => {
+            msg!("Instruction: withdraw");
+            withdraw::process(program_id, accounts, deposit_idx, amount)
+        }
+        GovInstruction::GetVoterWeight { at_ts } => {
+            msg!("Instruction: get voter weight");
+            get_voter_weight::process(program_id, accounts, at_ts)
+        }
+        GovInstruction::StartUnlock { deposit_idx } => {
+            msg!("Instruction: start unlock");
+            start_unlock::process(program_id, accounts, deposit_idx)
+        }
+        GovInstruction::SetEarlyUnlockPolicy {
+            penalty_bps,
+            treasury,
+        } => {
+            msg!("Instruction: set early unlock policy");
+            set_early_unlock_policy::process(program_id, accounts, penalty_bps, treasury)
+        }
+        GovInstruction::EarlyUnlock { deposit_idx } => {
+            msg!("Instruction: early unlock");
+            early_unlock::process(program_id, accounts, deposit_idx)
+        }
+        GovInstruction::CreateRewardPool => {
+            msg!("Instruction: create reward pool");
+            create_reward_pool::process(program_id, accounts)
+        }
+        GovInstruction::FundRewards { amount } => {
+            msg!("Instruction: fund rewards");
+            fund_rewards::process(program_id, accounts, amount)
+        }
+        GovInstruction::ClaimRewards => {
+            msg!("Instruction: claim rewards");
+            claim_rewards::process(program_id, accounts)
+        }
+        GovInstruction::CreateSnapshot => {
+            msg!("Instruction: create snapshot");
+            create_snapshot::process(program_id, accounts)
+        }
+        GovInstruction::UpdateSnapshotVoterWeightRecord => {
+            msg!("Instruction: update snapshot voter weight record");
+            update_snapshot_voter_weight_record::process(program_id, accounts)
+        }
+        GovInstruction::SetLockupPolicy { lockup_policy } => {
+            msg!("Instruction: set lockup policy");
+            set_lockup_policy::process(program_id, accounts, lockup_policy)
+        }
+        GovInstruction::RefreshRewardWeight => {
+            msg!("Instruction: refresh reward weight");
+            refresh_reward_weight::process(program_id, accounts)
+        }
+        GovInstruction::SetDelegationCooldown {
+            delegation_cooldown_secs,
+        } => {
+            msg!("Instruction: set delegation cooldown");
+            set_delegation_cooldown::process(program_id, accounts, delegation_cooldown_secs)
+        }
+        GovInstruction::SetGoverningSnapshot { epoch } => {
+            msg!("Instruction: set governing snapshot");
+            set_governing_snapshot::process(program_id, accounts, epoch)
+        }
+        #[cfg(feature = "time-offset")]
+        GovInstruction::SetTimeOffset { time_offset } => {
+            msg!("Instruction: set time offset");
+            set_time_offset::process(program_id, accounts, time_offset)
         }
     }
 }
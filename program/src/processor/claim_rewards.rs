@@ -0,0 +1,79 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{
+    events::{self, RewardsClaimed},
+    state::{Registrar, RewardPool, Voter},
+    utils::spl_token_util,
+};
+
+use super::reward_pool;
+
+/// Pay the rewards of the voter out of the reward vault, settled at the lower
+/// of its staked and current weight. Its current weight is staked from now on
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_info = next_account_info(account_info_iter)?; //.0
+    let registrar_info = next_account_info(account_info_iter)?; //.1
+    let voter_info = next_account_info(account_info_iter)?; //.2
+    let reward_pool_info = next_account_info(account_info_iter)?; //.3
+    let reward_vault_info = next_account_info(account_info_iter)?; //.4
+    let destination_info = next_account_info(account_info_iter)?; //.5
+    let token_program_info = next_account_info(account_info_iter)?; //.6
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if registrar_info.owner != program_id || voter_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    //unpack
+    let registrar = Registrar::try_from_slice(&registrar_info.try_borrow_data()?)?;
+    let mut voter = Voter::try_from_slice(&voter_info.try_borrow_data()?)?;
+    voter.assert_voter(authority_info.key, registrar_info.key)?;
+    let mut pool = reward_pool::load(program_id, reward_pool_info, registrar_info.key)?
+        .ok_or(ProgramError::UninitializedAccount)?;
+    spl_token_util::assert_token_account(
+        reward_vault_info,
+        &pool.reward_mint,
+        Some(reward_pool_info.key),
+    )?;
+    spl_token_util::assert_token_account(destination_info, &pool.reward_mint, None)?;
+
+    //logic
+    let weight = voter.weight(&registrar, registrar.unix_timestamp()?)?;
+    pool.stake(&mut voter, weight)?;
+    let amount = voter.rewards_owed;
+    voter.rewards_owed = 0;
+    pool.serialize(&mut *reward_pool_info.try_borrow_mut_data()?)?;
+    voter.serialize(&mut *voter_info.try_borrow_mut_data()?)?;
+
+    //release token of the {reward_vault} signed by the pool
+    if amount > 0 {
+        spl_token_util::transfer_spl_token_signed(
+            reward_vault_info,
+            destination_info,
+            reward_pool_info,
+            &RewardPool::get_seeds(registrar_info.key),
+            pool.bump,
+            amount,
+            token_program_info,
+        )?;
+    }
+
+    events::emit(RewardsClaimed {
+        voter: *voter_info.key,
+        amount,
+        reward_weight: weight,
+    })?;
+
+    Ok(())
+}
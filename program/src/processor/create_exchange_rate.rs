@@ -12,8 +12,9 @@ use solana_program::{
 
 use crate::{
     error::GovError,
+    events::{self, ExchangeRateCreated},
     state::{ExchangeRateEntry, Registrar},
-    utils::spl_token_util,
+    utils::{account_info_util::assert_canonical_pda, spl_token_util},
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 use spl_associated_token_account::instruction as ata_instruction;
@@ -66,26 +67,54 @@ pub fn process(
     let seeds =
         ExchangeRateEntry::get_voting_mint_seeds(registrar_account.key, deposit_mint_account.key);
     let deposit_mint = spl_token::state::Mint::unpack(&deposit_mint_account.data.borrow())?;
-
-    spl_token_util::create_and_initialize_mint(
-        authority_account,
-        voting_mint_account,
+    let voting_mint_bump = assert_canonical_pda(
         &seeds,
-        voting_mint_bump,
-        registrar_account.key,
-        deposit_mint.decimals,
-        token_program_account,
-        rent_info,
+        program_id,
+        voting_mint_account.key,
+        Some(voting_mint_bump),
     )?;
 
+    //no voting mint when deposits receive no voting token
+    if er.voting_token.has_token() {
+        spl_token_util::create_and_initialize_mint(
+            authority_account,
+            voting_mint_account,
+            &seeds,
+            voting_mint_bump,
+            registrar_account.key,
+            deposit_mint.decimals,
+            token_program_account,
+            rent_info,
+        )?;
+    }
+
     //logic
     if (er.rate > 0).not() {
         return Err(GovError::InvalidRate.into());
     };
-    registrar.rates[idx as usize] = er;
+    er.validate()?;
+    if er.mint != *deposit_mint_account.key {
+        return Err(GovError::TokenMintMismatch.into());
+    }
+    if er.decimals != deposit_mint.decimals {
+        return Err(GovError::InvalidDecimals.into());
+    }
+    *registrar
+        .rates
+        .get_mut(usize::from(idx))
+        .ok_or(GovError::InvalidExchangeRateIndex)? = er;
 
     //seriazlie
     registrar.serialize(&mut *registrar_account.try_borrow_mut_data()?)?;
 
+    events::emit(ExchangeRateCreated {
+        registrar: *registrar_account.key,
+        idx,
+        mint: er.mint,
+        voting_mint: *voting_mint_account.key,
+        rate: er.rate,
+        decimals: er.decimals,
+    })?;
+
     Ok(())
 }
@@ -6,14 +6,16 @@ use solana_program::{
 };
 
 use crate::{
-    state::{ExchangeRateEntry, Registrar},
-    utils::account_info_util::create_and_serialize_account_signed,
+    events::{self, RegistrarCreated},
+    state::{ExchangeRateEntry, LockupPolicy, Registrar, SNAPSHOT_HISTORY},
+    utils::account_info_util::{assert_canonical_pda, create_and_serialize_account_signed},
 };
 pub fn process(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     rate_decimals: u8,
     registrar_bump: u8,
+    lockup_policy: LockupPolicy,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
@@ -27,6 +29,15 @@ pub fn process(
     if !payer_account.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
+    lockup_policy.validate()?;
+
+    let seeds = Registrar::get_seeds(realm_account.key);
+    let registrar_bump = assert_canonical_pda(
+        &seeds,
+        program_id,
+        registrar_account.key,
+        Some(registrar_bump),
+    )?;
 
     let new_registrar = Registrar {
         authority: *authority_account.key,
@@ -35,18 +46,33 @@ pub fn process(
         bump: registrar_bump,
         rates: [ExchangeRateEntry::default(), ExchangeRateEntry::default()],
         rate_decimals,
+        early_unlock_penalty_bps: 0,
+        treasury: Pubkey::default(),
+        snapshot_epoch: 0,
+        snapshot_ts: [0; SNAPSHOT_HISTORY],
+        lockup_policy,
+        delegation_cooldown_secs: 0,
+        governing_snapshot_epoch: 0,
+        #[cfg(feature = "time-offset")]
+        time_offset: 0,
     };
 
-    let seeds = Registrar::get_seeds(realm_account.key);
-
     create_and_serialize_account_signed(
         registrar_account,
         &new_registrar,
         payer_account,
         program_id,
         &seeds,
-        Some(registrar_bump),
+        registrar_bump,
     )?;
 
+    events::emit(RegistrarCreated {
+        registrar: *registrar_account.key,
+        realm: new_registrar.realm,
+        realm_community_mint: new_registrar.realm_community_mint,
+        authority: new_registrar.authority,
+        rate_decimals,
+    })?;
+
     Ok(())
 }
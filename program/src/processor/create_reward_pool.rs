@@ -0,0 +1,82 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    events::{self, RewardPoolCreated},
+    state::{Registrar, RewardPool},
+    utils::{
+        account_info_util::{assert_canonical_pda, create_and_serialize_account_signed},
+        spl_token_util,
+    },
+};
+
+use spl_associated_token_account::instruction as ata_instruction;
+
+/// Create the reward pool of the registrar and the ATA holding its fees
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_info = next_account_info(account_info_iter)?; //.0
+    let registrar_info = next_account_info(account_info_iter)?; //.1
+    let reward_pool_info = next_account_info(account_info_iter)?; //.2
+    let reward_mint_info = next_account_info(account_info_iter)?; //.3
+    let reward_vault_info = next_account_info(account_info_iter)?; //.4
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if registrar_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    //only the registrar authority opens the pool
+    Registrar::check_and_get_mut_registrar(registrar_info, authority_info)?;
+
+    let seeds = RewardPool::get_seeds(registrar_info.key);
+    let bump = assert_canonical_pda(&seeds, program_id, reward_pool_info.key, None)?;
+    let reward_pool = RewardPool {
+        registrar: *registrar_info.key,
+        reward_mint: *reward_mint_info.key,
+        bump,
+        ..RewardPool::default()
+    };
+    create_and_serialize_account_signed(
+        reward_pool_info,
+        &reward_pool,
+        authority_info,
+        program_id,
+        &seeds,
+        bump,
+    )?;
+
+    //anyone may create the ATA beforehand
+    if reward_vault_info.data_is_empty() {
+        invoke(
+            &ata_instruction::create_associated_token_account(
+                authority_info.key,
+                reward_pool_info.key,
+                reward_mint_info.key,
+            ),
+            accounts,
+        )?;
+        msg!("RewardVault for '{:?}' created", reward_mint_info.key);
+    }
+    spl_token_util::assert_token_account(
+        reward_vault_info,
+        reward_mint_info.key,
+        Some(reward_pool_info.key),
+    )?;
+
+    events::emit(RewardPoolCreated {
+        registrar: *registrar_info.key,
+        reward_pool: *reward_pool_info.key,
+        reward_mint: *reward_mint_info.key,
+    })?;
+
+    Ok(())
+}
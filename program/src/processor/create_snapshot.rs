@@ -0,0 +1,62 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use borsh::BorshSerialize;
+
+use crate::{
+    events::{self, SnapshotCreated},
+    state::{Registrar, Snapshot},
+    utils::account_info_util::{assert_canonical_pda, create_and_serialize_account_signed},
+};
+
+/// Open the next snapshot epoch of the registrar at the current time
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_info = next_account_info(account_info_iter)?; //.0
+    let registrar_info = next_account_info(account_info_iter)?; //.1
+    let snapshot_info = next_account_info(account_info_iter)?; //.2
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if registrar_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut registrar = Registrar::check_and_get_mut_registrar(registrar_info, authority_info)?;
+    let ts = registrar.unix_timestamp()?;
+    let epoch = registrar.open_snapshot(ts)?;
+
+    let epoch_bytes = epoch.to_le_bytes();
+    let seeds = Snapshot::get_seeds(registrar_info.key, &epoch_bytes);
+    let bump = assert_canonical_pda(&seeds, program_id, snapshot_info.key, None)?;
+    let snapshot = Snapshot {
+        registrar: *registrar_info.key,
+        epoch,
+        ts,
+        bump,
+    };
+    create_and_serialize_account_signed(
+        snapshot_info,
+        &snapshot,
+        authority_info,
+        program_id,
+        &seeds,
+        bump,
+    )?;
+    registrar.serialize(&mut *registrar_info.try_borrow_mut_data()?)?;
+
+    events::emit(SnapshotCreated {
+        registrar: *registrar_info.key,
+        snapshot: *snapshot_info.key,
+        epoch,
+        ts,
+    })?;
+
+    Ok(())
+}
@@ -11,11 +11,12 @@ use solana_program::{
 };
 
 use crate::{
-    state::{DepositEntry, Registrar, Voter},
-    utils::account_info_util::create_and_serialize_account_signed,
+    events::{self, VoterCreated},
+    state::{Checkpoint, DepositEntry, Registrar, Voter, SNAPSHOT_HISTORY},
+    utils::account_info_util::{assert_canonical_pda, create_and_serialize_account_signed},
 };
 
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::BorshSerialize;
 use spl_governance_addin_api::voter_weight::VoterWeightRecord;
 
 pub const VOTER_WEIGHT_RECORD: [u8; 19] = *b"voter-weight-record";
@@ -42,19 +43,36 @@ pub fn process(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let registrar = Registrar::check_and_get_immut_registrar(registrar_account, authority_account)?;
+    //any authority may register as a voter of the registrar
+    let registrar = Registrar::check_and_get_registrar(program_id, registrar_account)?;
+
+    let seeds = Voter::get_voter_seeds(registrar_account.key, authority_account.key);
+    let voter_bump = assert_canonical_pda(&seeds, program_id, voter_account.key, Some(voter_bump))?;
+    let voter_weight_record_seeds =
+        Voter::get_voter_weight_seeds(registrar_account.key, authority_account.key);
+    let voter_weight_record_bump = assert_canonical_pda(
+        &voter_weight_record_seeds,
+        program_id,
+        voter_weight_record_account.key,
+        Some(voter_weight_record_bump),
+    )?;
 
     // state
     let new_voter = Voter {
         authority: *authority_account.key,
         registrar: *registrar_account.key,
+        delegate: Pubkey::default(),
         voter_bump,
         voter_weight_record_bump,
         deposits: [DepositEntry::default(); 10],
+        reward_weight: 0,
+        reward_index: 0,
+        rewards_owed: 0,
+        checkpoint_epoch: 0,
+        checkpoints: [Checkpoint::default(); SNAPSHOT_HISTORY],
+        delegated_at: 0,
     };
 
-    let seeds = Voter::get_voter_seeds(registrar_account.key, authority_account.key);
-
     //Why below payers are different when creating PDA ??
     create_and_serialize_account_signed(
         voter_account,
@@ -62,7 +80,7 @@ pub fn process(
         authority_account,
         program_id,
         &seeds,
-        Some(voter_bump),
+        voter_bump,
     )?;
 
     // ------ voter_weight ------
@@ -79,17 +97,21 @@ pub fn process(
         reserved: [0; 8],
     };
 
-    let voter_weight_record_seeds =
-        Voter::get_voter_weight_seeds(registrar_account.key, authority_account.key);
-
     create_and_serialize_account_signed(
         voter_weight_record_account,
         &new_voter_weight_record,
         payer_account,
         program_id, // owner should be governance program
         &voter_weight_record_seeds,
-        Some(voter_weight_record_bump),
+        voter_weight_record_bump,
     )?;
 
+    events::emit(VoterCreated {
+        registrar: *registrar_account.key,
+        voter: *voter_account.key,
+        authority: *authority_account.key,
+        voter_weight_record: *voter_weight_record_account.key,
+    })?;
+
     Ok(())
 }
@@ -0,0 +1,194 @@
+//! Accounts and token flow shared by the deposit instructions.
+//!
+//! A deposit is funded by the `depositor`: the voter authority itself for
+//! `CreateDeposit`/`UpdateDeposit`, or any signer for the `..For` variants,
+//! which pass the voter authority after the reward pool. `CreateDepositFor`
+//! also needs the signature of the voter authority.
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use borsh::BorshDeserialize;
+
+use crate::{
+    error::GovError,
+    state::{ExchangeRateEntry, Registrar, Voter, VotingTokenPolicy},
+    utils::{
+        account_info_util::Acc,
+        spl_token_util::{self, PdaMint},
+    },
+};
+
+pub struct DepositAccounts<'a, 'b> {
+    pub depositor: &'b AccountInfo<'a>,
+    pub registrar: &'b AccountInfo<'a>,
+    pub voter: &'b AccountInfo<'a>,
+    pub deposit_mint: &'b AccountInfo<'a>,
+    pub voting_mint: &'b AccountInfo<'a>,
+    pub deposit_token: &'b AccountInfo<'a>,
+    pub exchange_vault: &'b AccountInfo<'a>,
+    pub voting_token: &'b AccountInfo<'a>,
+    pub token_program: &'b AccountInfo<'a>,
+    pub reward_pool: &'b AccountInfo<'a>,
+    pub voter_authority: &'b AccountInfo<'a>,
+}
+
+impl<'a, 'b> DepositAccounts<'a, 'b> {
+    ///`on_behalf` when the depositor is not required to be the voter authority
+    pub fn parse(accounts: &'b [AccountInfo<'a>], on_behalf: bool) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+
+        let depositor = next_account_info(account_info_iter)?; //.0
+        let registrar = next_account_info(account_info_iter)?; //.1
+        let voter = next_account_info(account_info_iter)?; //.2
+                                                           //mint
+        let deposit_mint = next_account_info(account_info_iter)?; //.3
+        let voting_mint = next_account_info(account_info_iter)?; //.4
+                                                                 //token
+        let deposit_token = next_account_info(account_info_iter)?; //.5
+        let exchange_vault = next_account_info(account_info_iter)?; //.6
+        let voting_token = next_account_info(account_info_iter)?; //.7
+                                                                  //program
+        let _system_program = next_account_info(account_info_iter)?; //.8
+        let token_program = next_account_info(account_info_iter)?; //.9
+        let _associated_token_program = next_account_info(account_info_iter)?; //.10
+        let _rent = next_account_info(account_info_iter)?; //.11
+        let reward_pool = next_account_info(account_info_iter)?; //.12
+        let voter_authority = if on_behalf {
+            next_account_info(account_info_iter)? //.13
+        } else {
+            depositor
+        };
+
+        if !depositor.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            depositor,
+            registrar,
+            voter,
+            deposit_mint,
+            voting_mint,
+            deposit_token,
+            exchange_vault,
+            voting_token,
+            token_program,
+            reward_pool,
+            voter_authority,
+        })
+    }
+
+    ///unpack the registrar and the voter, and check the accounts against them.
+    /// The voter is checkpointed before its deposits change.
+    /// Also returns the voting token policy of the deposit mint
+    pub fn load(
+        &self,
+        program_id: &Pubkey,
+    ) -> Result<(Registrar, Voter, VotingTokenPolicy), ProgramError> {
+        if self.voter.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let registrar = Registrar::try_from_slice(&self.registrar.try_borrow_data()?)?;
+        let mut voter = Voter::try_from_slice(&self.voter.try_borrow_data()?)?;
+        voter.assert_voter(self.voter_authority.key, self.registrar.key)?;
+        voter.checkpoint(&registrar)?;
+
+        let voting_mint_seeds =
+            ExchangeRateEntry::get_voting_mint_seeds(self.registrar.key, self.deposit_mint.key);
+        Voter::verify_pda(&voting_mint_seeds, self.voting_mint.key)?;
+
+        spl_token_util::assert_token_account(self.deposit_token, self.deposit_mint.key, None)?;
+        //Wh no need to check the ATA,
+        //since it is also the PDA as well
+        spl_token_util::assert_token_account(
+            self.exchange_vault,
+            self.deposit_mint.key,
+            Some(self.registrar.key),
+        )?;
+
+        let policy = registrar
+            .rates
+            .iter()
+            .find(|er| er.mint == *self.deposit_mint.key)
+            .ok_or(GovError::ExchangeRateEntryNotFound)?
+            .voting_token;
+
+        Ok((registrar, voter, policy))
+    }
+
+    ///create the voting token ATA of the voter authority, paid by the depositor
+    pub fn create_voting_token(
+        &self,
+        accounts: &[AccountInfo<'a>],
+        policy: VotingTokenPolicy,
+    ) -> ProgramResult {
+        if policy.has_token() && self.voting_token.data_is_empty() {
+            invoke(
+                &spl_associated_token_account::instruction::create_associated_token_account(
+                    self.depositor.key,
+                    self.voter_authority.key,
+                    self.voting_mint.key,
+                ),
+                accounts,
+            )?;
+            msg!("Voting token ATA created")
+        }
+
+        Ok(())
+    }
+
+    pub fn assert_voting_token(&self, policy: VotingTokenPolicy) -> ProgramResult {
+        if !policy.has_token() {
+            return Ok(());
+        }
+        spl_token_util::assert_token_account(
+            self.voting_token,
+            self.voting_mint.key,
+            Some(self.voter_authority.key),
+        )?;
+
+        Ok(())
+    }
+
+    ///transfer `amount` from the depositor into the exchange vault,
+    /// and mint as many voting tokens to the voter authority when the policy has any
+    pub fn transfer_and_mint(
+        &self,
+        registrar: &Registrar,
+        amount: u64,
+        policy: VotingTokenPolicy,
+    ) -> ProgramResult {
+        spl_token_util::transfer_spl_token(
+            self.deposit_token,
+            self.exchange_vault,
+            self.depositor,
+            amount,
+            self.token_program,
+        )?;
+        if !policy.has_token() {
+            return Ok(());
+        }
+
+        let realm = registrar.realm.to_bytes();
+        let voting_mint = PdaMint {
+            mint: self.voting_mint,
+            authority: self.registrar,
+            seeds: &[&realm],
+            bump: registrar.bump,
+            token_program: self.token_program,
+        };
+        spl_token_util::mint_token_signed(
+            &voting_mint,
+            self.voting_token,
+            amount,
+            "voting_token",
+            policy == VotingTokenPolicy::Frozen,
+        )
+    }
+}
@@ -0,0 +1,53 @@
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    events::{self, EarlyUnlocked},
+    state::DepositEntry,
+    utils::spl_token_util,
+};
+
+use super::withdraw::WithdrawAccounts;
+
+/// Release a whole deposit entry before its lockup ends. The tokens still
+/// locked pay the registrar penalty to the treasury, all voting tokens are burnt
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], deposit_idx: u8) -> ProgramResult {
+    let withdraw_accounts = WithdrawAccounts::parse(accounts)?;
+    let treasury_token_info = accounts.get(10).ok_or(ProgramError::NotEnoughAccountKeys)?; //.10
+    let (registrar, mut voter, er) = withdraw_accounts.load(program_id, deposit_idx)?;
+    registrar.assert_early_unlock_enabled()?;
+    spl_token_util::assert_token_account(
+        treasury_token_info,
+        withdraw_accounts.deposit_mint.key,
+        Some(&registrar.treasury),
+    )?;
+
+    //logic
+    let curr_ts = registrar.unix_timestamp()?;
+    let d_er = &mut voter.deposits[usize::from(deposit_idx)];
+    let remaining = d_er.amount_deposited.saturating_sub(d_er.amount_withdrawn);
+    let locked = remaining.saturating_sub(d_er.withdrawable(curr_ts));
+    let penalty = registrar.early_unlock_penalty(locked, d_er.lockup.days_left(curr_ts))?;
+    *d_er = DepositEntry::default();
+    withdraw_accounts.stake_voter_weight(program_id, &registrar, &mut voter)?;
+
+    //burn voting token, then split token A between {voter} and {treasury}
+    withdraw_accounts.burn_voting_tokens(&registrar, &er, remaining)?;
+    let amount = remaining - penalty;
+    withdraw_accounts.transfer_out(&registrar, amount, withdraw_accounts.destination)?;
+    if penalty > 0 {
+        withdraw_accounts.transfer_out(&registrar, penalty, treasury_token_info)?;
+    }
+
+    events::emit(EarlyUnlocked {
+        voter: *withdraw_accounts.voter.key,
+        deposit_idx,
+        mint: *withdraw_accounts.deposit_mint.key,
+        amount,
+        penalty,
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use borsh::BorshSerialize;
+
+use crate::{
+    events::{self, RewardsFunded},
+    utils::spl_token_util,
+};
+
+use super::reward_pool;
+
+/// Transfer fees into the reward vault and share them by the staked weight.
+/// Any signer may fund the pool
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let funder_info = next_account_info(account_info_iter)?; //.0
+    let registrar_info = next_account_info(account_info_iter)?; //.1
+    let reward_pool_info = next_account_info(account_info_iter)?; //.2
+    let source_info = next_account_info(account_info_iter)?; //.3
+    let reward_vault_info = next_account_info(account_info_iter)?; //.4
+    let token_program_info = next_account_info(account_info_iter)?; //.5
+
+    if !funder_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut pool = reward_pool::load(program_id, reward_pool_info, registrar_info.key)?
+        .ok_or(ProgramError::UninitializedAccount)?;
+    spl_token_util::assert_token_account(
+        reward_vault_info,
+        &pool.reward_mint,
+        Some(reward_pool_info.key),
+    )?;
+
+    //logic
+    pool.fund(amount)?;
+    pool.serialize(&mut *reward_pool_info.try_borrow_mut_data()?)?;
+
+    spl_token_util::transfer_spl_token(
+        source_info,
+        reward_vault_info,
+        funder_info,
+        amount,
+        token_program_info,
+    )?;
+
+    events::emit(RewardsFunded {
+        reward_pool: *reward_pool_info.key,
+        amount,
+        total_weight: pool.total_weight,
+        reward_index: pool.reward_index,
+    })?;
+
+    Ok(())
+}
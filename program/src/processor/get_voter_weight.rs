@@ -0,0 +1,45 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::GovError,
+    return_data::{DepositWeight, VoterWeight},
+};
+
+use super::update_voter_weight_record::{load_delegators, load_voter, total_weight};
+
+/// Compute, without writing any account, the weight `UpdateVoterWeightRecord`
+/// would record now and at `at_ts`, and return it through `set_return_data`.
+/// Deposits are the current ones even for an `at_ts` in the past
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], at_ts: i64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let registrar_info = next_account_info(account_info_iter)?; //.0
+    let voter_info = next_account_info(account_info_iter)?; //.1
+    let delegator_infos = account_info_iter.as_slice(); //.2..
+
+    let (registrar, voter) = load_voter(program_id, registrar_info, voter_info)?;
+    let delegators = load_delegators(
+        program_id,
+        registrar_info,
+        voter_info,
+        &voter,
+        delegator_infos,
+    )?;
+
+    let curr_ts = registrar.unix_timestamp()?;
+    VoterWeight {
+        voter: *voter_info.key,
+        curr_ts,
+        at_ts,
+        weight: total_weight(&registrar, &voter, &delegators, curr_ts)?,
+        weight_at: total_weight(&registrar, &voter, &delegators, at_ts)?,
+        delegate: voter.delegate,
+        delegators: u8::try_from(delegators.len()).map_err(|_| GovError::MathOverflow)?,
+        deposits: DepositWeight::of_voter(&voter, &registrar, curr_ts, at_ts)?,
+    }
+    .set_return_data()
+}
@@ -0,0 +1,40 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use borsh::BorshSerialize;
+
+use crate::events::{self, RewardWeightRefreshed};
+
+use super::{reward_pool, update_voter_weight_record::load_voter};
+
+/// Stake the current weight of the voter in the reward pool, settling its
+/// rewards at the lower of its staked and current weight. Anyone may call it,
+/// so an expired lockup stops earning, and a voter that deposited before the
+/// pool was created starts to
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let registrar_info = next_account_info(account_info_iter)?; //.0
+    let voter_info = next_account_info(account_info_iter)?; //.1
+    let reward_pool_info = next_account_info(account_info_iter)?; //.2
+
+    let (registrar, mut voter) = load_voter(program_id, registrar_info, voter_info)?;
+    let mut pool = reward_pool::load(program_id, reward_pool_info, registrar_info.key)?
+        .ok_or(ProgramError::UninitializedAccount)?;
+
+    let weight = voter.weight(&registrar, registrar.unix_timestamp()?)?;
+    pool.stake(&mut voter, weight)?;
+    pool.serialize(&mut *reward_pool_info.try_borrow_mut_data()?)?;
+    voter.serialize(&mut *voter_info.try_borrow_mut_data()?)?;
+
+    events::emit(RewardWeightRefreshed {
+        voter: *voter_info.key,
+        reward_weight: weight,
+    })?;
+
+    Ok(())
+}
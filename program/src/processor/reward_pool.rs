@@ -0,0 +1,52 @@
+//! Reward pool bookkeeping shared by every instruction changing deposits:
+//! the voter stakes its new weight, so the fees funded from then on are
+//! shared according to it.
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{
+    state::{Registrar, RewardPool, Voter},
+    utils::account_info_util::Acc,
+};
+
+///unpack the reward pool PDA of `registrar`, `None` until `CreateRewardPool`
+pub fn load(
+    program_id: &Pubkey,
+    reward_pool_info: &AccountInfo,
+    registrar: &Pubkey,
+) -> Result<Option<RewardPool>, ProgramError> {
+    RewardPool::verify_pda(&RewardPool::get_seeds(registrar), reward_pool_info.key)?;
+    if reward_pool_info.data_is_empty() {
+        return Ok(None);
+    }
+    if reward_pool_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    Ok(Some(RewardPool::try_from_slice(
+        &reward_pool_info.try_borrow_data()?,
+    )?))
+}
+
+///stake the current weight of `voter` in the reward pool, if any, then serialize the voter
+pub fn stake_voter_weight(
+    program_id: &Pubkey,
+    reward_pool_info: &AccountInfo,
+    registrar_info: &AccountInfo,
+    registrar: &Registrar,
+    voter: &mut Voter,
+    voter_info: &AccountInfo,
+) -> ProgramResult {
+    if let Some(mut reward_pool) = load(program_id, reward_pool_info, registrar_info.key)? {
+        let weight = voter.weight(registrar, registrar.unix_timestamp()?)?;
+        reward_pool.stake(voter, weight)?;
+        reward_pool.serialize(&mut *reward_pool_info.try_borrow_mut_data()?)?;
+    }
+    voter.serialize(&mut *voter_info.try_borrow_mut_data()?)?;
+
+    Ok(())
+}
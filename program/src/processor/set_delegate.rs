@@ -0,0 +1,63 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::GovError,
+    events::{self, DelegateSet},
+    state::{Registrar, Voter},
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+/// Point the weight of the voter at `delegate`, or back at itself.
+/// The weight moves once the delegation cooldown of the registrar passed,
+/// and the delegation cannot change again before
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], delegate: Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_info = next_account_info(account_info_iter)?; //.0
+    let registrar_info = next_account_info(account_info_iter)?; //.1
+    let voter_info = next_account_info(account_info_iter)?; //.2
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if registrar_info.owner != program_id || voter_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let registrar = Registrar::try_from_slice(&registrar_info.try_borrow_data()?)?;
+    let mut voter: Voter = Voter::try_from_slice(&voter_info.try_borrow_data()?)?;
+    voter.assert_voter(authority_info.key, registrar_info.key)?;
+
+    registrar.assert_delegation_enabled()?;
+
+    let curr_ts = registrar.unix_timestamp()?;
+    if curr_ts < registrar.delegation_settled_at(voter.delegated_at) {
+        return Err(GovError::DelegationCooldown.into());
+    }
+    //snapshots taken so far keep the current holder
+    voter.checkpoint(&registrar)?;
+    voter.delegated_at = curr_ts;
+
+    //delegating to oneself is the same as revoking
+    voter.delegate = if delegate == voter.authority {
+        Pubkey::default()
+    } else {
+        delegate
+    };
+    voter.serialize(&mut *voter_info.try_borrow_mut_data()?)?;
+
+    events::emit(DelegateSet {
+        voter: *voter_info.key,
+        authority: voter.authority,
+        delegate: voter.delegate,
+    })?;
+
+    Ok(())
+}
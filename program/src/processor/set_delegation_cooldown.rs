@@ -0,0 +1,66 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use borsh::BorshSerialize;
+use spl_governance::state::governance::get_governance_data_for_realm;
+
+use crate::{
+    error::GovError,
+    events::{self, DelegationCooldownSet},
+    state::Registrar,
+};
+
+/// Set the delegation cooldown, checked against the max voting time of
+/// the governances of the realm passed as remaining accounts
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    delegation_cooldown_secs: u32,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_info = next_account_info(account_info_iter)?; //.0
+    let registrar_info = next_account_info(account_info_iter)?; //.1
+    let realm_info = next_account_info(account_info_iter)?; //.2
+    let governance_infos = account_info_iter.as_slice(); //.3..
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if registrar_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut registrar = Registrar::check_and_get_mut_registrar(registrar_info, authority_info)?;
+    if *realm_info.key != registrar.realm {
+        return Err(GovError::RealmMismatch.into());
+    }
+    if governance_infos.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    //the realm is owned by the governance program owning its governances
+    for governance_info in governance_infos {
+        let governance =
+            get_governance_data_for_realm(realm_info.owner, governance_info, &registrar.realm)?;
+        if delegation_cooldown_secs < governance.config.max_voting_time {
+            return Err(GovError::InvalidDelegationCooldown.into());
+        }
+    }
+    if delegation_cooldown_secs == 0 {
+        return Err(GovError::InvalidDelegationCooldown.into());
+    }
+
+    registrar.delegation_cooldown_secs = delegation_cooldown_secs;
+    registrar.serialize(&mut *registrar_info.try_borrow_mut_data()?)?;
+
+    events::emit(DelegationCooldownSet {
+        registrar: *registrar_info.key,
+        delegation_cooldown_secs,
+    })?;
+
+    Ok(())
+}
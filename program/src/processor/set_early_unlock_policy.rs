@@ -0,0 +1,50 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use borsh::BorshSerialize;
+
+use crate::{
+    error::GovError,
+    events::{self, EarlyUnlockPolicySet},
+    state::{Registrar, MAX_PENALTY_BPS},
+};
+
+/// Configure the penalty of early unlocks and the treasury receiving it
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    penalty_bps: u16,
+    treasury: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_info = next_account_info(account_info_iter)?; //.0
+    let registrar_info = next_account_info(account_info_iter)?; //.1
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if registrar_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if penalty_bps > MAX_PENALTY_BPS {
+        return Err(GovError::InvalidPenalty.into());
+    }
+
+    let mut registrar = Registrar::check_and_get_mut_registrar(registrar_info, authority_info)?;
+    registrar.early_unlock_penalty_bps = penalty_bps;
+    registrar.treasury = treasury;
+    registrar.serialize(&mut *registrar_info.try_borrow_mut_data()?)?;
+
+    events::emit(EarlyUnlockPolicySet {
+        registrar: *registrar_info.key,
+        penalty_bps,
+        treasury,
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use borsh::BorshSerialize;
+
+use crate::{
+    events::{self, GoverningSnapshotSet},
+    state::Registrar,
+};
+
+/// Designate the snapshot the voter weight records are updated from, 0 for the live weight
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], epoch: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_info = next_account_info(account_info_iter)?; //.0
+    let registrar_info = next_account_info(account_info_iter)?; //.1
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if registrar_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut registrar = Registrar::check_and_get_mut_registrar(registrar_info, authority_info)?;
+    registrar.set_governing_snapshot(epoch)?;
+    registrar.serialize(&mut *registrar_info.try_borrow_mut_data()?)?;
+
+    events::emit(GoverningSnapshotSet {
+        registrar: *registrar_info.key,
+        epoch,
+    })?;
+
+    Ok(())
+}
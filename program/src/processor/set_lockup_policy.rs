@@ -0,0 +1,44 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use borsh::BorshSerialize;
+
+use crate::{
+    events::{self, LockupPolicySet},
+    state::{LockupPolicy, Registrar},
+};
+
+/// Replace the lockup policy checked by every new deposit
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lockup_policy: LockupPolicy,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_info = next_account_info(account_info_iter)?; //.0
+    let registrar_info = next_account_info(account_info_iter)?; //.1
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if registrar_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    lockup_policy.validate()?;
+
+    let mut registrar = Registrar::check_and_get_mut_registrar(registrar_info, authority_info)?;
+    registrar.lockup_policy = lockup_policy;
+    registrar.serialize(&mut *registrar_info.try_borrow_mut_data()?)?;
+
+    events::emit(LockupPolicySet {
+        registrar: *registrar_info.key,
+        lockup_policy,
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::GovError,
+    events::{self, UnlockStarted},
+    state::{Registrar, Voter},
+};
+
+use borsh::BorshDeserialize;
+
+use super::reward_pool;
+
+/// Start the countdown of a Constant lockup deposit
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], deposit_idx: u8) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_info = next_account_info(account_info_iter)?; //.0
+    let registrar_info = next_account_info(account_info_iter)?; //.1
+    let voter_info = next_account_info(account_info_iter)?; //.2
+    let reward_pool_info = next_account_info(account_info_iter)?; //.3
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if registrar_info.owner != program_id || voter_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    //unpack
+    let registrar = Registrar::try_from_slice(&registrar_info.try_borrow_data()?)?;
+    let mut voter = Voter::try_from_slice(&voter_info.try_borrow_data()?)?;
+    voter.assert_voter(authority_info.key, registrar_info.key)?;
+    voter.checkpoint(&registrar)?;
+
+    //logic
+    let d_er = voter
+        .deposits
+        .get_mut(usize::from(deposit_idx))
+        .filter(|d_er| d_er.is_used)
+        .ok_or(GovError::InvalidDepositId)?;
+    d_er.lockup.start_unlock(registrar.unix_timestamp()?)?;
+    let lockup = d_er.lockup;
+    reward_pool::stake_voter_weight(
+        program_id,
+        reward_pool_info,
+        registrar_info,
+        &registrar,
+        &mut voter,
+        voter_info,
+    )?;
+
+    events::emit(UnlockStarted {
+        voter: *voter_info.key,
+        deposit_idx,
+        lockup,
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    borsh::try_from_slice_unchecked,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    error::GovError,
+    events::{self, SnapshotVoterWeightUpdated},
+    state::{Registrar, Snapshot, Voter},
+    utils::account_info_util::Acc,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use spl_governance_addin_api::voter_weight::VoterWeightRecord;
+
+use super::update_voter_weight_record::{load_other_voters, load_voter};
+
+/// Refresh the VoterWeightRecord of `voter.authority` with its weight as of
+/// the governing snapshot, so a proposal is not swayed by deposits or delegations
+/// changed since. The delegators passed must have delegated to it as of the snapshot
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let registrar_info = next_account_info(account_info_iter)?; //.0
+    let snapshot_info = next_account_info(account_info_iter)?; //.1
+    let voter_info = next_account_info(account_info_iter)?; //.2
+    let voter_weight_record_info = next_account_info(account_info_iter)?; //.3
+    let delegator_infos = account_info_iter.as_slice(); //.4..
+
+    if snapshot_info.owner != program_id || voter_weight_record_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let (registrar, voter) = load_voter(program_id, registrar_info, voter_info)?;
+
+    let snapshot = Snapshot::try_from_slice(&snapshot_info.try_borrow_data()?)?;
+    let epoch_bytes = snapshot.epoch.to_le_bytes();
+    Snapshot::verify_pda(
+        &Snapshot::get_seeds(registrar_info.key, &epoch_bytes),
+        snapshot_info.key,
+    )?;
+    registrar.assert_governing_snapshot(snapshot.epoch)?;
+
+    let seeds = Voter::get_voter_weight_seeds(registrar_info.key, &voter.authority);
+    Voter::verify_pda(&seeds, voter_weight_record_info.key)?;
+
+    let delegators = load_other_voters(program_id, registrar_info, voter_info, delegator_infos)?;
+    let weight = snapshot_weight(&registrar, &voter, &delegators, &snapshot)?;
+
+    let mut record: VoterWeightRecord =
+        try_from_slice_unchecked(&voter_weight_record_info.try_borrow_data()?)?;
+    if record.governing_token_owner != voter.authority {
+        return Err(GovError::AuthorityMismatch.into());
+    }
+
+    //delegations may still change after this slot
+    record.voter_weight = weight;
+    record.voter_weight_expiry = Some(Clock::get()?.slot);
+    record.serialize(&mut *voter_weight_record_info.try_borrow_mut_data()?)?;
+
+    events::emit(SnapshotVoterWeightUpdated {
+        voter: *voter_info.key,
+        voter_weight_record: *voter_weight_record_info.key,
+        epoch: snapshot.epoch,
+        voter_weight: weight,
+        delegators: u8::try_from(delegators.len()).map_err(|_| GovError::MathOverflow)?,
+    })?;
+
+    Ok(())
+}
+
+/// Same as `total_weight`, as of `snapshot`: delegators are the voters whose
+/// weight `voter.authority` held then, whoever they delegate to now
+fn snapshot_weight(
+    registrar: &Registrar,
+    voter: &Voter,
+    delegators: &[Voter],
+    snapshot: &Snapshot,
+) -> Result<u64, ProgramError> {
+    let holder = voter.authority;
+    let own = voter.snapshot_checkpoint(registrar, snapshot)?;
+    let own = if own.holder == holder { own.weight } else { 0 };
+    delegators.iter().try_fold(own, |acc, delegator| {
+        let checkpoint = delegator.snapshot_checkpoint(registrar, snapshot)?;
+        if checkpoint.holder != holder {
+            return Err(GovError::DelegateMismatch.into());
+        }
+        acc.checked_add(checkpoint.weight)
+            .ok_or_else(|| GovError::MathOverflow.into())
+    })
+}
@@ -0,0 +1,154 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    borsh::try_from_slice_unchecked,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    error::GovError,
+    events::{self, VoterWeightUpdated},
+    state::{Registrar, Voter},
+    utils::account_info_util::Acc,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use spl_governance_addin_api::voter_weight::VoterWeightRecord;
+
+/// Refresh the VoterWeightRecord of `voter.authority`
+///
+/// The weight is the voter's own weight (unless delegated away)
+/// plus the weight of every delegating voter passed as remaining accounts.
+/// Deposits with transferable voting tokens carry no weight, and a voter
+/// whose delegation changed within `Registrar::delegation_cooldown_secs` counts for nobody.
+/// Fails while a snapshot governs, the weight is then counted as of it
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let registrar_info = next_account_info(account_info_iter)?; //.0
+    let voter_info = next_account_info(account_info_iter)?; //.1
+    let voter_weight_record_info = next_account_info(account_info_iter)?; //.2
+    let delegator_infos = account_info_iter.as_slice(); //.3..
+
+    if voter_weight_record_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let (registrar, voter) = load_voter(program_id, registrar_info, voter_info)?;
+    registrar.assert_live_weight()?;
+
+    let seeds = Voter::get_voter_weight_seeds(registrar_info.key, &voter.authority);
+    Voter::verify_pda(&seeds, voter_weight_record_info.key)?;
+
+    let delegators = load_delegators(
+        program_id,
+        registrar_info,
+        voter_info,
+        &voter,
+        delegator_infos,
+    )?;
+
+    let clock = Clock::get()?;
+    let weight = total_weight(&registrar, &voter, &delegators, registrar.unix_timestamp()?)?;
+
+    let mut record: VoterWeightRecord =
+        try_from_slice_unchecked(&voter_weight_record_info.try_borrow_data()?)?;
+    if record.governing_token_owner != voter.authority {
+        return Err(GovError::AuthorityMismatch.into());
+    }
+
+    //decaying weight is only valid within the current slot
+    record.voter_weight = weight;
+    record.voter_weight_expiry = Some(clock.slot);
+    record.serialize(&mut *voter_weight_record_info.try_borrow_mut_data()?)?;
+
+    events::emit(VoterWeightUpdated {
+        voter: *voter_info.key,
+        voter_weight_record: *voter_weight_record_info.key,
+        voter_weight: weight,
+        delegators: u8::try_from(delegators.len()).map_err(|_| GovError::MathOverflow)?,
+    })?;
+
+    Ok(())
+}
+
+/// Unpack the registrar and a voter of it, both owned by the program
+pub fn load_voter(
+    program_id: &Pubkey,
+    registrar_info: &AccountInfo,
+    voter_info: &AccountInfo,
+) -> Result<(Registrar, Voter), ProgramError> {
+    if registrar_info.owner != program_id || voter_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let registrar = Registrar::try_from_slice(&registrar_info.try_borrow_data()?)?;
+    let voter: Voter = Voter::try_from_slice(&voter_info.try_borrow_data()?)?;
+    if voter.registrar != *registrar_info.key {
+        return Err(GovError::RegistrarMismatch.into());
+    }
+
+    Ok((registrar, voter))
+}
+
+/// Unpack the voters delegating to `voter`, each passed at most once
+pub fn load_delegators(
+    program_id: &Pubkey,
+    registrar_info: &AccountInfo,
+    voter_info: &AccountInfo,
+    voter: &Voter,
+    delegator_infos: &[AccountInfo],
+) -> Result<Vec<Voter>, ProgramError> {
+    let delegators = load_other_voters(program_id, registrar_info, voter_info, delegator_infos)?;
+    if delegators.iter().any(|d| d.delegate != voter.authority) {
+        return Err(GovError::DelegateMismatch.into());
+    }
+
+    Ok(delegators)
+}
+
+/// Unpack voters of the registrar other than `voter_info`, each passed at most once
+pub fn load_other_voters(
+    program_id: &Pubkey,
+    registrar_info: &AccountInfo,
+    voter_info: &AccountInfo,
+    delegator_infos: &[AccountInfo],
+) -> Result<Vec<Voter>, ProgramError> {
+    let mut counted: Vec<&Pubkey> = Vec::with_capacity(delegator_infos.len());
+    let mut delegators = Vec::with_capacity(delegator_infos.len());
+    for delegator_info in delegator_infos {
+        if delegator_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if delegator_info.key == voter_info.key || counted.contains(&delegator_info.key) {
+            return Err(GovError::DuplicateDelegator.into());
+        }
+        counted.push(delegator_info.key);
+
+        let delegator: Voter = Voter::try_from_slice(&delegator_info.try_borrow_data()?)?;
+        if delegator.registrar != *registrar_info.key {
+            return Err(GovError::RegistrarMismatch.into());
+        }
+        delegators.push(delegator);
+    }
+
+    Ok(delegators)
+}
+
+/// The weight at `ts` of the voter and of its delegators that `voter.authority` holds,
+/// see `Voter::weight_holder`
+pub fn total_weight(
+    registrar: &Registrar,
+    voter: &Voter,
+    delegators: &[Voter],
+    ts: i64,
+) -> Result<u64, ProgramError> {
+    let holder = voter.authority;
+    let own = voter.weight_for(registrar, &holder, ts)?;
+    delegators.iter().try_fold(own, |acc, delegator| {
+        acc.checked_add(delegator.weight_for(registrar, &holder, ts)?)
+            .ok_or_else(|| GovError::MathOverflow.into())
+    })
+}
@@ -0,0 +1,226 @@
+//! Accounts and token flow shared by the instructions releasing a deposit:
+//! `Withdraw` for unlocked tokens, `EarlyUnlock` for the whole deposit.
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    error::GovError,
+    events::{self, Withdrawn},
+    state::{ExchangeRateEntry, Registrar, Voter, VotingTokenPolicy},
+    utils::{
+        account_info_util::Acc,
+        spl_token_util::{self, PdaMint},
+    },
+};
+
+use borsh::BorshDeserialize;
+
+use super::reward_pool;
+
+pub struct WithdrawAccounts<'a, 'b> {
+    pub authority: &'b AccountInfo<'a>,
+    pub registrar: &'b AccountInfo<'a>,
+    pub voter: &'b AccountInfo<'a>,
+    pub deposit_mint: &'b AccountInfo<'a>,
+    pub voting_mint: &'b AccountInfo<'a>,
+    pub destination: &'b AccountInfo<'a>,
+    pub exchange_vault: &'b AccountInfo<'a>,
+    pub voting_token: &'b AccountInfo<'a>,
+    pub token_program: &'b AccountInfo<'a>,
+    pub reward_pool: &'b AccountInfo<'a>,
+}
+
+impl<'a, 'b> WithdrawAccounts<'a, 'b> {
+    pub fn parse(accounts: &'b [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+
+        let authority = next_account_info(account_info_iter)?; //.0
+        let registrar = next_account_info(account_info_iter)?; //.1
+        let voter = next_account_info(account_info_iter)?; //.2
+                                                           //mint
+        let deposit_mint = next_account_info(account_info_iter)?; //.3
+        let voting_mint = next_account_info(account_info_iter)?; //.4
+                                                                 //token
+        let destination = next_account_info(account_info_iter)?; //.5
+        let exchange_vault = next_account_info(account_info_iter)?; //.6
+        let voting_token = next_account_info(account_info_iter)?; //.7
+                                                                  //program
+        let token_program = next_account_info(account_info_iter)?; //.8
+        let reward_pool = next_account_info(account_info_iter)?; //.9
+
+        if !authority.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Ok(Self {
+            authority,
+            registrar,
+            voter,
+            deposit_mint,
+            voting_mint,
+            destination,
+            exchange_vault,
+            voting_token,
+            token_program,
+            reward_pool,
+        })
+    }
+
+    ///unpack the registrar and the voter, and check the accounts against
+    /// the used deposit entry `deposit_idx`. The voter is checkpointed before
+    /// its deposits change. Also returns its exchange rate
+    pub fn load(
+        &self,
+        program_id: &Pubkey,
+        deposit_idx: u8,
+    ) -> Result<(Registrar, Voter, ExchangeRateEntry), ProgramError> {
+        if self.registrar.owner != program_id || self.voter.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let registrar = Registrar::try_from_slice(&self.registrar.try_borrow_data()?)?;
+        let mut voter = Voter::try_from_slice(&self.voter.try_borrow_data()?)?;
+        voter.assert_voter(self.authority.key, self.registrar.key)?;
+        voter.checkpoint(&registrar)?;
+
+        let d_er = voter
+            .deposits
+            .get(usize::from(deposit_idx))
+            .filter(|d_er| d_er.is_used)
+            .ok_or(GovError::InvalidDepositId)?;
+        let er = *registrar
+            .rates
+            .get(usize::from(d_er.rate_idx))
+            .ok_or(GovError::ExchangeRateEntryNotFound)?;
+        if er.mint != *self.deposit_mint.key {
+            return Err(GovError::TokenMintMismatch.into());
+        }
+
+        let voting_mint_seeds =
+            ExchangeRateEntry::get_voting_mint_seeds(self.registrar.key, self.deposit_mint.key);
+        Voter::verify_pda(&voting_mint_seeds, self.voting_mint.key)?;
+
+        spl_token_util::assert_token_account(self.destination, self.deposit_mint.key, None)?;
+        spl_token_util::assert_token_account(
+            self.exchange_vault,
+            self.deposit_mint.key,
+            Some(self.registrar.key),
+        )?;
+
+        Ok((registrar, voter, er))
+    }
+
+    ///burn `amount` voting tokens, when the exchange rate has any, signed by the
+    /// voter authority. Frozen ones sit in its ATA. Transferable ones may be burnt
+    /// from any holder's account that approved the voter authority as delegate,
+    /// the tokens released then go to a `destination` of the same owner
+    pub fn burn_voting_tokens(
+        &self,
+        registrar: &Registrar,
+        er: &ExchangeRateEntry,
+        amount: u64,
+    ) -> ProgramResult {
+        if !er.voting_token.has_token() {
+            return Ok(());
+        }
+        let owner = (er.voting_token == VotingTokenPolicy::Frozen).then_some(self.authority.key);
+        let holder =
+            spl_token_util::assert_token_account(self.voting_token, self.voting_mint.key, owner)?;
+        //the receipt is redeemed by whoever holds it
+        if er.voting_token == VotingTokenPolicy::Transferable {
+            spl_token_util::assert_token_account(
+                self.destination,
+                self.deposit_mint.key,
+                Some(&holder.owner),
+            )?;
+        }
+
+        let realm = registrar.realm.to_bytes();
+        let voting_mint = PdaMint {
+            mint: self.voting_mint,
+            authority: self.registrar,
+            seeds: &[&realm],
+            bump: registrar.bump,
+            token_program: self.token_program,
+        };
+        spl_token_util::burn_token(
+            &voting_mint,
+            self.voting_token,
+            self.authority,
+            amount,
+            "voting_token",
+        )
+    }
+
+    ///stake the weight left to the voter in the reward pool, and serialize it
+    pub fn stake_voter_weight(
+        &self,
+        program_id: &Pubkey,
+        registrar: &Registrar,
+        voter: &mut Voter,
+    ) -> ProgramResult {
+        reward_pool::stake_voter_weight(
+            program_id,
+            self.reward_pool,
+            self.registrar,
+            registrar,
+            voter,
+            self.voter,
+        )
+    }
+
+    ///move `amount` of token A out of the {exchange_vault}, signed by the registrar
+    pub fn transfer_out(
+        &self,
+        registrar: &Registrar,
+        amount: u64,
+        destination: &'b AccountInfo<'a>,
+    ) -> ProgramResult {
+        let seeds: &[&[_]] = &[&registrar.realm.to_bytes()];
+        spl_token_util::transfer_spl_token_signed(
+            self.exchange_vault,
+            destination,
+            self.registrar,
+            seeds,
+            registrar.bump,
+            amount,
+            self.token_program,
+        )
+    }
+}
+
+/// Withdraw unlocked tokens of a deposit entry back to the voter,
+/// burning as many voting tokens when the exchange rate has any
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_idx: u8,
+    amount: u64,
+) -> ProgramResult {
+    let withdraw_accounts = WithdrawAccounts::parse(accounts)?;
+    let (registrar, mut voter, er) = withdraw_accounts.load(program_id, deposit_idx)?;
+
+    //logic
+    let d_er = &mut voter.deposits[usize::from(deposit_idx)];
+    d_er.withdraw(amount, registrar.unix_timestamp()?)?;
+    let remaining = d_er.amount_deposited.saturating_sub(d_er.amount_withdrawn);
+    withdraw_accounts.stake_voter_weight(program_id, &registrar, &mut voter)?;
+
+    //burn voting token, then release token A from {exchange_vault} to {voter}
+    withdraw_accounts.burn_voting_tokens(&registrar, &er, amount)?;
+    withdraw_accounts.transfer_out(&registrar, amount, withdraw_accounts.destination)?;
+
+    events::emit(Withdrawn {
+        voter: *withdraw_accounts.voter.key,
+        deposit_idx,
+        mint: *withdraw_accounts.deposit_mint.key,
+        amount,
+        remaining,
+    })?;
+
+    Ok(())
+}
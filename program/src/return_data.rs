@@ -0,0 +1,141 @@
+//! Borsh payloads returned through `set_return_data`.
+//!
+//! Callers read them with `get_return_data` after a CPI, or from the
+//! `Program return: <program_id> <base64>` line of a simulated transaction.
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{
+    program::{get_return_data, set_return_data},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::state::{Registrar, Voter};
+
+/// Prefix of the log line written by the runtime for return data
+pub const PROGRAM_RETURN: &str = "Program return: ";
+
+/// Returned by `GetVoterWeight`
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct VoterWeight {
+    pub voter: Pubkey,
+    pub curr_ts: i64,
+    pub at_ts: i64,
+    // what `UpdateVoterWeightRecord` would record, at `curr_ts` and `at_ts`
+    pub weight: u64,
+    pub weight_at: u64,
+    // `Pubkey::default()` unless the voter delegated its own weight away
+    pub delegate: Pubkey,
+    pub delegators: u8,
+    // used deposit entries of the voter itself
+    pub deposits: Vec<DepositWeight>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Clone, Debug, PartialEq, Eq)]
+pub struct DepositWeight {
+    pub deposit_idx: u8,
+    pub rate_idx: u8,
+    // false when the voting token policy of the rate grants no weight
+    pub has_weight: bool,
+    pub voting_power: u64,
+    pub voting_power_at: u64,
+    // native amounts at `curr_ts`: unlocked so far, and still to withdraw of it
+    pub vested: u64,
+    pub withdrawable: u64,
+}
+
+impl DepositWeight {
+    /// Breakdown of every used deposit of `voter` at `curr_ts` and `at_ts`
+    pub fn of_voter(
+        voter: &Voter,
+        registrar: &Registrar,
+        curr_ts: i64,
+        at_ts: i64,
+    ) -> Result<Vec<Self>, ProgramError> {
+        (0_u8..)
+            .zip(voter.deposits.iter())
+            .filter(|(_, d)| d.is_used)
+            .map(|(deposit_idx, d)| {
+                let er = registrar.rate(d.rate_idx)?;
+                Ok(Self {
+                    deposit_idx,
+                    rate_idx: d.rate_idx,
+                    has_weight: er.voting_token.has_weight(),
+                    voting_power: d.voting_power(er, curr_ts)?,
+                    voting_power_at: d.voting_power(er, at_ts)?,
+                    vested: d.vested(curr_ts),
+                    withdrawable: d.withdrawable(curr_ts),
+                })
+            })
+            .collect()
+    }
+}
+
+impl VoterWeight {
+    pub fn set_return_data(&self) -> Result<(), ProgramError> {
+        set_return_data(&self.try_to_vec()?);
+        Ok(())
+    }
+
+    /// Read the return data of the last CPI into this program
+    pub fn get_return_data() -> Option<Self> {
+        let (program_id, data) = get_return_data()?;
+        if program_id != crate::id() {
+            return None;
+        }
+        Self::try_from_slice(&data).ok()
+    }
+
+    /// Decode a `Program return: <program_id> <base64>` log line of this program
+    pub fn from_log(log: &str) -> Option<Self> {
+        let (program_id, encoded) = log.strip_prefix(PROGRAM_RETURN)?.split_once(' ')?;
+        if program_id != crate::id().to_string() {
+            return None;
+        }
+        Self::try_from_slice(&base64::decode(encoded).ok()?).ok()
+    }
+
+    /// Decode the return data found in the transaction logs
+    pub fn from_logs<S: AsRef<str>>(logs: &[S]) -> Option<Self> {
+        logs.iter().find_map(|log| Self::from_log(log.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_from_log_line() {
+        let weight = VoterWeight {
+            voter: Pubkey::new_unique(),
+            curr_ts: 10,
+            at_ts: 20,
+            weight: 5,
+            weight_at: 3,
+            delegate: Pubkey::default(),
+            delegators: 0,
+            deposits: vec![DepositWeight {
+                deposit_idx: 1,
+                rate_idx: 0,
+                has_weight: true,
+                voting_power: 5,
+                voting_power_at: 3,
+                vested: 10,
+                withdrawable: 4,
+            }],
+        };
+        let log = format!(
+            "{}{} {}",
+            PROGRAM_RETURN,
+            crate::id(),
+            base64::encode(weight.try_to_vec().unwrap())
+        );
+
+        assert_eq!(
+            VoterWeight::from_logs(&["Program log: Instruction: get voter weight", &log]),
+            Some(weight)
+        );
+        let other = format!("{}{} AA==", PROGRAM_RETURN, Pubkey::new_unique());
+        assert_eq!(VoterWeight::from_log(&other), None);
+    }
+}
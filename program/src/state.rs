@@ -1,8 +1,10 @@
 mod registrar;
-pub use registrar::Registrar;
+pub use registrar::{Registrar, MAX_PENALTY_BPS};
 
 mod exchange_rate_entry;
-pub use exchange_rate_entry::ExchangeRateEntry;
+pub use exchange_rate_entry::{
+    ExchangeRateEntry, VotingTokenPolicy, RATE_SCALE, RATE_SCALE_DECIMALS, SCALED_FACTOR_BASE,
+};
 
 mod voter;
 pub use voter::Voter;
@@ -10,6 +12,15 @@ pub use voter::Voter;
 mod deposit_entry;
 pub use deposit_entry::DepositEntry;
 
+mod reward_pool;
+pub use reward_pool::{RewardPool, REWARD_INDEX_SCALE};
+
+mod snapshot;
+pub use snapshot::{Checkpoint, Snapshot, SNAPSHOT_HISTORY};
+
 mod lockup;
-pub use lockup::{Lockup, LockupKind, MAX_DAYS_LOCKED, SECS_PER_DAY};
+pub use lockup::{
+    Lockup, LockupKind, LockupPolicy, DAYS_PER_MONTH, MAX_DAYS_LOCKED, MAX_SECS_LOCKED,
+    SECS_PER_DAY,
+};
 //logic first --> reprc(C), using Zeroable trait and sub-trait "POD" --> adding macro to write DRY
@@ -2,11 +2,13 @@ use std::cell::{Ref, RefMut};
 
 use crate::{
     error::GovError,
-    state::{Lockup, Registrar, Voter},
+    state::{ExchangeRateEntry, Lockup, Registrar, Voter},
 };
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
-use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+};
 
 /// Bookkeeping for a single deposit for a given mint and lockup schedule.
 #[derive(BorshDeserialize, BorshSerialize, PartialEq, BorshSchema, Default, Copy, Clone, Debug)]
@@ -32,6 +34,64 @@ pub struct DepositEntry {
 
 //could be optimized by RefMut (?)
 impl DepositEntry {
+    /// Amount in the native currency unlocked at `curr_ts`, withdrawn or not:
+    /// whole elapsed days or months of vesting kinds, everything once expired
+    pub fn vested(&self, curr_ts: i64) -> u64 {
+        if !self.is_used {
+            return 0;
+        }
+        self.lockup.unlocked(self.amount_deposited, curr_ts)
+    }
+
+    /// Amount in the native currency that can be withdrawn at `curr_ts`:
+    /// what vested so far, minus what was already withdrawn
+    pub fn withdrawable(&self, curr_ts: i64) -> u64 {
+        let remaining = self.amount_deposited.saturating_sub(self.amount_withdrawn);
+        self.vested(curr_ts)
+            .saturating_sub(self.amount_withdrawn)
+            .min(remaining)
+    }
+
+    /// Take `amount` out of the deposit, scaling `amount_scaled` down pro rata.
+    /// The entry is freed once fully withdrawn
+    pub fn withdraw(&mut self, amount: u64, curr_ts: i64) -> ProgramResult {
+        let remaining = self.amount_deposited.saturating_sub(self.amount_withdrawn);
+        if amount > self.withdrawable(curr_ts) {
+            return Err(GovError::InsufficientUnlockedTokens.into());
+        }
+        if amount == remaining {
+            *self = Self::default();
+            return Ok(());
+        }
+
+        let scaled_out =
+            u128::from(self.amount_scaled) * u128::from(amount) / u128::from(remaining);
+        self.amount_scaled -= u64::try_from(scaled_out).map_err(|_| GovError::MathOverflow)?;
+        self.amount_withdrawn += amount;
+
+        Ok(())
+    }
+
+    /// Voting power of this deposit at `curr_ts` under the factors of its
+    /// exchange rate `er`, zero for unused entries. The lockup curve applies to
+    /// the scaled amount of the whole deposit: tranches already withdrawn were
+    /// unlocked and earn no extra weight anyway
+    pub fn voting_power(&self, er: &ExchangeRateEntry, curr_ts: i64) -> Result<u64, ProgramError> {
+        if !self.is_used {
+            return Ok(0);
+        }
+        let remaining = self.amount_deposited.saturating_sub(self.amount_withdrawn);
+        let deposited_scaled = (u128::from(self.amount_scaled) * u128::from(self.amount_deposited))
+            .checked_div(u128::from(remaining))
+            .and_then(|scaled| u64::try_from(scaled).ok())
+            .unwrap_or(self.amount_scaled);
+        let locked =
+            self.lockup
+                .voting_power(deposited_scaled, er.lockup_saturation_secs, curr_ts)?;
+        er.vote_weight(self.amount_scaled, locked.min(self.amount_scaled))
+    }
+
+    /// Add `amount` to the used entry `update_idx`, see `Lockup::check_top_up`
     pub fn update_deposit(
         voter: &mut Voter, //to access the struct state
         registrar: &Registrar,
@@ -40,26 +100,35 @@ impl DepositEntry {
         voter_info: &AccountInfo,
         deposit_mint: &AccountInfo,
     ) -> ProgramResult {
-        let amount_scaled = {
-            let er_idx = registrar
-                .rates
-                .iter()
-                .position(|i| i.mint == *deposit_mint.key)
-                .ok_or(GovError::ExchangeRateEntryNotFound)?;
-            let er = registrar.rates[er_idx];
-            registrar.convert(&er, amount)?
-        };
+        let er_idx = registrar
+            .rates
+            .iter()
+            .position(|i| i.mint == *deposit_mint.key)
+            .ok_or(GovError::ExchangeRateEntryNotFound)?;
+        let amount_scaled = registrar.convert(&registrar.rates[er_idx], amount)?;
         //verify
-        if !(voter.deposits.len() > update_idx as usize) {
-            return Err(GovError::InvalidDepositId.into());
+        let d_er = voter
+            .deposits
+            .get_mut(usize::from(update_idx))
+            .filter(|d_er| d_er.is_used)
+            .ok_or(GovError::InvalidDepositId)?;
+        if usize::from(d_er.rate_idx) != er_idx {
+            return Err(GovError::TokenMintMismatch.into());
         }
+        d_er.lockup.check_top_up(
+            registrar.lockup_policy.min_lockup_days,
+            registrar.unix_timestamp()?,
+        )?;
 
         //logic
-        let mut d_er = &mut voter.deposits[update_idx as usize];
-        d_er.amount_deposited += amount;
-        d_er.amount_scaled += amount_scaled;
-
-        msg!("d_er{:?}", d_er);
+        d_er.amount_deposited = d_er
+            .amount_deposited
+            .checked_add(amount)
+            .ok_or(GovError::MathOverflow)?;
+        d_er.amount_scaled = d_er
+            .amount_scaled
+            .checked_add(amount_scaled)
+            .ok_or(GovError::MathOverflow)?;
 
         //serialize
         voter.serialize(&mut *voter_info.try_borrow_mut_data()?)?;
@@ -67,3 +136,96 @@ impl DepositEntry {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{LockupKind, SECS_PER_DAY};
+
+    #[test]
+    fn withdraw_after_expiry_only() {
+        let mut d = DepositEntry {
+            is_used: true,
+            amount_deposited: 100,
+            amount_scaled: 1_000,
+            lockup: Lockup {
+                kind: LockupKind::Cliff,
+                start_ts: 0,
+                end_ts: 10 * SECS_PER_DAY,
+                unlocking: false,
+                padding: [0; 15],
+            },
+            ..DepositEntry::default()
+        };
+
+        assert_eq!(d.withdrawable(SECS_PER_DAY), 0);
+        assert_eq!(
+            d.withdraw(1, SECS_PER_DAY),
+            Err(GovError::InsufficientUnlockedTokens.into())
+        );
+
+        let end_ts = d.lockup.end_ts;
+        d.withdraw(30, end_ts).unwrap();
+        assert_eq!(d.withdrawable(end_ts), 70);
+        assert_eq!((d.amount_withdrawn, d.amount_scaled), (30, 700));
+        assert!(d.withdraw(71, end_ts).is_err());
+
+        d.withdraw(70, end_ts).unwrap();
+        assert_eq!(d, DepositEntry::default());
+    }
+
+    #[test]
+    fn monthly_withdraws_tranche_by_tranche() {
+        let mut d = DepositEntry {
+            is_used: true,
+            amount_deposited: 300,
+            amount_scaled: 1_095,
+            lockup: Lockup {
+                kind: LockupKind::Monthly,
+                start_ts: 0,
+                end_ts: 90 * SECS_PER_DAY,
+                unlocking: false,
+                padding: [0; 15],
+            },
+            ..DepositEntry::default()
+        };
+
+        let er = ExchangeRateEntry::default();
+        let month = 30 * SECS_PER_DAY;
+        assert_eq!(d.withdrawable(month - 1), 0);
+        assert_eq!(d.withdrawable(month), 100);
+        assert_eq!(d.voting_power(&er, month).unwrap(), 30);
+
+        d.withdraw(100, month).unwrap();
+        assert_eq!(d.withdrawable(month), 0);
+        assert_eq!((d.amount_withdrawn, d.amount_scaled), (100, 730));
+        // withdrawing an unlocked tranche leaves the locked ones their weight
+        assert_eq!(d.voting_power(&er, month).unwrap(), 30);
+        assert_eq!(d.withdrawable(2 * month), 100);
+    }
+
+    #[test]
+    fn daily_vests_every_whole_day() {
+        let mut d = DepositEntry {
+            is_used: true,
+            amount_deposited: 1_000,
+            amount_scaled: 1_000,
+            lockup: Lockup {
+                kind: LockupKind::Daily,
+                start_ts: 0,
+                end_ts: 10 * SECS_PER_DAY,
+                unlocking: false,
+                padding: [0; 15],
+            },
+            ..DepositEntry::default()
+        };
+
+        let ts = 2 * SECS_PER_DAY + SECS_PER_DAY / 2;
+        assert_eq!((d.vested(ts), d.withdrawable(ts)), (200, 200));
+        d.withdraw(150, ts).unwrap();
+        assert_eq!((d.vested(ts), d.withdrawable(ts)), (200, 50));
+        assert_eq!(d.withdrawable(3 * SECS_PER_DAY), 150);
+        assert_eq!(d.withdrawable(d.lockup.end_ts), 850);
+        assert_eq!(DepositEntry::default().vested(ts), 0);
+    }
+}
@@ -1,18 +1,77 @@
-use crate::utils::account_info_util::Acc;
+use crate::{error::GovError, state::MAX_SECS_LOCKED, utils::account_info_util::Acc};
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+/// Vote weight factors are scaled by this: `SCALED_FACTOR_BASE` is a factor of 1
+pub const SCALED_FACTOR_BASE: u64 = 1_000_000_000;
+
+/// Decimals of an exchange rate: `RATE_SCALE` is a rate of 1
+pub const RATE_SCALE_DECIMALS: u32 = 9;
+
+/// Exchange rates are scaled by this, so a rate can be a fraction of a unit
+pub const RATE_SCALE: u64 = 10_u64.pow(RATE_SCALE_DECIMALS);
 
 /// Exchange rate for an asset that can be used to mint voting rights
-#[derive(BorshDeserialize, BorshSerialize, BorshSchema, Default, Clone, Copy, Debug, PartialEq)]
+#[derive(BorshDeserialize, BorshSerialize, BorshSchema, Clone, Copy, Debug, PartialEq)]
 pub struct ExchangeRateEntry {
-    pub mint: Pubkey, //mint for this entry
-    pub rate: u64,    // Exchange rate into the common currency.
+    pub mint: Pubkey,                    //mint for this entry
+    pub rate: u64,    // Exchange rate into the common currency, scaled by RATE_SCALE.
     pub decimals: u8, // Mint decimals.
+    pub voting_token: VotingTokenPolicy, // Voting token of the deposits.
+
+    // weight of every scaled token deposited, locked or not
+    pub baseline_vote_weight_scaled_factor: u64,
+    // extra weight of a scaled token locked for `lockup_saturation_secs` or more
+    pub max_extra_lockup_vote_weight_scaled_factor: u64,
+    // lockup earning the whole extra weight, shorter ones earn it pro rata. At most `MAX_SECS_LOCKED`
+    pub lockup_saturation_secs: u64,
+}
+
+/// No baseline, and a locked token weighs one scaled token at `MAX_DAYS_LOCKED`
+impl Default for ExchangeRateEntry {
+    fn default() -> Self {
+        Self {
+            mint: Pubkey::default(),
+            rate: 0,
+            decimals: 0,
+            voting_token: VotingTokenPolicy::default(),
+            baseline_vote_weight_scaled_factor: 0,
+            max_extra_lockup_vote_weight_scaled_factor: SCALED_FACTOR_BASE,
+            lockup_saturation_secs: MAX_SECS_LOCKED,
+        }
+    }
+}
+
+/// Voting tokens minted 1:1 for the deposited amount, and burnt on withdrawal
+#[derive(
+    BorshDeserialize, BorshSerialize, BorshSchema, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub enum VotingTokenPolicy {
+    /// soulbound: the voter's voting token account stays frozen
+    #[default]
+    Frozen,
+    /// tradable receipt, redeemed by its holder: the withdrawn tokens go to
+    /// the owner of the burnt receipts. The deposits do not count toward
+    /// the voter weight since receipts may change hands
+    Transferable,
+    /// no voting mint, no voting token
+    Disabled,
+}
+
+impl VotingTokenPolicy {
+    pub fn has_token(self) -> bool {
+        self != Self::Disabled
+    }
+
+    /// Whether deposits of this exchange rate count toward the voter weight
+    pub fn has_weight(self) -> bool {
+        self != Self::Transferable
+    }
 }
 
 impl Acc for ExchangeRateEntry {
     fn get_max_size(&self) -> Option<usize> {
-        Some(32 + 8 + 1)
+        Some(32 + 8 + 1 + 1 + 8 * 3)
     }
 }
 
@@ -23,4 +82,64 @@ impl ExchangeRateEntry {
     ) -> [&'a [u8]; 2] {
         [registrar.as_ref(), deposit_mint.as_ref()]
     }
+
+    /// The lockup saturation must be positive and at most `MAX_SECS_LOCKED`
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if !(1..=MAX_SECS_LOCKED).contains(&self.lockup_saturation_secs) {
+            return Err(GovError::InvalidVoteWeightFactors.into());
+        }
+        Ok(())
+    }
+
+    /// Vote weight of `amount_scaled` tokens, `locked` of them weighted by
+    /// the lockup curve already: the baseline of all plus the extra of the locked
+    pub fn vote_weight(&self, amount_scaled: u64, locked: u64) -> Result<u64, ProgramError> {
+        let weight = (u128::from(amount_scaled)
+            * u128::from(self.baseline_vote_weight_scaled_factor)
+            + u128::from(locked) * u128::from(self.max_extra_lockup_vote_weight_scaled_factor))
+            / u128::from(SCALED_FACTOR_BASE);
+
+        Ok(u64::try_from(weight).map_err(|_| GovError::MathOverflow)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vote_weight_adds_baseline_and_lockup_bonus() {
+        let er = ExchangeRateEntry::default();
+        assert_eq!(er.vote_weight(1_000, 400), Ok(400));
+
+        let lp = ExchangeRateEntry {
+            baseline_vote_weight_scaled_factor: SCALED_FACTOR_BASE / 10,
+            max_extra_lockup_vote_weight_scaled_factor: 3 * SCALED_FACTOR_BASE,
+            ..er
+        };
+        assert_eq!(lp.vote_weight(1_000, 400), Ok(100 + 1_200));
+        assert_eq!(lp.vote_weight(1_000, 0), Ok(100));
+        assert_eq!(
+            lp.vote_weight(u64::MAX, u64::MAX),
+            Err(GovError::MathOverflow.into())
+        );
+
+        assert_eq!(er.validate(), Ok(()));
+        let unsaturated = ExchangeRateEntry {
+            lockup_saturation_secs: 0,
+            ..er
+        };
+        assert_eq!(
+            unsaturated.validate(),
+            Err(GovError::InvalidVoteWeightFactors.into())
+        );
+        let oversaturated = ExchangeRateEntry {
+            lockup_saturation_secs: MAX_SECS_LOCKED + 1,
+            ..er
+        };
+        assert_eq!(
+            oversaturated.validate(),
+            Err(GovError::InvalidVoteWeightFactors.into())
+        );
+    }
 }
@@ -1,4 +1,7 @@
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, program_error::ProgramError};
+
+use crate::error::GovError;
 
 /// Seconds in one day.
 pub const SECS_PER_DAY: i64 = 86_400;
@@ -6,6 +9,12 @@ pub const SECS_PER_DAY: i64 = 86_400;
 /// Maximum number of days one can lock for.
 pub const MAX_DAYS_LOCKED: u64 = 1095;
 
+/// `MAX_DAYS_LOCKED` in seconds, the longest lockup saturation of an exchange rate.
+pub const MAX_SECS_LOCKED: u64 = MAX_DAYS_LOCKED * SECS_PER_DAY.unsigned_abs();
+
+/// Days in one month of a Monthly lockup.
+pub const DAYS_PER_MONTH: u64 = 30;
+
 #[derive(BorshDeserialize, BorshSerialize, PartialEq, BorshSchema, Default, Copy, Clone, Debug)]
 ///Provide ether (1. Daily or Cliff vesting with maximum locked daysv in 3 years
 pub struct Lockup {
@@ -14,18 +23,88 @@ pub struct Lockup {
     pub start_ts: i64,
     // End of the lockup.
     pub end_ts: i64,
+    // Constant lockups only: true once the voter started to unlock.
+    pub unlocking: bool,
     // Empty bytes for future upgrades.
-    pub padding: [u8; 16],
+    pub padding: [u8; 15],
+}
+
+/// Lockups a registrar accepts for new deposits, set at `CreateRegistrar`
+/// and through `SetLockupPolicy`
+#[derive(BorshDeserialize, BorshSerialize, PartialEq, Eq, BorshSchema, Clone, Copy, Debug)]
+pub struct LockupPolicy {
+    pub min_lockup_days: u16,
+    pub max_lockup_days: u16, // at most `MAX_DAYS_LOCKED`
+    pub allowed_kinds: u8,    // `LockupKind::mask` of every kind allowed
+}
+
+/// Every kind, for any number of days up to `MAX_DAYS_LOCKED`
+impl Default for LockupPolicy {
+    fn default() -> Self {
+        Self {
+            min_lockup_days: 0,
+            max_lockup_days: u16::try_from(MAX_DAYS_LOCKED).unwrap_or(u16::MAX),
+            allowed_kinds: Self::ALL_KINDS,
+        }
+    }
+}
+
+impl LockupPolicy {
+    /// Mask of every `LockupKind`
+    pub const ALL_KINDS: u8 = {
+        let mut mask = 0;
+        let mut i = 0;
+        while i < LockupKind::ALL.len() {
+            mask |= LockupKind::ALL[i].mask();
+            i += 1;
+        }
+        mask
+    };
+
+    /// A policy allows at least one kind, for a range of days within `MAX_DAYS_LOCKED`
+    pub fn validate(&self) -> ProgramResult {
+        let valid = self.min_lockup_days <= self.max_lockup_days
+            && u64::from(self.max_lockup_days) <= MAX_DAYS_LOCKED
+            && self.allowed_kinds != 0
+            && self.allowed_kinds & !Self::ALL_KINDS == 0;
+        if !valid {
+            return Err(GovError::InvalidLockupPolicy.into());
+        }
+        Ok(())
+    }
+
+    /// Check a new lockup of `kind` for `days`, returned as unsigned
+    pub fn check_lockup(&self, kind: LockupKind, days: i32) -> Result<u64, ProgramError> {
+        if self.allowed_kinds & kind.mask() == 0 {
+            return Err(GovError::LockupKindNotAllowed.into());
+        }
+        let lockup_days = u64::try_from(days).map_err(|_| GovError::InvalidLockupDays)?;
+        if lockup_days < u64::from(self.min_lockup_days)
+            || lockup_days > u64::from(self.max_lockup_days).min(MAX_DAYS_LOCKED)
+        {
+            return Err(GovError::InvalidLockupDays.into());
+        }
+        //vesting kinds lock for a whole number of periods
+        if matches!(kind.period_days(), Some(period_days) if lockup_days % period_days != 0) {
+            return Err(GovError::InvalidLockupDays.into());
+        }
+
+        Ok(lockup_days)
+    }
 }
 
-#[derive(BorshDeserialize, BorshSerialize, PartialEq, BorshSchema, Clone, Copy, Debug)]
+#[derive(BorshDeserialize, BorshSerialize, PartialEq, Eq, BorshSchema, Clone, Copy, Debug)]
 pub enum LockupKind {
     /// let n = days_left
     /// let m = max_days = 1095
-    ///voting_power = (n / m) * amount
-    Daily, //linear unlock
     ///voting_power = (1 / m) * (amount / n) * [(n * [(n + 1)]) / 2],
+    Daily, //linear unlock
+    ///voting_power = (n / m) * amount
     Cliff, // unlock all at once under specific situation
+    ///n stays the whole lockup period until `StartUnlock`, then decays as Cliff
+    Constant, //never unlocks until the voter asks for it
+    ///as Daily, with one tranche every `DAYS_PER_MONTH` days: n counts whole months
+    Monthly, //unlock in equal monthly tranches
 }
 
 impl Default for LockupKind {
@@ -33,3 +112,373 @@ impl Default for LockupKind {
         LockupKind::Cliff
     }
 }
+
+impl LockupKind {
+    /// Every kind, in discriminant order
+    pub const ALL: [Self; 4] = [Self::Daily, Self::Cliff, Self::Constant, Self::Monthly];
+
+    /// Bit of the kind in `LockupPolicy::allowed_kinds`
+    pub const fn mask(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// Days between two tranches of a vesting kind, `None` when unlocking all at once
+    pub const fn period_days(self) -> Option<u64> {
+        match self {
+            Self::Daily => Some(1),
+            Self::Monthly => Some(DAYS_PER_MONTH),
+            Self::Cliff | Self::Constant => None,
+        }
+    }
+}
+
+impl Lockup {
+    /// Number of days in the whole lockup period, rounded up.
+    pub fn total_days(&self) -> u64 {
+        Self::days_ceil(self.end_ts.saturating_sub(self.start_ts))
+    }
+
+    /// Whether the whole lockup period is over at `curr_ts`.
+    /// A constant lockup never expires before it starts unlocking
+    pub const fn expired(&self, curr_ts: i64) -> bool {
+        !self.frozen() && curr_ts >= self.end_ts
+    }
+
+    /// Number of days until `end_ts`, rounded up. Zero once the lockup expired.
+    /// The whole period for a constant lockup not unlocking yet
+    pub fn days_left(&self, curr_ts: i64) -> u64 {
+        if self.frozen() {
+            return self.total_days();
+        }
+        Self::days_ceil(self.end_ts.saturating_sub(curr_ts))
+    }
+
+    /// Number of periods of `period_days` in the whole lockup, rounded up
+    pub fn total_periods(&self, period_days: u64) -> u64 {
+        Self::periods_ceil(self.end_ts.saturating_sub(self.start_ts), period_days)
+    }
+
+    /// Number of periods of `period_days` until `end_ts`, rounded up
+    pub fn periods_left(&self, curr_ts: i64, period_days: u64) -> u64 {
+        Self::periods_ceil(self.end_ts.saturating_sub(curr_ts), period_days)
+    }
+
+    /// Part of `amount` unlocked at `curr_ts`: one tranche per elapsed period
+    /// for Daily and Monthly lockups, everything once expired for the other kinds.
+    /// The tranche of a period in progress is still locked
+    pub fn unlocked(&self, amount: u64, curr_ts: i64) -> u64 {
+        if self.expired(curr_ts) {
+            return amount;
+        }
+        self.kind.period_days().map_or(0, |period_days| {
+            let total = self.total_periods(period_days);
+            let vested = total.saturating_sub(self.periods_left(curr_ts, period_days));
+            (u128::from(amount) * u128::from(vested))
+                .checked_div(u128::from(total))
+                .and_then(|unlocked| u64::try_from(unlocked).ok())
+                .unwrap_or(0)
+        })
+    }
+
+    /// A top-up vests with the rest of the deposit, so only a Cliff or Constant
+    /// lockup locking it for `min_days` or more is topped up: a vesting or
+    /// expired one would release part or all of it at once
+    pub fn check_top_up(&self, min_days: u16, curr_ts: i64) -> ProgramResult {
+        if self.kind.period_days().is_some()
+            || self.expired(curr_ts)
+            || self.days_left(curr_ts) < u64::from(min_days)
+        {
+            return Err(GovError::TopUpNotAllowed.into());
+        }
+        Ok(())
+    }
+
+    /// Start the countdown of a constant lockup: the same period now runs
+    /// from `curr_ts`, after which the lockup behaves as Cliff
+    pub fn start_unlock(&mut self, curr_ts: i64) -> ProgramResult {
+        if !self.frozen() {
+            return Err(GovError::InvalidLockupKind.into());
+        }
+        let period = self.end_ts.saturating_sub(self.start_ts);
+        self.start_ts = curr_ts;
+        self.end_ts = curr_ts.checked_add(period).ok_or(GovError::MathOverflow)?;
+        self.unlocking = true;
+        Ok(())
+    }
+
+    // a constant lockup keeps its remaining period until it starts unlocking
+    const fn frozen(&self) -> bool {
+        matches!(self.kind, LockupKind::Constant) && !self.unlocking
+    }
+
+    fn days_ceil(secs: i64) -> u64 {
+        Self::periods_ceil(secs, 1)
+    }
+
+    fn periods_ceil(secs: i64, period_days: u64) -> u64 {
+        let secs = u64::try_from(secs).unwrap_or(0);
+        let period = SECS_PER_DAY.unsigned_abs() * period_days;
+        secs / period + u64::from(secs % period != 0)
+    }
+
+    /// Voting power of `amount` (already scaled by the exchange rate) at `curr_ts`:
+    /// all of it while `saturation_secs` or more are left, pro rata below.
+    /// The saturation is capped at `MAX_SECS_LOCKED`
+    pub fn voting_power(
+        &self,
+        amount: u64,
+        saturation_secs: u64,
+        curr_ts: i64,
+    ) -> Result<u64, ProgramError> {
+        let n = self.days_left(curr_ts);
+        if n == 0 || amount == 0 {
+            return Ok(0);
+        }
+        let m = u128::from(saturation_secs.clamp(1, MAX_SECS_LOCKED));
+        let amount = u128::from(amount);
+
+        let power = self.kind.period_days().map_or_else(
+            || amount * (u128::from(n) * DAY).min(m) / m,
+            |period_days| self.tranches_power(amount, curr_ts, period_days, m),
+        );
+
+        u64::try_from(power).map_err(|_| GovError::MathOverflow.into())
+    }
+
+    // every period one tranche of `amount / total_periods` unlocks,
+    // each tranche weighted by its own secs left (capped at m):
+    // the tranches left past m weigh fully, the first `capped` ones p, 2p, ...
+    fn tranches_power(&self, amount: u128, curr_ts: i64, period_days: u64, m: u128) -> u128 {
+        let left = self.periods_left(curr_ts, period_days);
+        let total = u128::from(self.total_periods(period_days).max(left));
+        let left = u128::from(left);
+        let p = u128::from(period_days) * DAY;
+        let capped = left.min(m / p);
+        amount * (left - capped) / total + amount * p * capped * (capped + 1) / (2 * m * total)
+    }
+}
+
+// `SECS_PER_DAY`, widened for the voting power math
+const DAY: u128 = SECS_PER_DAY.unsigned_abs() as u128;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lockup(kind: LockupKind, days: i64) -> Lockup {
+        Lockup {
+            kind,
+            start_ts: 0,
+            end_ts: days * SECS_PER_DAY,
+            unlocking: false,
+            padding: [0; 15],
+        }
+    }
+
+    #[test]
+    fn all_kinds_covers_every_variant() {
+        for (i, kind) in LockupKind::ALL.into_iter().enumerate() {
+            // exhaustive, so a new variant fails to compile until listed in ALL
+            let idx = match kind {
+                LockupKind::Daily => 0,
+                LockupKind::Cliff => 1,
+                LockupKind::Constant => 2,
+                LockupKind::Monthly => 3,
+            };
+            assert_eq!(idx, i);
+            assert_eq!(kind as usize, i);
+            assert_ne!(LockupPolicy::ALL_KINDS & kind.mask(), 0);
+        }
+        assert_eq!(
+            LockupPolicy::ALL_KINDS.count_ones() as usize,
+            LockupKind::ALL.len()
+        );
+    }
+
+    #[test]
+    fn cliff_decays_linearly() {
+        let l = lockup(LockupKind::Cliff, 1_095);
+        assert_eq!(l.voting_power(1_095, MAX_SECS_LOCKED, 0).unwrap(), 1_095);
+        assert_eq!(
+            l.voting_power(1_095, MAX_SECS_LOCKED, 95 * SECS_PER_DAY)
+                .unwrap(),
+            1_000
+        );
+        assert_eq!(l.voting_power(1_095, MAX_SECS_LOCKED, l.end_ts).unwrap(), 0);
+    }
+
+    #[test]
+    fn saturation_caps_the_lockup_counted() {
+        let year = MAX_SECS_LOCKED / 3;
+        let l = lockup(LockupKind::Cliff, 1_095);
+        assert_eq!(l.voting_power(1_000, year, 0).unwrap(), 1_000);
+        assert_eq!(
+            l.voting_power(1_000, year, 730 * SECS_PER_DAY).unwrap(),
+            1_000
+        );
+        assert_eq!(
+            l.voting_power(1_000, year, 1_000 * SECS_PER_DAY).unwrap(),
+            260
+        );
+
+        // every tranche left saturates
+        let l = lockup(LockupKind::Monthly, 90);
+        let month = 30 * SECS_PER_DAY;
+        assert_eq!(l.voting_power(900, month.unsigned_abs(), 0).unwrap(), 900);
+        assert_eq!(
+            l.voting_power(900, month.unsigned_abs(), month).unwrap(),
+            600
+        );
+        assert_eq!(l.voting_power(900, 0, month).unwrap(), 600);
+    }
+
+    #[test]
+    fn daily_sums_tranches() {
+        let l = lockup(LockupKind::Daily, 10);
+        // (1 / m) * (amount / n) * n(n+1)/2 with n = 10
+        assert_eq!(
+            l.voting_power(1_095_000, MAX_SECS_LOCKED, 0).unwrap(),
+            5_500
+        );
+        assert_eq!(
+            l.voting_power(1_095_000, MAX_SECS_LOCKED, 9 * SECS_PER_DAY)
+                .unwrap(),
+            100
+        );
+    }
+
+    #[test]
+    fn constant_is_frozen_until_unlock() {
+        let mut l = lockup(LockupKind::Constant, 1_095);
+        let later = 2_000 * SECS_PER_DAY;
+        assert!(!l.expired(later));
+        assert_eq!(
+            l.voting_power(1_095, MAX_SECS_LOCKED, later).unwrap(),
+            1_095
+        );
+
+        l.start_unlock(later).unwrap();
+        assert_eq!(
+            (l.start_ts, l.end_ts),
+            (later, later + 1_095 * SECS_PER_DAY)
+        );
+        assert_eq!(
+            l.voting_power(1_095, MAX_SECS_LOCKED, later + 95 * SECS_PER_DAY)
+                .unwrap(),
+            1_000
+        );
+        assert!(l.expired(l.end_ts));
+        assert_eq!(
+            l.start_unlock(l.end_ts),
+            Err(GovError::InvalidLockupKind.into())
+        );
+        assert!(lockup(LockupKind::Cliff, 1).start_unlock(0).is_err());
+    }
+
+    #[test]
+    fn monthly_unlocks_and_decays_per_month() {
+        let l = lockup(LockupKind::Monthly, 90);
+        // tranches of 365 weighted by 90, 60 and 30 days left
+        assert_eq!(l.voting_power(1_095, MAX_SECS_LOCKED, 0).unwrap(), 60);
+        assert_eq!(
+            l.voting_power(1_095, MAX_SECS_LOCKED, SECS_PER_DAY)
+                .unwrap(),
+            60
+        );
+        assert_eq!(
+            l.voting_power(1_095, MAX_SECS_LOCKED, 30 * SECS_PER_DAY)
+                .unwrap(),
+            30
+        );
+
+        assert_eq!(l.unlocked(900, 29 * SECS_PER_DAY), 0);
+        assert_eq!(l.unlocked(900, 30 * SECS_PER_DAY), 300);
+        assert_eq!(l.unlocked(900, 89 * SECS_PER_DAY), 600);
+        assert_eq!(l.unlocked(900, l.end_ts), 900);
+        assert_eq!(
+            lockup(LockupKind::Cliff, 90).unlocked(900, 89 * SECS_PER_DAY),
+            0
+        );
+    }
+
+    #[test]
+    fn daily_unlocks_whole_days() {
+        let l = lockup(LockupKind::Daily, 10);
+        assert_eq!(l.unlocked(1_000, -SECS_PER_DAY), 0);
+        assert_eq!(l.unlocked(1_000, SECS_PER_DAY - 1), 0);
+        assert_eq!(l.unlocked(1_000, SECS_PER_DAY), 100);
+        assert_eq!(l.unlocked(1_000, 9 * SECS_PER_DAY + SECS_PER_DAY / 2), 900);
+        assert_eq!(l.unlocked(1_000, l.end_ts), 1_000);
+    }
+
+    #[test]
+    fn top_ups_need_a_running_cliff_or_constant_lockup() {
+        let refused = Err(GovError::TopUpNotAllowed.into());
+        let half = 5 * SECS_PER_DAY;
+        assert_eq!(lockup(LockupKind::Daily, 10).check_top_up(0, half), refused);
+        assert_eq!(lockup(LockupKind::Monthly, 60).check_top_up(0, 0), refused);
+
+        let cliff = lockup(LockupKind::Cliff, 10);
+        assert_eq!(cliff.check_top_up(0, half), Ok(()));
+        assert_eq!(cliff.check_top_up(6, half), refused);
+        assert_eq!(cliff.check_top_up(0, cliff.end_ts), refused);
+
+        // locked for its whole period until it starts unlocking
+        let mut constant = lockup(LockupKind::Constant, 10);
+        assert_eq!(constant.check_top_up(10, 20 * SECS_PER_DAY), Ok(()));
+        constant.start_unlock(20 * SECS_PER_DAY).unwrap();
+        assert_eq!(constant.check_top_up(0, 30 * SECS_PER_DAY), refused);
+    }
+
+    #[test]
+    fn policy_bounds_days_and_kinds() {
+        let any = LockupPolicy::default();
+        assert_eq!(any.validate(), Ok(()));
+        assert_eq!(any.check_lockup(LockupKind::Cliff, 0), Ok(0));
+        assert_eq!(any.check_lockup(LockupKind::Daily, 1_095), Ok(1_095));
+        assert_eq!(
+            any.check_lockup(LockupKind::Cliff, -1),
+            Err(GovError::InvalidLockupDays.into())
+        );
+        assert_eq!(
+            any.check_lockup(LockupKind::Monthly, 45),
+            Err(GovError::InvalidLockupDays.into())
+        );
+
+        let policy = LockupPolicy {
+            min_lockup_days: 30,
+            max_lockup_days: 365,
+            allowed_kinds: LockupKind::Cliff.mask() | LockupKind::Monthly.mask(),
+        };
+        assert_eq!(policy.validate(), Ok(()));
+        assert_eq!(policy.check_lockup(LockupKind::Monthly, 360), Ok(360));
+        assert_eq!(
+            policy.check_lockup(LockupKind::Cliff, 29),
+            Err(GovError::InvalidLockupDays.into())
+        );
+        assert_eq!(
+            policy.check_lockup(LockupKind::Cliff, 366),
+            Err(GovError::InvalidLockupDays.into())
+        );
+        assert_eq!(
+            policy.check_lockup(LockupKind::Constant, 30),
+            Err(GovError::LockupKindNotAllowed.into())
+        );
+    }
+
+    #[test]
+    fn policy_needs_a_kind_and_ordered_days() {
+        let invalid = Err(GovError::InvalidLockupPolicy.into());
+        let any = LockupPolicy::default();
+        let policy = |min_lockup_days, max_lockup_days, allowed_kinds| LockupPolicy {
+            min_lockup_days,
+            max_lockup_days,
+            allowed_kinds,
+        };
+        assert_eq!(policy(10, 9, any.allowed_kinds).validate(), invalid);
+        assert_eq!(policy(0, 1_096, any.allowed_kinds).validate(), invalid);
+        assert_eq!(policy(0, 10, 0).validate(), invalid);
+        assert_eq!(policy(0, 10, 0b1_0000).validate(), invalid);
+        assert_eq!(policy(10, 10, LockupKind::Daily.mask()).validate(), Ok(()));
+    }
+}
@@ -1,11 +1,21 @@
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
-    pubkey::Pubkey,
+    account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+    program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
 };
 
 //Account
-use crate::{error::GovError, state::ExchangeRateEntry, utils::account_info_util::Acc};
+use crate::{
+    error::GovError,
+    state::{
+        ExchangeRateEntry, LockupPolicy, Snapshot, MAX_DAYS_LOCKED, RATE_SCALE_DECIMALS,
+        SNAPSHOT_HISTORY,
+    },
+    utils::account_info_util::Acc,
+};
+
+/// Basis points of a penalty taking the whole locked amount.
+pub const MAX_PENALTY_BPS: u16 = 10_000;
 
 //exchange rate for an asset that can mint the voting rights
 #[derive(Debug, BorshDeserialize, BorshSchema, BorshSerialize, Default, Copy, Clone, PartialEq)]
@@ -18,6 +28,31 @@ pub struct Registrar {
     pub rates: [ExchangeRateEntry; 2], // The length should be adjusted for one's use case.
 
     pub rate_decimals: u8, // The decimals to use when converting deposits into a common currency.
+
+    // penalty of an early unlock at the maximum lockup, set through `SetEarlyUnlockPolicy`
+    pub early_unlock_penalty_bps: u16,
+    // owner of the token accounts receiving the penalties, early unlock is disabled while unset
+    pub treasury: Pubkey,
+
+    // latest epoch opened by `CreateSnapshot`, 0 before the first one
+    pub snapshot_epoch: u64,
+    // timestamps of the latest snapshots, the one of epoch `e` at `e % SNAPSHOT_HISTORY`
+    pub snapshot_ts: [i64; SNAPSHOT_HISTORY],
+
+    // lockups accepted for new deposits, set at `CreateRegistrar` and through `SetLockupPolicy`
+    pub lockup_policy: LockupPolicy,
+
+    // time a delegation change takes to move the weight, at least the longest voting time
+    // of the realm's governances, set through `SetDelegationCooldown`, delegation is disabled while 0
+    pub delegation_cooldown_secs: u32,
+
+    // snapshot counted by `UpdateSnapshotVoterWeightRecord`, set through `SetGoverningSnapshot`,
+    // 0 for the live weight of `UpdateVoterWeightRecord`, which is disabled otherwise
+    pub governing_snapshot_epoch: u64,
+
+    // added to the clock by every lockup computation, set through `SetTimeOffset`
+    #[cfg(feature = "time-offset")]
+    pub time_offset: i64,
 }
 
 impl Acc for Registrar {}
@@ -26,23 +61,142 @@ impl Registrar {
     pub fn get_seeds<'a>(realm: &'a Pubkey) -> [&'a [u8]; 1] {
         [realm.as_ref()]
     }
+
+    /// Unix timestamp of the clock, shifted by `time_offset` with the `time-offset` feature.
+    /// Every lockup computation goes through it
+    pub fn unix_timestamp(&self) -> Result<i64, ProgramError> {
+        let now = Clock::get()?.unix_timestamp;
+        #[cfg(feature = "time-offset")]
+        let now = now
+            .checked_add(self.time_offset)
+            .ok_or(GovError::MathOverflow)?;
+
+        Ok(now)
+    }
+
+    /// Exchange rate `rate_idx`, `InvalidExchangeRateIndex` when out of range
+    pub fn rate(&self, rate_idx: u8) -> Result<&ExchangeRateEntry, ProgramError> {
+        self.rates
+            .get(usize::from(rate_idx))
+            .ok_or_else(|| GovError::InvalidExchangeRateIndex.into())
+    }
+
+    /// Whether deposits of the exchange rate `rate_idx` count toward the voter weight
+    pub fn rate_has_weight(&self, rate_idx: u8) -> Result<bool, ProgramError> {
+        Ok(self.rate(rate_idx)?.voting_token.has_weight())
+    }
+
+    /// Early unlock is enabled once the registrar has a treasury
+    pub fn assert_early_unlock_enabled(&self) -> ProgramResult {
+        if self.treasury == Pubkey::default() {
+            return Err(GovError::EarlyUnlockDisabled.into());
+        }
+        Ok(())
+    }
+
+    /// Fail with `DelegationDisabled` until `SetDelegationCooldown` set a cooldown
+    pub fn assert_delegation_enabled(&self) -> ProgramResult {
+        if self.delegation_cooldown_secs == 0 {
+            return Err(GovError::DelegationDisabled.into());
+        }
+        Ok(())
+    }
+
+    /// End of the cooldown of a delegation changed at `delegated_at`
+    pub fn delegation_settled_at(&self, delegated_at: i64) -> i64 {
+        delegated_at.saturating_add(i64::from(self.delegation_cooldown_secs))
+    }
+
+    /// Penalty on `locked` native tokens released `days_left` days early:
+    /// `early_unlock_penalty_bps` of them at the maximum lockup, proportionally less below
+    pub fn early_unlock_penalty(&self, locked: u64, days_left: u64) -> Result<u64, ProgramError> {
+        self.assert_early_unlock_enabled()?;
+        let penalty = u128::from(locked)
+            * u128::from(self.early_unlock_penalty_bps.min(MAX_PENALTY_BPS))
+            * u128::from(days_left.min(MAX_DAYS_LOCKED))
+            / (u128::from(MAX_PENALTY_BPS) * u128::from(MAX_DAYS_LOCKED));
+
+        Ok(u64::try_from(penalty).map_err(|_| GovError::MathOverflow)?)
+    }
+
+    /// Open the next snapshot epoch at `ts`, returning it.
+    /// Fails when it would drop the governing snapshot from the kept ones
+    pub fn open_snapshot(&mut self, ts: i64) -> Result<u64, ProgramError> {
+        let epoch = self
+            .snapshot_epoch
+            .checked_add(1)
+            .ok_or(GovError::MathOverflow)?;
+        if self.governing_snapshot_epoch != 0
+            && epoch - self.governing_snapshot_epoch >= SNAPSHOT_HISTORY as u64
+        {
+            return Err(GovError::SnapshotGoverns.into());
+        }
+        self.snapshot_ts[Snapshot::slot(epoch)] = ts;
+        self.snapshot_epoch = epoch;
+
+        Ok(epoch)
+    }
+
+    /// Timestamp of the snapshot `epoch`, while it is one of the latest `SNAPSHOT_HISTORY`
+    pub fn snapshot_ts(&self, epoch: u64) -> Option<i64> {
+        let kept = epoch != 0
+            && epoch <= self.snapshot_epoch
+            && self.snapshot_epoch - epoch < SNAPSHOT_HISTORY as u64;
+        kept.then(|| self.snapshot_ts[Snapshot::slot(epoch)])
+    }
+
+    /// Count the voter weight as of the snapshot `epoch`, one of the kept ones,
+    /// or live again with 0
+    pub fn set_governing_snapshot(&mut self, epoch: u64) -> ProgramResult {
+        if epoch != 0 && self.snapshot_ts(epoch).is_none() {
+            return Err(GovError::SnapshotNotKept.into());
+        }
+        self.governing_snapshot_epoch = epoch;
+        Ok(())
+    }
+
+    /// Fail with `SnapshotNotGoverning` unless the snapshot `epoch` governs
+    pub fn assert_governing_snapshot(&self, epoch: u64) -> ProgramResult {
+        if epoch == 0 || epoch != self.governing_snapshot_epoch {
+            return Err(GovError::SnapshotNotGoverning.into());
+        }
+        Ok(())
+    }
+
+    /// Fail with `SnapshotGoverns` while the voter weight is counted as of a snapshot
+    pub fn assert_live_weight(&self) -> ProgramResult {
+        if self.governing_snapshot_epoch != 0 {
+            return Err(GovError::SnapshotGoverns.into());
+        }
+        Ok(())
+    }
+
     //convert the given amount into community-based currency
     //update both
-    //  1: exchagne rate conversion
-    //  2: decimals conversion
+    //  1: exchagne rate conversion, the rate being scaled by `RATE_SCALE`
+    //  2: decimals conversion, scaling up or down to `rate_decimals`
+    //computed in u128 so `amount * rate` never overflows before the final check
     pub fn convert(&self, er: &ExchangeRateEntry, amount: u64) -> Result<u64, ProgramError> {
-        if !(self.rate_decimals >= er.decimals) {
-            return Err(GovError::InvalidDecimals.into());
-        }
+        let value = u128::from(amount) * u128::from(er.rate);
 
-        let decimals_diff = self.rate_decimals.checked_sub(er.decimals).unwrap();
-        let convert = amount
-            .checked_mul(er.rate)
-            .unwrap()
-            .checked_mul(10_u64.pow(decimals_diff.into()))
-            .unwrap();
+        //unscaling the rate is one more downscale of the decimals
+        let up = u32::from(self.rate_decimals);
+        let down = u32::from(er.decimals) + RATE_SCALE_DECIMALS;
+        let convert = if up >= down {
+            let factor = 10_u128.checked_pow(up - down);
+            match factor {
+                Some(factor) => value.checked_mul(factor),
+                None if value == 0 => Some(0),
+                None => None,
+            }
+            .ok_or(GovError::ConversionOverflow)?
+        } else {
+            //anything below 10^39 is floored to zero when the factor leaves u128
+            let factor = 10_u128.checked_pow(down - up);
+            factor.map_or(0, |factor| value / factor)
+        };
 
-        Ok(convert)
+        Ok(u64::try_from(convert).map_err(|_| GovError::ConversionOverflow)?)
     }
 
     pub fn check_and_get_mut_registrar(
@@ -95,4 +249,266 @@ impl Registrar {
 
         Ok(registrar)
     }
+
+    /// Unpack a registrar open to everyone, checking the account is the
+    /// registrar PDA of its realm rather than any account of the program
+    pub fn check_and_get_registrar(
+        program_id: &Pubkey,
+        account: &AccountInfo,
+    ) -> Result<Self, ProgramError> {
+        if account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if account.data_is_empty() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let registrar = Self::try_from_slice(&account.try_borrow_data()?)?;
+        Self::verify_pda(&Self::get_seeds(&registrar.realm), account.key)?;
+
+        Ok(registrar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::RATE_SCALE;
+
+    const AMOUNTS: [u64; 5] = [0, 1, 9, 1_000_000_007, u64::MAX];
+    const SCALED_RATES: [u64; 5] = [1, 3, RATE_SCALE / 2, RATE_SCALE, u64::MAX];
+
+    fn registrar(rate_decimals: u8) -> Registrar {
+        Registrar {
+            rate_decimals,
+            ..Registrar::default()
+        }
+    }
+
+    /// Rate of `rate` whole units
+    fn er(rate: u64, decimals: u8) -> ExchangeRateEntry {
+        er_scaled(rate * RATE_SCALE, decimals)
+    }
+
+    fn er_scaled(rate: u64, decimals: u8) -> ExchangeRateEntry {
+        ExchangeRateEntry {
+            rate,
+            decimals,
+            ..ExchangeRateEntry::default()
+        }
+    }
+
+    fn overflow() -> ProgramError {
+        GovError::ConversionOverflow.into()
+    }
+
+    #[test]
+    fn rate_out_of_range_fails() {
+        let mut reg = registrar(6);
+        reg.rates[1] = er(2, 6);
+        assert_eq!(reg.rate(1), Ok(&er(2, 6)));
+        assert_eq!(reg.rate(2), Err(GovError::InvalidExchangeRateIndex.into()));
+        assert_eq!(
+            reg.rate_has_weight(u8::MAX),
+            Err(GovError::InvalidExchangeRateIndex.into())
+        );
+    }
+
+    #[test]
+    fn convert_upscales_decimals() {
+        let reg = registrar(6);
+        assert_eq!(reg.convert(&er(1, 6), 10), Ok(10));
+        assert_eq!(reg.convert(&er(3, 4), 7), Ok(2_100));
+        assert_eq!(reg.convert(&er(1_000_000, 0), 10), Ok(10_000_000_000_000));
+        assert_eq!(reg.convert(&er(1, 6), u64::MAX), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn convert_downscales_decimals() {
+        let reg = registrar(2);
+        assert_eq!(reg.convert(&er(1, 6), 1_234_567), Ok(123));
+        assert_eq!(reg.convert(&er(1, 6), 9_999), Ok(0));
+        assert_eq!(reg.convert(&er(3, 3), 7), Ok(2));
+    }
+
+    #[test]
+    fn convert_applies_fractional_rates() {
+        let reg = registrar(6);
+        assert_eq!(reg.convert(&er_scaled(RATE_SCALE / 2, 6), 7), Ok(3));
+        assert_eq!(reg.convert(&er_scaled(1_500_000_000, 0), 1), Ok(1_500_000));
+        assert_eq!(reg.convert(&er_scaled(1, 0), 999), Ok(0));
+        assert_eq!(reg.convert(&er_scaled(1, 0), 1_000), Ok(1));
+    }
+
+    #[test]
+    fn convert_overflow_returns_error() {
+        assert_eq!(
+            registrar(6).convert(&er(1_000_000, 0), u64::MAX),
+            Err(overflow())
+        );
+        assert_eq!(
+            registrar(9).convert(&er_scaled(u64::MAX, 0), 2),
+            Err(overflow())
+        );
+        // 10^38 still fits u128 but not u64
+        assert_eq!(registrar(47).convert(&er_scaled(1, 0), 1), Err(overflow()));
+        // 10^39 leaves u128
+        assert_eq!(registrar(48).convert(&er_scaled(1, 0), 1), Err(overflow()));
+        assert_eq!(registrar(255).convert(&er(1, 0), 0), Ok(0));
+        assert_eq!(registrar(255).convert(&er_scaled(u64::MAX, 0), 0), Ok(0));
+    }
+
+    #[test]
+    fn convert_rounds_at_u64_boundary() {
+        let max = u128::from(u64::MAX);
+        // (2^64 - 1)^2 / 10^19 overflows, / 10^20 fits
+        assert_eq!(
+            registrar(9).convert(&er_scaled(u64::MAX, 19), u64::MAX),
+            Err(overflow())
+        );
+        assert_eq!(
+            registrar(9).convert(&er_scaled(u64::MAX, 20), u64::MAX),
+            Ok(u64::try_from(max * max / 10_u128.pow(20)).unwrap())
+        );
+        assert_eq!(
+            registrar(9).convert(&er_scaled(u64::MAX, 39), u64::MAX),
+            Ok(0)
+        );
+        assert_eq!(
+            registrar(9).convert(&er_scaled(u64::MAX, 255), u64::MAX),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn convert_never_panics_across_decimals() {
+        for rate_decimals in 0..=u8::MAX {
+            let reg = registrar(rate_decimals);
+            let up = u32::from(rate_decimals);
+            for decimals in 0..=u8::MAX {
+                let down = u32::from(decimals) + RATE_SCALE_DECIMALS;
+                for rate in SCALED_RATES {
+                    for amount in AMOUNTS {
+                        let value = u128::from(amount) * u128::from(rate);
+                        match reg.convert(&er_scaled(rate, decimals), amount) {
+                            Ok(converted) => {
+                                let converted = u128::from(converted);
+                                if up >= down {
+                                    let factor = 10_u128.checked_pow(up - down);
+                                    assert_eq!(converted, factor.map_or(0, |f| value * f));
+                                } else if let Some(factor) = 10_u128.checked_pow(down - up) {
+                                    assert!(converted * factor <= value);
+                                    assert!(value < (converted + 1).saturating_mul(factor));
+                                } else {
+                                    assert_eq!(converted, 0);
+                                }
+                            }
+                            Err(err) => {
+                                assert_eq!(err, overflow());
+                                assert!(up > down || value > u128::from(u64::MAX));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check(key: &Pubkey, owner: &Pubkey, mut data: Vec<u8>) -> Result<Registrar, ProgramError> {
+        let mut lamports = 0;
+        let account =
+            AccountInfo::new(key, false, false, &mut lamports, &mut data, owner, false, 0);
+        Registrar::check_and_get_registrar(&crate::id(), &account)
+    }
+
+    #[test]
+    fn open_registrar_is_the_pda_of_its_realm() {
+        let registrar = Registrar {
+            realm: Pubkey::new_unique(),
+            ..Registrar::default()
+        };
+        let data = registrar.try_to_vec().unwrap();
+        let (pda, _) =
+            Pubkey::find_program_address(&Registrar::get_seeds(&registrar.realm), &crate::id());
+
+        assert_eq!(check(&pda, &crate::id(), data.clone()), Ok(registrar));
+        assert_eq!(
+            check(&Pubkey::new_unique(), &crate::id(), data.clone()),
+            Err(GovError::PdaMismatch.into())
+        );
+        assert_eq!(
+            check(&pda, &Pubkey::new_unique(), data),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        assert_eq!(
+            check(&pda, &crate::id(), Vec::new()),
+            Err(ProgramError::UninitializedAccount)
+        );
+    }
+
+    #[test]
+    fn early_unlock_penalty_follows_days_left() {
+        let mut reg = registrar(6);
+        assert_eq!(
+            reg.early_unlock_penalty(1_000, 10),
+            Err(GovError::EarlyUnlockDisabled.into())
+        );
+
+        reg.treasury = Pubkey::new_unique();
+        reg.early_unlock_penalty_bps = 5_000;
+        assert_eq!(reg.early_unlock_penalty(1_095, 1_095), Ok(547));
+        assert_eq!(reg.early_unlock_penalty(1_095, 2_000), Ok(547));
+        assert_eq!(reg.early_unlock_penalty(1_095, 219), Ok(109));
+        assert_eq!(reg.early_unlock_penalty(1_095, 0), Ok(0));
+        assert_eq!(reg.early_unlock_penalty(u64::MAX, 1_095), Ok(u64::MAX / 2));
+    }
+
+    #[test]
+    fn snapshot_ts_keeps_the_latest_epochs() {
+        let mut reg = registrar(6);
+        assert_eq!(reg.snapshot_ts(0), None);
+        for epoch in 1..=6 {
+            let ts = 100 * i64::try_from(epoch).unwrap();
+            assert_eq!(reg.open_snapshot(ts), Ok(epoch));
+        }
+        assert_eq!(reg.snapshot_ts(2), None);
+        assert_eq!(reg.snapshot_ts(3), Some(300));
+        assert_eq!(reg.snapshot_ts(6), Some(600));
+        assert_eq!(reg.snapshot_ts(7), None);
+    }
+
+    #[test]
+    fn governing_snapshot_stays_kept() {
+        let mut reg = registrar(6);
+        assert_eq!(reg.assert_live_weight(), Ok(()));
+        assert_eq!(
+            reg.set_governing_snapshot(1),
+            Err(GovError::SnapshotNotKept.into())
+        );
+
+        reg.open_snapshot(100).unwrap();
+        reg.set_governing_snapshot(1).unwrap();
+        assert_eq!(reg.assert_governing_snapshot(1), Ok(()));
+        assert_eq!(
+            reg.assert_live_weight(),
+            Err(GovError::SnapshotGoverns.into())
+        );
+        for epoch in 2..=SNAPSHOT_HISTORY as u64 {
+            assert_eq!(reg.open_snapshot(100), Ok(epoch));
+        }
+        assert_eq!(
+            reg.assert_governing_snapshot(2),
+            Err(GovError::SnapshotNotGoverning.into())
+        );
+        assert_eq!(
+            reg.open_snapshot(100),
+            Err(GovError::SnapshotGoverns.into())
+        );
+
+        reg.set_governing_snapshot(0).unwrap();
+        assert_eq!(
+            reg.assert_governing_snapshot(0),
+            Err(GovError::SnapshotNotGoverning.into())
+        );
+        assert_eq!(reg.open_snapshot(100), Ok(SNAPSHOT_HISTORY as u64 + 1));
+    }
 }
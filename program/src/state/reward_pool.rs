@@ -0,0 +1,155 @@
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{error::GovError, state::Voter, utils::account_info_util::Acc};
+
+/// Scale of `RewardPool::reward_index`
+pub const REWARD_INDEX_SCALE: u128 = 1_000_000_000_000;
+
+/// Platform fees shared among the voters of a registrar, pro rata to their weight.
+/// Each voter stakes its own weight, delegation aside, on every deposit change,
+/// claim and `RefreshRewardWeight`
+#[derive(
+    BorshDeserialize, BorshSerialize, BorshSchema, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub struct RewardPool {
+    pub registrar: Pubkey,
+    pub reward_mint: Pubkey, // mint of the fees, held by the ATA of the pool
+    pub bump: u8,
+    pub total_weight: u64,  // sum of the weight staked by every voter
+    pub reward_index: u128, // rewards funded per unit of staked weight, scaled by `REWARD_INDEX_SCALE`
+    pub undistributed: u64, // funded while no weight was staked
+}
+
+impl Acc for RewardPool {}
+
+impl RewardPool {
+    pub fn get_seeds(registrar: &Pubkey) -> [&[u8]; 2] {
+        const REWARD_POOL: [u8; 11] = *b"reward-pool";
+        [REWARD_POOL.as_ref(), registrar.as_ref()]
+    }
+
+    /// Share `amount` among the staked weight, kept for later while there is none
+    pub fn fund(&mut self, amount: u64) -> Result<(), ProgramError> {
+        let amount = self
+            .undistributed
+            .checked_add(amount)
+            .ok_or(GovError::MathOverflow)?;
+        if self.total_weight == 0 {
+            self.undistributed = amount;
+            return Ok(());
+        }
+
+        self.reward_index = (u128::from(amount) * REWARD_INDEX_SCALE
+            / u128::from(self.total_weight))
+        .checked_add(self.reward_index)
+        .ok_or(GovError::MathOverflow)?;
+        self.undistributed = 0;
+
+        Ok(())
+    }
+
+    /// Rewards accrued by `weight` since the last settlement of `voter`
+    fn accrued(&self, voter: &Voter, weight: u64) -> Result<u64, ProgramError> {
+        let accrued = u128::from(weight)
+            .checked_mul(self.reward_index.saturating_sub(voter.reward_index))
+            .ok_or(GovError::MathOverflow)?
+            / REWARD_INDEX_SCALE;
+
+        u64::try_from(accrued).map_err(|_| GovError::MathOverflow.into())
+    }
+
+    /// Rewards of `voter` not claimed yet, its weight being `weight` now: settled ones,
+    /// and those accrued since at the lower of its staked and current weight
+    pub fn pending(&self, voter: &Voter, weight: u64) -> Result<u64, ProgramError> {
+        self.accrued(voter, voter.reward_weight.min(weight))?
+            .checked_add(voter.rewards_owed)
+            .ok_or_else(|| GovError::MathOverflow.into())
+    }
+
+    /// Settle the rewards of `voter`, then stake `weight` in place of its previous one.
+    ///
+    /// The staked weight may have decayed since it was staked, so the rewards
+    /// it accrued beyond `weight` are funded again to every voter
+    pub fn stake(&mut self, voter: &mut Voter, weight: u64) -> Result<(), ProgramError> {
+        let owed = self.pending(voter, weight)?;
+        let forfeited = self
+            .accrued(voter, voter.reward_weight)?
+            .saturating_sub(self.accrued(voter, voter.reward_weight.min(weight))?);
+        self.undistributed = self
+            .undistributed
+            .checked_add(forfeited)
+            .ok_or(GovError::MathOverflow)?;
+
+        voter.rewards_owed = owed;
+        voter.reward_index = self.reward_index;
+        self.total_weight = self
+            .total_weight
+            .saturating_sub(voter.reward_weight)
+            .checked_add(weight)
+            .ok_or(GovError::MathOverflow)?;
+        voter.reward_weight = weight;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewards_follow_the_staked_weight() {
+        let mut pool = RewardPool::default();
+        let (mut alice, mut bob) = (Voter::default(), Voter::default());
+
+        // nobody staked yet: kept for the first stakers
+        pool.fund(100).unwrap();
+        pool.stake(&mut alice, 1).unwrap();
+        pool.stake(&mut bob, 3).unwrap();
+        pool.fund(300).unwrap();
+        assert_eq!(pool.pending(&alice, 1), Ok(100));
+        assert_eq!(pool.pending(&bob, 3), Ok(300));
+
+        // rewards funded before a deposit stay with the old weight
+        pool.stake(&mut alice, 2).unwrap();
+        pool.fund(50).unwrap();
+        assert_eq!(pool.total_weight, 5);
+        assert_eq!(
+            (alice.rewards_owed, pool.pending(&alice, 2)),
+            (100, Ok(120))
+        );
+        assert_eq!(pool.pending(&bob, 3), Ok(330));
+    }
+
+    #[test]
+    fn rounding_never_pays_more_than_funded() {
+        let mut pool = RewardPool::default();
+        let mut voters = [Voter::default(), Voter::default(), Voter::default()];
+        for voter in &mut voters {
+            pool.stake(voter, 7).unwrap();
+        }
+
+        pool.fund(100).unwrap();
+        let paid: u64 = voters.iter().map(|v| pool.pending(v, 7).unwrap()).sum();
+        assert_eq!(paid, 99);
+    }
+
+    #[test]
+    fn decayed_weight_earns_at_its_current_value() {
+        let mut pool = RewardPool::default();
+        let (mut alice, mut bob) = (Voter::default(), Voter::default());
+        pool.stake(&mut alice, 3).unwrap();
+        pool.stake(&mut bob, 1).unwrap();
+
+        // alice's lockup expired before the funding: her share goes to the next one
+        pool.fund(400).unwrap();
+        assert_eq!(pool.pending(&alice, 0), Ok(0));
+        pool.stake(&mut alice, 0).unwrap();
+        assert_eq!((alice.rewards_owed, pool.undistributed), (0, 300));
+
+        pool.fund(100).unwrap();
+        assert_eq!(pool.pending(&alice, 0), Ok(0));
+        assert_eq!(pool.pending(&bob, 1), Ok(500));
+    }
+}
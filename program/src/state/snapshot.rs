@@ -0,0 +1,158 @@
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::utils::account_info_util::Acc;
+
+/// Number of the latest snapshots whose timestamp the registrar keeps,
+/// and whose weight every voter checkpoints, see `Voter::checkpoint`
+pub const SNAPSHOT_HISTORY: usize = 4;
+
+/// Timestamp of a registrar epoch, opened by `CreateSnapshot`.
+/// Proposals count the voter weight as of it
+#[derive(
+    BorshDeserialize, BorshSerialize, BorshSchema, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub struct Snapshot {
+    pub registrar: Pubkey,
+    pub epoch: u64, // starts at 1
+    pub ts: i64,
+    pub bump: u8,
+}
+
+impl Acc for Snapshot {}
+
+impl Snapshot {
+    pub fn get_seeds<'a>(registrar: &'a Pubkey, epoch: &'a [u8; 8]) -> [&'a [u8]; 3] {
+        const SNAPSHOT: [u8; 8] = *b"snapshot";
+        [SNAPSHOT.as_ref(), registrar.as_ref(), epoch.as_ref()]
+    }
+
+    /// Index of the snapshot `epoch` in the rings of `SNAPSHOT_HISTORY` entries
+    pub fn slot(epoch: u64) -> usize {
+        usize::try_from(epoch % SNAPSHOT_HISTORY as u64).unwrap_or_default()
+    }
+}
+
+/// Weight of a voter as of the snapshot `epoch`, and who held it then
+#[derive(
+    BorshDeserialize, BorshSerialize, BorshSchema, Default, Clone, Copy, Debug, PartialEq, Eq,
+)]
+pub struct Checkpoint {
+    pub epoch: u64, // 0 while unused
+    pub weight: u64,
+    pub holder: Pubkey, // `Voter::weight_holder` at the snapshot, default for nobody
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::GovError,
+        state::{DepositEntry, Lockup, LockupKind, Registrar, Voter, SECS_PER_DAY},
+    };
+
+    fn voter_with_cliff(amount_scaled: u64, days: i64) -> Voter {
+        let mut voter = Voter::default();
+        voter.deposits[0] = DepositEntry {
+            is_used: true,
+            amount_deposited: amount_scaled,
+            amount_scaled,
+            lockup: Lockup {
+                kind: LockupKind::Cliff,
+                start_ts: 0,
+                end_ts: days * SECS_PER_DAY,
+                unlocking: false,
+                padding: [0; 15],
+            },
+            ..DepositEntry::default()
+        };
+        voter
+    }
+
+    fn snapshot(registrar: &Registrar, epoch: u64) -> Snapshot {
+        Snapshot {
+            epoch,
+            ts: registrar.snapshot_ts(epoch).unwrap(),
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn checkpoints_keep_the_weight_before_a_change() {
+        let mut registrar = Registrar::default();
+        let mut voter = voter_with_cliff(1_095, 1_095);
+        registrar.open_snapshot(10 * SECS_PER_DAY).unwrap();
+        let first = snapshot(&registrar, 1);
+
+        // untouched deposits: weight at the snapshot timestamp
+        assert_eq!(voter.snapshot_weight(&registrar, &first), Ok(1_085));
+
+        // a deposit after the snapshot does not count for it
+        voter.checkpoint(&registrar).unwrap();
+        voter.deposits[0].amount_scaled = 2_190;
+        voter.deposits[0].amount_deposited = 2_190;
+        assert_eq!(voter.snapshot_weight(&registrar, &first), Ok(1_085));
+
+        registrar.open_snapshot(20 * SECS_PER_DAY).unwrap();
+        let second = snapshot(&registrar, 2);
+        assert_eq!(voter.snapshot_weight(&registrar, &second), Ok(2_150));
+    }
+
+    #[test]
+    fn checkpoints_cover_every_snapshot_since_the_last_change() {
+        let mut registrar = Registrar::default();
+        let mut voter = voter_with_cliff(1_095, 1_095);
+        for day in 1..=6 {
+            registrar.open_snapshot(day * SECS_PER_DAY).unwrap();
+        }
+
+        voter.checkpoint(&registrar).unwrap();
+        voter.deposits[0] = DepositEntry::default();
+        assert_eq!(voter.checkpoint_epoch, 6);
+        for epoch in 3..=6 {
+            let weight = 1_095 - epoch;
+            let at = snapshot(&registrar, epoch);
+            assert_eq!(voter.snapshot_weight(&registrar, &at), Ok(weight));
+        }
+
+        // older than the checkpoints kept
+        let at = Snapshot {
+            epoch: 2,
+            ts: 2 * SECS_PER_DAY,
+            ..Snapshot::default()
+        };
+        assert_eq!(
+            voter.snapshot_weight(&registrar, &at),
+            Err(GovError::CheckpointNotFound.into())
+        );
+    }
+
+    #[test]
+    fn checkpoints_keep_the_holder_before_a_delegation_change() {
+        let mut registrar = Registrar {
+            delegation_cooldown_secs: u32::try_from(3 * SECS_PER_DAY).unwrap(),
+            ..Registrar::default()
+        };
+        let mut voter = voter_with_cliff(1_095, 1_095);
+        let (own, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        voter.authority = own;
+        voter.delegate = bob;
+        registrar.open_snapshot(10 * SECS_PER_DAY).unwrap();
+        let first = snapshot(&registrar, 1);
+        let held = voter.snapshot_checkpoint(&registrar, &first).unwrap();
+        assert_eq!((held.holder, held.weight), (bob, 1_085));
+
+        // revoked after the snapshot: bob still held the weight as of it
+        voter.checkpoint(&registrar).unwrap();
+        voter.delegate = Pubkey::default();
+        voter.delegated_at = 11 * SECS_PER_DAY;
+        let held = voter.snapshot_checkpoint(&registrar, &first).unwrap();
+        assert_eq!((held.holder, held.weight), (bob, 1_085));
+
+        // in transit as of the next snapshot, held by nobody
+        registrar.open_snapshot(12 * SECS_PER_DAY).unwrap();
+        let second = snapshot(&registrar, 2);
+        let held = voter.snapshot_checkpoint(&registrar, &second).unwrap();
+        assert_eq!(held.holder, Pubkey::default());
+    }
+}
@@ -2,7 +2,11 @@ use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 
 use solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
 
-use crate::{error::GovError, state::DepositEntry, utils::account_info_util::Acc};
+use crate::{
+    error::GovError,
+    state::{Checkpoint, DepositEntry, Registrar, Snapshot, SNAPSHOT_HISTORY},
+    utils::account_info_util::Acc,
+};
 
 use spl_governance_addin_api::voter_weight::VoterWeightRecord;
 
@@ -10,13 +14,25 @@ use spl_governance_addin_api::voter_weight::VoterWeightRecord;
 pub struct Voter {
     pub authority: Pubkey,
     pub registrar: Pubkey,
-    pub voter_bump: u8,               // for state::Voter
+    pub delegate: Pubkey, // `Pubkey::default()` when voting for oneself
+    pub voter_bump: u8,   // for state::Voter
     pub voter_weight_record_bump: u8, //for state::VoterWeightRecord
     pub deposits: [DepositEntry; 10], //bookkeeping records of individual assets
+    pub reward_weight: u64, // weight staked in the reward pool, see `RewardPool::stake`
+    pub reward_index: u128, // `RewardPool::reward_index` at the last settlement
+    pub rewards_owed: u64, // settled rewards not claimed yet
+    pub checkpoint_epoch: u64, // `Registrar::snapshot_epoch` at the last deposit change
+    pub checkpoints: [Checkpoint; SNAPSHOT_HISTORY], // see `Voter::checkpoint`
+    pub delegated_at: i64, // last `SetDelegate`, see `Registrar::delegation_cooldown_secs`
 }
 
 impl Acc for Voter {}
-impl Acc for VoterWeightRecord {}
+impl Acc for VoterWeightRecord {
+    //reserve the space of every `Option` so the record could be updated in place
+    fn get_max_size(&self) -> Option<usize> {
+        Some(8 + 32 * 3 + 8 + (1 + 8) + (1 + 1) + (1 + 32) + 8)
+    }
+}
 
 impl Voter {
     pub fn assert_voter(&self, authority: &Pubkey, registrar: &Pubkey) -> Result<(), ProgramError> {
@@ -30,6 +46,112 @@ impl Voter {
         Ok(())
     }
 
+    pub fn has_delegate(&self) -> bool {
+        self.delegate != Pubkey::default()
+    }
+
+    /// Authority whose voter weight record counts the weight of this voter at `ts`:
+    /// its delegate or itself, none while a delegation change cools down.
+    ///
+    /// The cooldown covers the longest voting time of the realm, so the next holder
+    /// cannot count the weight again on a proposal the previous one voted on
+    pub fn weight_holder(&self, registrar: &Registrar, ts: i64) -> Option<Pubkey> {
+        let settled = ts >= registrar.delegation_settled_at(self.delegated_at);
+        settled.then(|| {
+            if self.has_delegate() {
+                self.delegate
+            } else {
+                self.authority
+            }
+        })
+    }
+
+    /// Weight of the voter at `ts` counted by `holder`, zero unless it holds it
+    pub fn weight_for(
+        &self,
+        registrar: &Registrar,
+        holder: &Pubkey,
+        ts: i64,
+    ) -> Result<u64, ProgramError> {
+        if self.weight_holder(registrar, ts) != Some(*holder) {
+            return Ok(0);
+        }
+        self.weight(registrar, ts)
+    }
+
+    /// Sum of the voting power at `curr_ts` of every deposit
+    /// whose exchange rate grants voter weight, under the factors of that rate
+    pub fn weight(&self, registrar: &Registrar, curr_ts: i64) -> Result<u64, ProgramError> {
+        self.deposits.iter().try_fold(0_u64, |acc, d| {
+            let er = registrar.rate(d.rate_idx)?;
+            if !er.voting_token.has_weight() {
+                return Ok(acc);
+            }
+            acc.checked_add(d.voting_power(er, curr_ts)?)
+                .ok_or_else(|| GovError::MathOverflow.into())
+        })
+    }
+
+    /// Record, before its deposits or delegation change, the weight of the voter
+    /// and its holder as of every snapshot taken since the previous change.
+    ///
+    /// The current deposits and delegation still hold them.
+    /// Only the latest `SNAPSHOT_HISTORY` snapshots are kept
+    pub fn checkpoint(&mut self, registrar: &Registrar) -> Result<(), ProgramError> {
+        let first = self
+            .checkpoint_epoch
+            .max(
+                registrar
+                    .snapshot_epoch
+                    .saturating_sub(SNAPSHOT_HISTORY as u64),
+            )
+            .saturating_add(1);
+        for epoch in first..=registrar.snapshot_epoch {
+            if let Some(ts) = registrar.snapshot_ts(epoch) {
+                self.checkpoints[Snapshot::slot(epoch)] = Checkpoint {
+                    epoch,
+                    weight: self.weight(registrar, ts)?,
+                    holder: self.weight_holder(registrar, ts).unwrap_or_default(),
+                };
+            }
+        }
+        self.checkpoint_epoch = registrar.snapshot_epoch;
+
+        Ok(())
+    }
+
+    /// Weight of the voter and its holder as of `snapshot`: the current ones at
+    /// its timestamp when nothing changed since, its checkpoint otherwise
+    pub fn snapshot_checkpoint(
+        &self,
+        registrar: &Registrar,
+        snapshot: &Snapshot,
+    ) -> Result<Checkpoint, ProgramError> {
+        if self.checkpoint_epoch < snapshot.epoch {
+            return Ok(Checkpoint {
+                epoch: snapshot.epoch,
+                weight: self.weight(registrar, snapshot.ts)?,
+                holder: self
+                    .weight_holder(registrar, snapshot.ts)
+                    .unwrap_or_default(),
+            });
+        }
+        self.checkpoints
+            .iter()
+            .find(|checkpoint| checkpoint.epoch == snapshot.epoch)
+            .copied()
+            .ok_or_else(|| GovError::CheckpointNotFound.into())
+    }
+
+    /// Weight of the voter as of `snapshot`
+    pub fn snapshot_weight(
+        &self,
+        registrar: &Registrar,
+        snapshot: &Snapshot,
+    ) -> Result<u64, ProgramError> {
+        Ok(self.snapshot_checkpoint(registrar, snapshot)?.weight)
+    }
+
     pub fn get_voter_seeds<'a>(registrar: &'a Pubkey, authority: &'a Pubkey) -> [&'a [u8]; 2] {
         [registrar.as_ref(), authority.as_ref()]
     }
@@ -45,3 +167,72 @@ impl Voter {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{Lockup, LockupKind, SECS_PER_DAY};
+
+    fn voter_with_constant(amount_scaled: u64) -> Voter {
+        let mut voter = Voter {
+            authority: Pubkey::new_unique(),
+            ..Voter::default()
+        };
+        voter.deposits[0] = DepositEntry {
+            is_used: true,
+            amount_deposited: amount_scaled,
+            amount_scaled,
+            lockup: Lockup {
+                kind: LockupKind::Constant,
+                start_ts: 0,
+                end_ts: 1_095 * SECS_PER_DAY,
+                unlocking: false,
+                padding: [0; 15],
+            },
+            ..DepositEntry::default()
+        };
+        voter
+    }
+
+    fn registrar_with_cooldown() -> Registrar {
+        Registrar {
+            delegation_cooldown_secs: u32::try_from(3 * SECS_PER_DAY).unwrap(),
+            ..Registrar::default()
+        }
+    }
+
+    #[test]
+    fn delegated_weight_moves_after_the_cooldown() {
+        let registrar = registrar_with_cooldown();
+        let bob = Pubkey::new_unique();
+        let mut alice = voter_with_constant(1_095);
+        let own = alice.authority;
+        let ts = 10 * SECS_PER_DAY;
+        assert_eq!(alice.weight_holder(&registrar, ts), Some(own));
+        assert_eq!(alice.weight_for(&registrar, &own, ts), Ok(1_095));
+        assert_eq!(alice.weight_for(&registrar, &bob, ts), Ok(0));
+
+        // delegated at day 10: counted by nobody until the cooldown passed
+        alice.delegate = bob;
+        alice.delegated_at = ts;
+        let settled = registrar.delegation_settled_at(ts);
+        assert_eq!(alice.weight_holder(&registrar, ts), None);
+        assert_eq!(alice.weight_for(&registrar, &own, ts), Ok(0));
+        assert_eq!(alice.weight_for(&registrar, &bob, settled - 1), Ok(0));
+        assert_eq!(alice.weight_for(&registrar, &bob, settled), Ok(1_095));
+        assert_eq!(alice.weight_for(&registrar, &own, settled), Ok(0));
+    }
+
+    #[test]
+    fn revoked_weight_returns_after_the_cooldown() {
+        let registrar = registrar_with_cooldown();
+        let mut alice = voter_with_constant(1_095);
+        let own = alice.authority;
+        let ts = 10 * SECS_PER_DAY;
+        alice.delegated_at = ts;
+
+        assert_eq!(alice.weight_holder(&registrar, ts + 1), None);
+        let settled = registrar.delegation_settled_at(ts);
+        assert_eq!(alice.weight_for(&registrar, &own, settled), Ok(1_095));
+    }
+}
@@ -10,9 +10,10 @@ use solana_program::{
     sysvar::Sysvar,
 };
 
-
 use borsh::{try_from_slice_with_schema, BorshDeserialize, BorshSerialize};
 
+use crate::error::GovError;
+
 //extend the initialize trait that could be seperated from Default value
 // Default -> Invalid Value
 // Initialzied -> Valid Pattern but wihtout any further modification
@@ -26,7 +27,7 @@ pub trait Acc {
     fn verify_pda(seeds: &[&[u8]], account: &Pubkey) -> ProgramResult {
         let pda = Pubkey::find_program_address(seeds, &crate::id()).0;
         if pda != *account {
-            return Err(ProgramError::InvalidSeeds);
+            return Err(GovError::PdaMismatch.into());
         }
         Ok(())
     }
@@ -77,6 +78,25 @@ pub fn create_and_serialize_account<'a, T: BorshSerialize + Acc + PartialEq>(
     Ok(())
 }
 
+///find the canonical bump of `seeds`, rejecting `account` when it is not the canonical PDA
+/// and `bump` when the client supplied any other bump
+pub fn assert_canonical_pda(
+    seeds: &[&[u8]],
+    program_id: &Pubkey,
+    account: &Pubkey,
+    bump: Option<u8>,
+) -> Result<u8, ProgramError> {
+    let (pda, canonical_bump) = Pubkey::find_program_address(seeds, program_id);
+    if pda != *account {
+        return Err(GovError::PdaMismatch.into());
+    }
+    if matches!(bump, Some(bump) if bump != canonical_bump) {
+        return Err(GovError::NonCanonicalBump.into());
+    }
+
+    Ok(canonical_bump)
+}
+
 ///Create PDA whose lamports might be positive
 /// Question: Hot to solve the dynamic vector of AccountInfo for doing CPI
 pub fn create_and_serialize_account_signed<'a, T: BorshSerialize + Acc + PartialEq>(
@@ -85,20 +105,8 @@ pub fn create_and_serialize_account_signed<'a, T: BorshSerialize + Acc + Partial
     payer_info: &AccountInfo<'a>,
     owner: &Pubkey,
     seeds: &[&[u8]],
-    bump: Option<u8>, // whether created by custodial bump
+    bump: u8, // canonical bump, verified by `assert_canonical_pda`
 ) -> ProgramResult {
-    //verify PDA
-    let (pda, bump) = if let Some(bump) = bump {
-        (*account_info.key, bump)
-    } else {
-        Pubkey::find_program_address(seeds, owner)
-    };
-
-    if pda != *account_info.key {
-        //log the message by type of PDA we are goting to create
-        return Err(ProgramError::InvalidSeeds);
-    }
-
     let (serialized_data, size) = if let Some(max_size) = account_data.get_max_size() {
         (None, max_size)
     } else {
@@ -178,3 +186,42 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_canonical_bump_is_accepted() {
+        let realm = Pubkey::new_unique();
+        let seeds = [realm.as_ref()];
+        let (pda, bump) = Pubkey::find_program_address(&seeds, &crate::id());
+
+        assert_eq!(
+            assert_canonical_pda(&seeds, &crate::id(), &pda, None),
+            Ok(bump)
+        );
+        assert_eq!(
+            assert_canonical_pda(&seeds, &crate::id(), &pda, Some(bump)),
+            Ok(bump)
+        );
+
+        // any lower bump off the curve derives a second, non-canonical address
+        let (other_pda, other_bump) = (0..bump)
+            .rev()
+            .find_map(|other_bump| {
+                Pubkey::create_program_address(&[realm.as_ref(), &[other_bump]], &crate::id())
+                    .ok()
+                    .map(|other_pda| (other_pda, other_bump))
+            })
+            .unwrap();
+        assert_eq!(
+            assert_canonical_pda(&seeds, &crate::id(), &pda, Some(other_bump)),
+            Err(GovError::NonCanonicalBump.into())
+        );
+        assert_eq!(
+            assert_canonical_pda(&seeds, &crate::id(), &other_pda, Some(other_bump)),
+            Err(GovError::PdaMismatch.into())
+        );
+    }
+}
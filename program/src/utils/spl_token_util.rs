@@ -13,6 +13,29 @@ use solana_program::{
 
 use spl_token::state::Account;
 
+use crate::error::GovError;
+
+// ------- verify -------
+
+///unpack the token account and check its `mint`, and its `owner` when given
+pub fn assert_token_account(
+    account: &AccountInfo,
+    mint: &Pubkey,
+    owner: Option<&Pubkey>,
+) -> Result<Account, ProgramError> {
+    let token = Account::unpack(&account.try_borrow_data()?)?;
+    if token.mint != *mint {
+        return Err(GovError::TokenMintMismatch.into());
+    }
+    if let Some(owner) = owner {
+        if token.owner != *owner {
+            return Err(GovError::TokenOwnerMismatch.into());
+        }
+    }
+
+    Ok(token)
+}
+
 // ------- create_account -------
 
 fn create_token_account<F>(account: &AccountInfo, check: F) -> ProgramResult {
@@ -114,86 +137,187 @@ pub fn transfer_spl_token<'a>(
 
     Ok(())
 }
-pub fn transfer_spl_token_signed() {
-    todo!();
-}
-
-/// `thaw, mint, and freeze ` spl_token account
-pub fn mint_token_signed<'a>(
+///transfer out of a token account owned by the PDA `source_owner`
+pub fn transfer_spl_token_signed<'a>(
+    source_account: &AccountInfo<'a>,
     destination_account: &AccountInfo<'a>,
-    mint: &AccountInfo<'a>,
-    token_owner: &AccountInfo<'a>,
+    source_owner: &AccountInfo<'a>,
     seeds: &[&[u8]],
     bump: u8,
     amount: u64,
-    token_program: &AccountInfo<'a>,
-    name: &str,
+    spl_token_program: &AccountInfo<'a>,
 ) -> ProgramResult {
-    let token: Account = Account::unpack(&destination_account.try_borrow_data()?)?;
-
     let mut signer_seeds = seeds.to_vec();
     let bump = &[bump];
     signer_seeds.push(bump);
 
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            source_account.key,
+            destination_account.key,
+            source_owner.key,
+            &[source_owner.key],
+            amount,
+        )?,
+        &[
+            spl_token_program.clone(),
+            source_account.clone(),
+            destination_account.clone(),
+            source_owner.clone(),
+        ],
+        &[&signer_seeds[..]],
+    )?;
+    msg!(
+        "transfer amount:{} to spl_token {}",
+        &amount,
+        destination_account.key
+    );
+
+    Ok(())
+}
+
+fn set_frozen_signed<'a>(
+    account: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    freeze_authority: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+    token_program: &AccountInfo<'a>,
+    freeze: bool,
+) -> ProgramResult {
+    let ix = if freeze {
+        spl_token::instruction::freeze_account
+    } else {
+        spl_token::instruction::thaw_account
+    };
+    invoke_signed(
+        &ix(
+            &spl_token::id(),
+            account.key,
+            mint.key,
+            freeze_authority.key,
+            &[freeze_authority.key],
+        )?,
+        &[
+            token_program.clone(),
+            account.clone(),
+            mint.clone(),
+            freeze_authority.clone(),
+        ],
+        &[signer_seeds],
+    )
+}
+
+/// A mint whose mint and freeze authority is the PDA `authority`,
+/// signing with `seeds` and `bump`
+pub struct PdaMint<'a, 'b> {
+    pub mint: &'b AccountInfo<'a>,
+    pub authority: &'b AccountInfo<'a>,
+    pub seeds: &'b [&'b [u8]],
+    pub bump: u8,
+    pub token_program: &'b AccountInfo<'a>,
+}
+
+impl<'a> PdaMint<'a, '_> {
+    fn set_frozen(&self, account: &AccountInfo<'a>, freeze: bool) -> ProgramResult {
+        let bump = &[self.bump];
+        let mut signer_seeds = self.seeds.to_vec();
+        signer_seeds.push(bump);
+        set_frozen_signed(
+            account,
+            self.mint,
+            self.authority,
+            &signer_seeds,
+            self.token_program,
+            freeze,
+        )
+    }
+}
+
+/// `thaw, mint, and freeze ` spl_token account, left thawed unless `freeze`
+pub fn mint_token_signed<'a>(
+    pda_mint: &PdaMint<'a, '_>,
+    destination_account: &AccountInfo<'a>,
+    amount: u64,
+    name: &str,
+    freeze: bool,
+) -> ProgramResult {
+    let token: Account = Account::unpack(&destination_account.try_borrow_data()?)?;
+
     if token.is_frozen() {
-        invoke_signed(
-            &spl_token::instruction::thaw_account(
-                &spl_token::id(),
-                destination_account.key,
-                mint.key,
-                token_owner.key,
-                &[token_owner.key],
-            )?,
-            &[
-                token_program.clone(),
-                destination_account.clone(),
-                mint.clone(),
-                token_owner.clone(),
-            ],
-            &[&signer_seeds[..]],
-        )?;
+        pda_mint.set_frozen(destination_account, false)?;
         msg!("thaw {} token account", name);
     }
 
     //mint
-
+    let bump = &[pda_mint.bump];
+    let mut signer_seeds = pda_mint.seeds.to_vec();
+    signer_seeds.push(bump);
     invoke_signed(
         &spl_token::instruction::mint_to(
             &spl_token::id(),
-            mint.key,
+            pda_mint.mint.key,
             destination_account.key,
-            token_owner.key,
-            &[token_owner.key],
+            pda_mint.authority.key,
+            &[pda_mint.authority.key],
             amount,
         )?,
         &[
-            token_program.clone(),
+            pda_mint.token_program.clone(),
             destination_account.clone(),
-            mint.clone(),
-            token_owner.clone(),
+            pda_mint.mint.clone(),
+            pda_mint.authority.clone(),
         ],
         &[&signer_seeds[..]],
     )?;
     msg!("mint '{}' to {} token account", amount, name);
 
     //freeze
-    invoke_signed(
-        &spl_token::instruction::freeze_account(
+    if freeze {
+        pda_mint.set_frozen(destination_account, true)?;
+        msg!("freeze {} token account", name);
+    }
+
+    Ok(())
+}
+
+/// burn from `source_account` signed by `source_authority`, its owner or approved
+/// delegate, thawing it for the burn when it is frozen by the mint authority PDA
+pub fn burn_token<'a>(
+    pda_mint: &PdaMint<'a, '_>,
+    source_account: &AccountInfo<'a>,
+    source_authority: &AccountInfo<'a>,
+    amount: u64,
+    name: &str,
+) -> ProgramResult {
+    let token: Account = Account::unpack(&source_account.try_borrow_data()?)?;
+
+    let frozen = token.is_frozen();
+    if frozen {
+        pda_mint.set_frozen(source_account, false)?;
+    }
+
+    invoke(
+        &spl_token::instruction::burn(
             &spl_token::id(),
-            destination_account.key,
-            mint.key,
-            token_owner.key,
-            &[token_owner.key],
+            source_account.key,
+            pda_mint.mint.key,
+            source_authority.key,
+            &[source_authority.key],
+            amount,
         )?,
         &[
-            token_program.clone(),
-            destination_account.clone(),
-            mint.clone(),
-            token_owner.clone(),
+            pda_mint.token_program.clone(),
+            source_account.clone(),
+            pda_mint.mint.clone(),
+            source_authority.clone(),
         ],
-        &[&signer_seeds[..]],
     )?;
-    msg!("freeze {} token account", name);
+    msg!("burn '{}' from {} token account", amount, name);
+
+    if frozen {
+        pda_mint.set_frozen(source_account, true)?;
+    }
 
     Ok(())
 }
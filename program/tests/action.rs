@@ -1,10 +1,11 @@
 use {
-    program::instruction,
+    program::{client, instruction, state::LockupPolicy},
     solana_program_test::BanksClient,
     solana_sdk::{
         hash::Hash,
         pubkey::Pubkey,
         signature::{Keypair, Signer},
+        system_instruction,
         transaction::Transaction,
         transport::TransportError,
     },
@@ -29,6 +30,7 @@ pub async fn create_registrar(
             realm,
             community_mint_pubkey,
             rate_decimals,
+            LockupPolicy::default(),
             &registrar_pda,
             registrar_bump,
         )],
@@ -104,6 +106,41 @@ pub async fn create_voter(
     Ok(())
 }
 
+/// Register `authority` as a voter, after funding it to pay for its voter account
+pub async fn create_voter_for(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    authority: &Keypair,
+    registrar_pda: &Pubkey,
+    voter_pda: &Pubkey,
+    voter_bump: u8,
+    voter_weight_record: &Pubkey,
+    voter_weight_record_bump: u8,
+) -> Result<(), TransportError> {
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &authority.pubkey(), 1_000_000_000),
+            instruction::create_voter(
+                &payer.pubkey(),
+                &authority.pubkey(),
+                registrar_pda,
+                voter_pda,
+                voter_bump,
+                voter_weight_record,
+                voter_weight_record_bump,
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[payer, authority],
+        recent_blockhash,
+    );
+
+    banks_client.process_transaction(transaction).await?;
+
+    Ok(())
+}
+
 pub async fn create_deposit(
     banks_client: &mut BanksClient,
     payer: &Keypair,
@@ -121,14 +158,17 @@ pub async fn create_deposit(
 ) -> Result<(), TransportError> {
     let tx = Transaction::new_signed_with_payer(
         &[instruction::create_deposit(
-            &payer.pubkey(),
-            registrar_pda,
-            voter_pda,
-            deposit_mint,
-            voting_mint_pda,
-            deposit_token,
-            exchange_vault_pda,
-            voting_token,
+            &instruction::DepositKeys {
+                depositor: payer.pubkey(),
+                registrar: *registrar_pda,
+                voter: *voter_pda,
+                deposit_mint: *deposit_mint,
+                voting_mint: *voting_mint_pda,
+                deposit_token: *deposit_token,
+                exchange_vault: *exchange_vault_pda,
+                voting_token: *voting_token,
+                reward_pool: client::pda::reward_pool_address(registrar_pda),
+            },
             kind,
             amount,
             days,
@@ -159,18 +199,105 @@ pub async fn update_deposit(
 ) -> Result<(), TransportError> {
     let tx = Transaction::new_signed_with_payer(
         &[instruction::update_deposit(
-            &payer.pubkey(),
+            &instruction::DepositKeys {
+                depositor: payer.pubkey(),
+                registrar: *registrar_pda,
+                voter: *voter_pda,
+                deposit_mint: *deposit_mint,
+                voting_mint: *voting_mint_pda,
+                deposit_token: *deposit_token,
+                exchange_vault: *exchange_vault_pda,
+                voting_token: *voting_token,
+                reward_pool: client::pda::reward_pool_address(registrar_pda),
+            },
+            update_idx,
+            amount,
+        )],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash,
+    );
+
+    banks_client.process_transaction(tx).await?;
+
+    Ok(())
+}
+
+pub async fn update_deposit_for(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    depositor: &Keypair,
+    registrar_pda: &Pubkey,
+    voter_authority: &Pubkey,
+    deposit_mint: &Pubkey,
+    deposit_token: &Pubkey,
+    update_idx: u8,
+    amount: u64,
+) -> Result<(), TransportError> {
+    let tx = Transaction::new_signed_with_payer(
+        &[client::instruction::update_deposit_for(
             registrar_pda,
-            voter_pda,
+            &depositor.pubkey(),
+            voter_authority,
             deposit_mint,
-            voting_mint_pda,
             deposit_token,
-            exchange_vault_pda,
-            voting_token,
             update_idx,
             amount,
         )],
         Some(&payer.pubkey()),
+        &[payer, depositor],
+        recent_blockhash,
+    );
+
+    banks_client.process_transaction(tx).await?;
+
+    Ok(())
+}
+
+pub async fn set_delegate(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    authority: &Keypair,
+    registrar_pda: &Pubkey,
+    voter_pda: &Pubkey,
+    delegate: &Pubkey,
+) -> Result<(), TransportError> {
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::set_delegate(
+            &authority.pubkey(),
+            registrar_pda,
+            voter_pda,
+            delegate,
+        )],
+        Some(&payer.pubkey()),
+        &[payer, authority],
+        recent_blockhash,
+    );
+
+    banks_client.process_transaction(tx).await?;
+
+    Ok(())
+}
+
+pub async fn update_voter_weight_record(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    registrar_pda: &Pubkey,
+    voter_pda: &Pubkey,
+    voter_weight_record: &Pubkey,
+    delegators: &[Pubkey],
+) -> Result<(), TransportError> {
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::update_voter_weight_record(
+            registrar_pda,
+            voter_pda,
+            voter_weight_record,
+            delegators,
+        )],
+        Some(&payer.pubkey()),
         &[payer],
         recent_blockhash,
     );
@@ -179,3 +306,56 @@ pub async fn update_deposit(
 
     Ok(())
 }
+
+pub async fn withdraw(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    registrar_pda: &Pubkey,
+    deposit_mint: &Pubkey,
+    destination: &Pubkey,
+    deposit_idx: u8,
+    amount: u64,
+) -> Result<(), TransportError> {
+    let tx = Transaction::new_signed_with_payer(
+        &[client::instruction::withdraw_to(
+            registrar_pda,
+            &payer.pubkey(),
+            deposit_mint,
+            destination,
+            deposit_idx,
+            amount,
+        )],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash,
+    );
+
+    banks_client.process_transaction(tx).await?;
+
+    Ok(())
+}
+
+#[cfg(feature = "time-offset")]
+pub async fn set_time_offset(
+    banks_client: &mut BanksClient,
+    authority: &Keypair,
+    recent_blockhash: Hash,
+    registrar_pda: &Pubkey,
+    time_offset: i64,
+) -> Result<(), TransportError> {
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::set_time_offset(
+            &authority.pubkey(),
+            registrar_pda,
+            time_offset,
+        )],
+        Some(&authority.pubkey()),
+        &[authority],
+        recent_blockhash,
+    );
+
+    banks_client.process_transaction(tx).await?;
+
+    Ok(())
+}
@@ -0,0 +1,311 @@
+//! Compute units consumed by every instruction, measured on the BPF build:
+//!
+//! ```sh
+//! cargo test-bpf --test compute_units -- --nocapture
+//! ```
+//!
+//! The measured table is compared with `tests/compute_units.txt`: the run fails
+//! when an instruction has no recorded units, consumes more than `REGRESSION_PERCENT`
+//! over them, or more than the compute budget of an instruction. Set
+//! `UPDATE_COMPUTE_UNITS` to write the measured table there instead.
+#![cfg(feature = "test-bpf")]
+mod realm;
+
+use std::{collections::BTreeMap, env, fmt::Write as _, fs, path::PathBuf};
+
+use {
+    program::{
+        client::{
+            instruction::{self, DepositLockup},
+            pda,
+        },
+        state::{ExchangeRateEntry, LockupKind, LockupPolicy, VotingTokenPolicy, RATE_SCALE},
+    },
+    realm::{clone_keypair, TestRealm, DELEGATION_COOLDOWN_SECS, RATE_DECIMALS},
+    solana_program_test::tokio,
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Keypair, signer::Signer,
+        system_instruction,
+    },
+};
+
+/// Allowed growth over the recorded units
+const REGRESSION_PERCENT: u64 = 5;
+
+/// Default compute budget of an instruction
+const MAX_UNITS: u64 = 200_000;
+
+const TABLE: &str = "tests/compute_units.txt";
+
+#[tokio::test]
+async fn compute_units_per_instruction() {
+    let measured = measure_all().await;
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(TABLE);
+    let update = env::var_os("UPDATE_COMPUTE_UNITS").is_some();
+    let recorded = fs::read_to_string(&path).unwrap_or_default();
+    let recorded = parse_table(&recorded);
+
+    let mut failures = Vec::new();
+    println!("{:<32} {:>10} {:>10}", "instruction", "units", "recorded");
+    for (name, units) in &measured {
+        let before = recorded.get(name.as_str()).copied();
+        println!(
+            "{name:<32} {units:>10} {:>10}",
+            before.map_or_else(|| "-".to_string(), |before| before.to_string())
+        );
+        if *units > MAX_UNITS {
+            failures.push(format!(
+                "{name}: {units} units over the budget of {MAX_UNITS}"
+            ));
+        }
+        if update {
+            continue;
+        }
+        match before {
+            Some(before) if *units * 100 > before * (100 + REGRESSION_PERCENT) => {
+                failures.push(format!(
+                    "{name}: {units} units, more than {REGRESSION_PERCENT}% over {before}"
+                ));
+            }
+            Some(_) => {}
+            None => failures.push(format!("{name}: no units recorded in {TABLE}")),
+        }
+    }
+
+    if update {
+        fs::write(&path, format_table(&measured)).unwrap();
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+/// Run every instruction once, in an order where each one succeeds
+async fn measure_all() -> BTreeMap<String, u64> {
+    let mut units = BTreeMap::new();
+    let mut realm = TestRealm::new()
+        .await
+        .with_rate(RATE_DECIMALS, 1)
+        .await
+        .voter("alice")
+        .await
+        .voter("bob")
+        .await;
+    let payer = realm.context.payer.pubkey();
+    let authority = clone_keypair(&realm.authority);
+    let alice = clone_keypair(realm.authority_of("alice"));
+    let bob = clone_keypair(realm.authority_of("bob"));
+    let mint = realm.mint(0);
+    let lockup = DepositLockup {
+        kind: LockupKind::Cliff,
+        days: 10,
+    };
+
+    // a second registrar, to measure its creation and the one of an exchange rate
+    let other_realm = Pubkey::new_unique();
+    let ix = instruction::create_registrar(
+        &payer,
+        &authority.pubkey(),
+        &other_realm,
+        &realm.community_mint.pubkey(),
+        RATE_DECIMALS,
+        LockupPolicy::default(),
+    );
+    units.insert("CreateRegistrar", realm.process_measured(ix, &[]).await);
+
+    let er = ExchangeRateEntry {
+        mint,
+        rate: RATE_SCALE,
+        decimals: RATE_DECIMALS,
+        voting_token: VotingTokenPolicy::Frozen,
+        ..ExchangeRateEntry::default()
+    };
+    let ix = instruction::create_exchange_rate(
+        &authority.pubkey(),
+        &pda::registrar_address(&other_realm),
+        0,
+        er,
+    );
+    let measured = realm.process_measured(ix, &[&authority]).await;
+    units.insert("CreateExchangeRate", measured);
+
+    let carol = Keypair::new();
+    realm
+        .process(
+            &[system_instruction::transfer(
+                &payer,
+                &carol.pubkey(),
+                LAMPORTS_PER_SOL,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+    let ix = instruction::create_voter(&payer, &realm.registrar, &carol.pubkey());
+    units.insert("CreateVoter", realm.process_measured(ix, &[&carol]).await);
+
+    let ix = instruction::deposit(&realm.registrar, &alice.pubkey(), &mint, 100, lockup);
+    units.insert("CreateDeposit", realm.process_measured(ix, &[&alice]).await);
+
+    let ix = instruction::update_deposit(&realm.registrar, &alice.pubkey(), &mint, 0, 50);
+    units.insert("UpdateDeposit", realm.process_measured(ix, &[&alice]).await);
+
+    let bob_token = realm.token_address("bob", 0);
+    let ix = instruction::deposit_for(
+        &realm.registrar,
+        &bob.pubkey(),
+        &alice.pubkey(),
+        &mint,
+        &bob_token,
+        100,
+        lockup,
+    );
+    units.insert(
+        "CreateDepositFor",
+        realm.process_measured(ix, &[&bob, &alice]).await,
+    );
+
+    let ix = instruction::update_deposit_for(
+        &realm.registrar,
+        &bob.pubkey(),
+        &alice.pubkey(),
+        &mint,
+        &bob_token,
+        1,
+        50,
+    );
+    units.insert(
+        "UpdateDepositFor",
+        realm.process_measured(ix, &[&bob]).await,
+    );
+
+    let cooldown = u32::try_from(DELEGATION_COOLDOWN_SECS).unwrap();
+    let governance = realm.create_governance(cooldown);
+    let ix = instruction::set_delegation_cooldown(
+        &authority.pubkey(),
+        &realm.registrar,
+        &realm.realm,
+        &[governance],
+        cooldown,
+    );
+    let measured = realm.process_measured(ix, &[&authority]).await;
+    units.insert("SetDelegationCooldown", measured);
+
+    let ix = instruction::set_delegate(&realm.registrar, &alice.pubkey(), &bob.pubkey());
+    units.insert("SetDelegate", realm.process_measured(ix, &[&alice]).await);
+
+    let delegators = [alice.pubkey()];
+    let ix = instruction::update_voter_weight_record(&realm.registrar, &bob.pubkey(), &delegators);
+    let measured = realm.process_measured(ix, &[]).await;
+    units.insert("UpdateVoterWeightRecord", measured);
+
+    let at_ts = realm.now().await;
+    let ix = instruction::get_voter_weight(&realm.registrar, &bob.pubkey(), &delegators, at_ts);
+    units.insert("GetVoterWeight", realm.process_measured(ix, &[]).await);
+
+    realm.advance_days(10).await;
+    let ix = instruction::withdraw(&realm.registrar, &alice.pubkey(), &mint, 0, 150);
+    units.insert("Withdraw", realm.process_measured(ix, &[&alice]).await);
+
+    realm
+        .deposit("bob", 0, 100, LockupKind::Constant, 10)
+        .await
+        .unwrap();
+    let ix = instruction::start_unlock(&realm.registrar, &bob.pubkey(), 0);
+    units.insert("StartUnlock", realm.process_measured(ix, &[&bob]).await);
+
+    let ix = instruction::set_early_unlock_policy(
+        &authority.pubkey(),
+        &realm.registrar,
+        100,
+        &bob.pubkey(),
+    );
+    let measured = realm.process_measured(ix, &[&authority]).await;
+    units.insert("SetEarlyUnlockPolicy", measured);
+
+    // the first entry was freed by the full withdrawal
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+    let ix = instruction::early_unlock(&realm.registrar, &alice.pubkey(), &mint, &bob.pubkey(), 0);
+    units.insert("EarlyUnlock", realm.process_measured(ix, &[&alice]).await);
+
+    // fees paid in the deposit mint
+    let ix = instruction::create_reward_pool(&authority.pubkey(), &realm.registrar, &mint);
+    let measured = realm.process_measured(ix, &[&authority]).await;
+    units.insert("CreateRewardPool", measured);
+
+    let alice_token = realm.token_address("alice", 0);
+    let ix = instruction::fund_rewards(&alice.pubkey(), &realm.registrar, &mint, &alice_token, 100);
+    units.insert("FundRewards", realm.process_measured(ix, &[&alice]).await);
+
+    let ix = instruction::claim_rewards(&realm.registrar, &bob.pubkey(), &mint);
+    units.insert("ClaimRewards", realm.process_measured(ix, &[&bob]).await);
+
+    let ix = instruction::create_snapshot(&authority.pubkey(), &realm.registrar, 1);
+    let measured = realm.process_measured(ix, &[&authority]).await;
+    units.insert("CreateSnapshot", measured);
+
+    // alice checkpoints the snapshot before her deposit changes
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+    let ix = instruction::set_governing_snapshot(&authority.pubkey(), &realm.registrar, 1);
+    let measured = realm.process_measured(ix, &[&authority]).await;
+    units.insert("SetGoverningSnapshot", measured);
+
+    let ix = instruction::update_snapshot_voter_weight_record(
+        &realm.registrar,
+        &bob.pubkey(),
+        1,
+        &delegators,
+    );
+    let measured = realm.process_measured(ix, &[]).await;
+    units.insert("UpdateSnapshotVoterWeightRecord", measured);
+
+    let ix = instruction::set_lockup_policy(
+        &authority.pubkey(),
+        &realm.registrar,
+        LockupPolicy::default(),
+    );
+    let measured = realm.process_measured(ix, &[&authority]).await;
+    units.insert("SetLockupPolicy", measured);
+
+    let ix = instruction::refresh_reward_weight(&realm.registrar, &alice.pubkey());
+    let measured = realm.process_measured(ix, &[]).await;
+    units.insert("RefreshRewardWeight", measured);
+
+    #[cfg(feature = "time-offset")]
+    {
+        let ix = instruction::set_time_offset(&authority.pubkey(), &realm.registrar, 86_400);
+        let measured = realm.process_measured(ix, &[&authority]).await;
+        units.insert("SetTimeOffset", measured);
+    }
+
+    units
+        .into_iter()
+        .map(|(name, units)| (name.to_string(), units))
+        .collect()
+}
+
+/// One `<instruction> <units>` line per instruction, `#` starting a comment line
+fn parse_table(table: &str) -> BTreeMap<&str, u64> {
+    table
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            Some((fields.next()?, fields.next()?.parse().ok()?))
+        })
+        .collect()
+}
+
+fn format_table(units: &BTreeMap<String, u64>) -> String {
+    units
+        .iter()
+        .fold(String::new(), |mut table, (name, units)| {
+            let _ = writeln!(table, "{name} {units}");
+            table
+        })
+}
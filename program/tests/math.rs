@@ -0,0 +1,385 @@
+//! Property tests of the conversion, lockup, deposit and reward accounting math
+use {
+    program::{
+        error::GovError,
+        state::{
+            DepositEntry, ExchangeRateEntry, Lockup, LockupKind, Registrar, RewardPool, Voter,
+            VotingTokenPolicy, MAX_DAYS_LOCKED, MAX_SECS_LOCKED, RATE_SCALE, SCALED_FACTOR_BASE,
+            SECS_PER_DAY,
+        },
+    },
+    proptest::prelude::*,
+    solana_program::program_error::ProgramError,
+};
+
+// voting power per token of a lockup with its whole saturation left,
+// the most any lockup can weigh
+fn max_multiplier() -> u128 {
+    const PROBE: u64 = 1 << 32;
+    let saturated = Lockup {
+        kind: LockupKind::Cliff,
+        start_ts: 0,
+        end_ts: MAX_LOCKUP_SECS,
+        unlocking: false,
+        padding: [0; 15],
+    };
+    let power = saturated.voting_power(PROBE, MAX_SECS_LOCKED, 0).unwrap();
+    u128::from(power).div_ceil(u128::from(PROBE))
+}
+
+// weight factors of an exchange rate, up to ten votes per token
+const MAX_FACTOR: u64 = 10 * SCALED_FACTOR_BASE;
+
+const MAX_LOCKUP_SECS: i64 = MAX_DAYS_LOCKED as i64 * SECS_PER_DAY;
+
+fn kind() -> impl Strategy<Value = LockupKind> {
+    prop_oneof![
+        Just(LockupKind::Daily),
+        Just(LockupKind::Cliff),
+        Just(LockupKind::Constant),
+        Just(LockupKind::Monthly),
+    ]
+}
+
+prop_compose! {
+    fn lockup()(
+        kind in kind(),
+        start_ts in -MAX_LOCKUP_SECS..=i64::MAX / 2,
+        duration in 0..=2 * MAX_LOCKUP_SECS,
+        unlocking in any::<bool>(),
+    ) -> Lockup {
+        Lockup {
+            kind,
+            start_ts,
+            end_ts: start_ts + duration,
+            unlocking: unlocking && kind == LockupKind::Constant,
+            padding: [0; 15],
+        }
+    }
+}
+
+prop_compose! {
+    fn deposit()(
+        lockup in lockup(),
+        rate_idx in 0_u8..2,
+        amount_deposited in any::<u64>(),
+        withdrawn_ratio in 0_u64..=100,
+        amount_scaled in any::<u64>(),
+    ) -> DepositEntry {
+        DepositEntry {
+            is_used: true,
+            rate_idx,
+            amount_deposited,
+            amount_withdrawn: u64::try_from(
+                u128::from(amount_deposited) * u128::from(withdrawn_ratio) / 100,
+            )
+            .unwrap(),
+            amount_scaled,
+            lockup,
+        }
+    }
+}
+
+fn registrar(rate_decimals: u8, rates: [ExchangeRateEntry; 2]) -> Registrar {
+    Registrar {
+        rate_decimals,
+        rates,
+        ..Registrar::default()
+    }
+}
+
+// most weight `amount_scaled` tokens can have under the factors of `er`
+fn max_weight(er: &ExchangeRateEntry, amount_scaled: u64) -> u128 {
+    u128::from(amount_scaled)
+        * u128::from(
+            er.baseline_vote_weight_scaled_factor + er.max_extra_lockup_vote_weight_scaled_factor,
+        )
+        / u128::from(SCALED_FACTOR_BASE)
+}
+
+fn policy() -> impl Strategy<Value = VotingTokenPolicy> {
+    prop_oneof![
+        Just(VotingTokenPolicy::Frozen),
+        Just(VotingTokenPolicy::Transferable),
+        Just(VotingTokenPolicy::Disabled),
+    ]
+}
+
+prop_compose! {
+    fn rate()(
+        voting_token in policy(),
+        baseline_vote_weight_scaled_factor in 0..=MAX_FACTOR,
+        max_extra_lockup_vote_weight_scaled_factor in 0..=MAX_FACTOR,
+        lockup_saturation_secs in 1..=MAX_SECS_LOCKED,
+    ) -> ExchangeRateEntry {
+        ExchangeRateEntry {
+            rate: RATE_SCALE,
+            voting_token,
+            baseline_vote_weight_scaled_factor,
+            max_extra_lockup_vote_weight_scaled_factor,
+            lockup_saturation_secs,
+            ..ExchangeRateEntry::default()
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn convert_is_superadditive(
+        rate_decimals in 0_u8..=24,
+        decimals in 0_u8..=24,
+        rate in any::<u64>(),
+        a in any::<u64>(),
+        b in any::<u64>(),
+    ) {
+        let reg = registrar(rate_decimals, Default::default());
+        let er = ExchangeRateEntry { rate, decimals, ..ExchangeRateEntry::default() };
+        // flooring once loses less than flooring each part, and at most one unit more
+        if let Some(Ok(sum)) = a.checked_add(b).map(|sum| reg.convert(&er, sum)) {
+            let parts = reg.convert(&er, a)? + reg.convert(&er, b)?;
+            prop_assert!(sum >= parts);
+            prop_assert!(sum <= parts + 1);
+        }
+    }
+
+    #[test]
+    fn convert_round_trips_decimals(
+        rate_decimals in 0_u8..=24,
+        decimals in 0_u8..=24,
+        amount in any::<u64>(),
+    ) {
+        // at a rate of 1, from `decimals` to `rate_decimals` and back
+        let to = registrar(rate_decimals, Default::default());
+        let from = ExchangeRateEntry { rate: RATE_SCALE, decimals, ..ExchangeRateEntry::default() };
+        let back = registrar(decimals, Default::default());
+        let into = ExchangeRateEntry {
+            rate: RATE_SCALE,
+            decimals: rate_decimals,
+            ..ExchangeRateEntry::default()
+        };
+
+        if let Ok(converted) = to.convert(&from, amount) {
+            let round_trip = back.convert(&into, converted)?;
+            if rate_decimals >= decimals {
+                prop_assert_eq!(round_trip, amount);
+            } else {
+                // downscaling drops the digits below `rate_decimals`
+                let dropped = 10_u128.pow(u32::from(decimals - rate_decimals));
+                prop_assert!(round_trip <= amount);
+                prop_assert!(u128::from(amount - round_trip) < dropped);
+            }
+        }
+    }
+
+    #[test]
+    fn convert_is_monotonic_in_amount(
+        rate_decimals in 0_u8..=24,
+        decimals in 0_u8..=24,
+        rate in any::<u64>(),
+        a in any::<u64>(),
+        b in any::<u64>(),
+    ) {
+        let reg = registrar(rate_decimals, Default::default());
+        let er = ExchangeRateEntry { rate, decimals, ..ExchangeRateEntry::default() };
+        let (low, high) = (a.min(b), a.max(b));
+        if let Ok(high) = reg.convert(&er, high) {
+            prop_assert!(reg.convert(&er, low)? <= high);
+        }
+    }
+
+    #[test]
+    fn voting_power_never_panics(
+        kind in kind(),
+        start_ts in any::<i64>(),
+        end_ts in any::<i64>(),
+        amount in any::<u64>(),
+        unlocking in any::<bool>(),
+        saturation_secs in any::<u64>(),
+        curr_ts in any::<i64>(),
+    ) {
+        let l = Lockup { kind, start_ts, end_ts, unlocking, padding: [0; 15] };
+        let power = l.voting_power(amount, saturation_secs, curr_ts)?;
+        prop_assert!(u128::from(power) <= u128::from(amount) * max_multiplier());
+    }
+
+    #[test]
+    fn voting_power_decays_over_time(
+        l in lockup(),
+        amount in any::<u64>(),
+        m in 1..=MAX_SECS_LOCKED,
+        t0 in -MAX_LOCKUP_SECS..=3 * MAX_LOCKUP_SECS,
+        dt in 0..=MAX_LOCKUP_SECS,
+    ) {
+        let t0 = l.start_ts.saturating_add(t0);
+        let before = l.voting_power(amount, m, t0)?;
+        let after = l.voting_power(amount, m, t0.saturating_add(dt))?;
+        prop_assert!(after <= before);
+        prop_assert!(u128::from(before) <= u128::from(amount) * max_multiplier());
+        if l.expired(l.end_ts) {
+            prop_assert_eq!(l.voting_power(amount, m, l.end_ts)?, 0);
+        } else {
+            // a constant lockup not unlocking yet keeps its power
+            prop_assert_eq!(after, before);
+        }
+    }
+
+    #[test]
+    fn start_unlock_keeps_the_period(
+        l in lockup(),
+        amount in any::<u64>(),
+        m in 1..=MAX_SECS_LOCKED,
+        dt in 0..=MAX_LOCKUP_SECS,
+    ) {
+        let curr_ts = l.start_ts + dt;
+        let mut unlocked = l;
+        match unlocked.start_unlock(curr_ts) {
+            Ok(()) => {
+                prop_assert_eq!(unlocked.total_days(), l.total_days());
+                prop_assert_eq!(
+                    unlocked.voting_power(amount, m, curr_ts)?,
+                    l.voting_power(amount, m, curr_ts)?
+                );
+                prop_assert!(unlocked.expired(unlocked.end_ts));
+            }
+            Err(err) => {
+                prop_assert_eq!(err, ProgramError::from(GovError::InvalidLockupKind));
+                prop_assert_eq!(unlocked, l);
+            }
+        }
+    }
+
+    #[test]
+    fn voting_power_grows_with_amount(
+        l in lockup(),
+        a in any::<u64>(),
+        b in any::<u64>(),
+        m in 1..=MAX_SECS_LOCKED,
+        dt in 0..=MAX_LOCKUP_SECS,
+    ) {
+        let curr_ts = l.start_ts + dt;
+        let (low, high) = (a.min(b), a.max(b));
+        prop_assert!(l.voting_power(low, m, curr_ts)? <= l.voting_power(high, m, curr_ts)?);
+    }
+
+    #[test]
+    fn deposit_weight_is_baseline_plus_lockup_bonus(
+        deposit in deposit(),
+        er in rate(),
+        dt in -MAX_LOCKUP_SECS..=MAX_LOCKUP_SECS,
+    ) {
+        let curr_ts = deposit.lockup.end_ts.saturating_add(dt);
+        let baseline = ExchangeRateEntry { max_extra_lockup_vote_weight_scaled_factor: 0, ..er };
+        match deposit.voting_power(&er, curr_ts) {
+            Ok(weight) => {
+                prop_assert!(u128::from(weight) <= max_weight(&er, deposit.amount_scaled));
+                prop_assert!(baseline.vote_weight(deposit.amount_scaled, 0)? <= weight);
+                if deposit.lockup.expired(curr_ts) {
+                    prop_assert_eq!(deposit.voting_power(&baseline, curr_ts)?, weight);
+                }
+            }
+            Err(err) => {
+                prop_assert_eq!(err, ProgramError::from(GovError::MathOverflow));
+                prop_assert!(max_weight(&er, deposit.amount_scaled) > u128::from(u64::MAX));
+            }
+        }
+    }
+
+    #[test]
+    fn withdraw_keeps_accounting(
+        deposit in deposit(),
+        amount_ratio in 0_u64..=110,
+        dt in -MAX_LOCKUP_SECS..=MAX_LOCKUP_SECS,
+    ) {
+        let curr_ts = deposit.lockup.end_ts + dt;
+        let remaining = deposit.amount_deposited - deposit.amount_withdrawn;
+        // mostly within what is left, sometimes over it
+        let amount = u64::try_from(
+            (u128::from(remaining) * u128::from(amount_ratio) / 100).min(u128::from(u64::MAX)),
+        )
+        .unwrap();
+        let withdrawable = deposit.withdrawable(curr_ts);
+        prop_assert!(withdrawable <= remaining);
+        prop_assert!(
+            deposit.lockup.expired(curr_ts)
+                || withdrawable == 0
+                || deposit.lockup.kind.period_days().is_some()
+        );
+        prop_assert!(deposit.vested(curr_ts) <= deposit.amount_deposited);
+        prop_assert!(withdrawable <= deposit.withdrawable(curr_ts.saturating_add(dt.abs())));
+
+        let mut after = deposit;
+        match after.withdraw(amount, curr_ts) {
+            Ok(()) if amount == remaining => prop_assert_eq!(after, DepositEntry::default()),
+            Ok(()) => {
+                prop_assert!(after.is_used);
+                prop_assert_eq!(after.amount_deposited, deposit.amount_deposited);
+                prop_assert_eq!(after.amount_withdrawn, deposit.amount_withdrawn + amount);
+                prop_assert!(after.amount_scaled <= deposit.amount_scaled);
+                prop_assert_eq!(after.withdrawable(curr_ts), withdrawable - amount);
+            }
+            Err(err) => {
+                prop_assert!(amount > withdrawable);
+                prop_assert_eq!(err, ProgramError::from(GovError::InsufficientUnlockedTokens));
+                prop_assert_eq!(after, deposit);
+            }
+        }
+    }
+
+    #[test]
+    fn voter_weight_is_bounded_and_decays(
+        deposits in proptest::collection::vec(deposit(), 0..=10),
+        rates in [rate(), rate()],
+        curr_ts in any::<i64>(),
+        dt in 0..=MAX_LOCKUP_SECS,
+    ) {
+        let reg = registrar(6, rates);
+        let mut voter = Voter::default();
+        voter.deposits[..deposits.len()].copy_from_slice(&deposits);
+
+        let bound: u128 = deposits
+            .iter()
+            .filter(|d| reg.rate_has_weight(d.rate_idx).unwrap())
+            .map(|d| max_weight(reg.rate(d.rate_idx).unwrap(), d.amount_scaled))
+            .sum();
+        match voter.weight(&reg, curr_ts) {
+            Ok(weight) => {
+                prop_assert!(u128::from(weight) <= bound);
+                prop_assert!(voter.weight(&reg, curr_ts.saturating_add(dt))? <= weight);
+            }
+            Err(err) => {
+                prop_assert_eq!(err, ProgramError::from(GovError::MathOverflow));
+                prop_assert!(bound > u128::from(u64::MAX));
+            }
+        }
+    }
+
+    #[test]
+    fn rewards_never_exceed_funding(
+        // stake the weight of a voter, or fund the pool when `None`
+        steps in proptest::collection::vec(
+            (0_usize..3, proptest::option::of(0..=u64::from(u32::MAX)), 0..=u64::from(u32::MAX)),
+            0..20,
+        ),
+    ) {
+        let mut pool = RewardPool::default();
+        let mut voters = [Voter::default(), Voter::default(), Voter::default()];
+        let mut funded = 0_u128;
+        for (idx, weight, amount) in steps {
+            match weight {
+                Some(weight) => pool.stake(&mut voters[idx], weight)?,
+                None => {
+                    pool.fund(amount)?;
+                    funded += u128::from(amount);
+                }
+            }
+            let staked: u64 = voters.iter().map(|v| v.reward_weight).sum();
+            prop_assert_eq!(pool.total_weight, staked);
+        }
+
+        let mut owed = u128::from(pool.undistributed);
+        for voter in &voters {
+            owed += u128::from(pool.pending(voter, voter.reward_weight)?);
+        }
+        prop_assert!(owed <= funded);
+    }
+}
@@ -0,0 +1,936 @@
+//! Scenario fixture over solana-program-test.
+//!
+//! A `TestRealm` owns the program test context, one registrar with its
+//! exchange rates and a set of named voters, each funded with every rate mint.
+//! PDAs and ATAs are derived from the names, so a scenario reads as
+//!
+//! ```ignore
+//! let mut realm = TestRealm::new().await.with_rate(6, 1).await.voter("alice").await;
+//! realm.deposit("alice", 0, 10, LockupKind::Cliff, 2).await.unwrap();
+//! realm.advance_days(2).await;
+//! realm.withdraw("alice", 0, 0, 10).await.unwrap();
+//! realm.assert_balance("alice", 0, INITIAL_BALANCE).await;
+//! ```
+#![allow(dead_code)]
+use std::collections::HashMap;
+
+use {
+    borsh::BorshSerialize,
+    program::{
+        client::{
+            instruction::{self, DepositLockup},
+            pda,
+        },
+        entrypoint::process_instruction,
+        error::GovError,
+        return_data::VoterWeight,
+        state::{
+            ExchangeRateEntry, LockupKind, LockupPolicy, Registrar, RewardPool, Voter,
+            VotingTokenPolicy, RATE_SCALE, SECS_PER_DAY,
+        },
+    },
+    solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::{Account as SolanaAccount, AccountSharedData},
+        borsh::try_from_slice_unchecked,
+        clock::Clock,
+        hash::Hash,
+        instruction::{Instruction, InstructionError},
+        native_token::LAMPORTS_PER_SOL,
+        program_pack::Pack,
+        pubkey,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction,
+        transaction::{Transaction, TransactionError},
+        transport::TransportError,
+    },
+    spl_associated_token_account::{
+        get_associated_token_address, instruction::create_associated_token_account,
+    },
+    spl_governance::state::{
+        enums::{GovernanceAccountType, VoteThresholdPercentage, VoteTipping},
+        governance::{GovernanceConfig, GovernanceV2},
+    },
+    spl_governance_addin_api::voter_weight::VoterWeightRecord,
+    spl_token::state::{Account, Mint},
+};
+
+/// Decimals every deposit is converted to
+pub const RATE_DECIMALS: u8 = 6;
+
+/// Amount of every rate mint each voter starts with
+pub const INITIAL_BALANCE: u64 = 1_000_000;
+
+/// Governance program owning the realm and the governances faked by `create_governance`
+pub const GOVERNANCE_PROGRAM_ID: Pubkey = pubkey!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
+
+/// Delegation cooldown set by `with_delegation`, the max voting time of its governance
+pub const DELEGATION_COOLDOWN_SECS: i64 = 3 * SECS_PER_DAY;
+
+pub struct TestRealm {
+    pub context: ProgramTestContext,
+    pub realm: Pubkey,
+    // registrar authority, and mint authority of every mint
+    pub authority: Keypair,
+    pub community_mint: Keypair,
+    pub registrar: Pubkey,
+    // deposit mint of each exchange rate, by rate index
+    pub mints: Vec<Keypair>,
+    // fee mint of the reward pool, once created
+    pub reward_mint: Option<Keypair>,
+    voters: HashMap<&'static str, Keypair>,
+    last_blockhash: Hash,
+}
+
+impl TestRealm {
+    /// Start the program test and create the registrar, without any exchange rate
+    pub async fn new() -> Self {
+        let pt = ProgramTest::new("program", program::id(), processor!(process_instruction));
+        let context = pt.start_with_context().await;
+        let last_blockhash = context.last_blockhash;
+
+        let mut realm = Self {
+            context,
+            realm: Pubkey::new_unique(),
+            authority: Keypair::new(),
+            community_mint: Keypair::new(),
+            registrar: Pubkey::default(),
+            mints: Vec::new(),
+            reward_mint: None,
+            voters: HashMap::new(),
+            last_blockhash,
+        };
+        realm.registrar = pda::registrar_address(&realm.realm);
+
+        let community_mint = clone_keypair(&realm.community_mint);
+        realm.create_mint(&community_mint, RATE_DECIMALS).await;
+        let payer = realm.context.payer.pubkey();
+        // the registrar authority pays for the vaults and the reward pool
+        let ixs = [
+            system_instruction::transfer(&payer, &realm.authority.pubkey(), LAMPORTS_PER_SOL),
+            instruction::create_registrar(
+                &payer,
+                &realm.authority.pubkey(),
+                &realm.realm,
+                &community_mint.pubkey(),
+                RATE_DECIMALS,
+                LockupPolicy::default(),
+            ),
+        ];
+        realm.process(&ixs, &[]).await.unwrap();
+
+        realm
+    }
+
+    /// Add an exchange rate of `rate` whole units over a new mint,
+    /// minting frozen voting tokens
+    pub async fn with_rate(self, decimals: u8, rate: u64) -> Self {
+        self.with_rate_policy(decimals, rate, VotingTokenPolicy::Frozen)
+            .await
+    }
+
+    pub async fn with_rate_policy(
+        self,
+        decimals: u8,
+        rate: u64,
+        voting_token: VotingTokenPolicy,
+    ) -> Self {
+        self.with_rate_entry(ExchangeRateEntry {
+            rate: rate * RATE_SCALE,
+            decimals,
+            voting_token,
+            ..ExchangeRateEntry::default()
+        })
+        .await
+    }
+
+    /// Add an exchange rate over a new mint, with the policy and weight factors of `er`
+    pub async fn with_rate_entry(mut self, er: ExchangeRateEntry) -> Self {
+        self.add_rate(er).await.unwrap();
+        self
+    }
+
+    /// Create `er` over a new mint of its decimals, at the next rate index
+    pub async fn add_rate(&mut self, er: ExchangeRateEntry) -> Result<(), TransportError> {
+        self.add_rate_over_mint(er.decimals, er).await
+    }
+
+    /// Create `er` over a new mint of `mint_decimals`, at the next rate index
+    pub async fn add_rate_over_mint(
+        &mut self,
+        mint_decimals: u8,
+        er: ExchangeRateEntry,
+    ) -> Result<(), TransportError> {
+        let mint = Keypair::new();
+        self.create_mint(&mint, mint_decimals).await;
+
+        let idx = u16::try_from(self.mints.len()).unwrap();
+        let er = ExchangeRateEntry {
+            mint: mint.pubkey(),
+            ..er
+        };
+        let ix =
+            instruction::create_exchange_rate(&self.authority.pubkey(), &self.registrar, idx, er);
+        let authority = clone_keypair(&self.authority);
+        self.process(&[ix], &[&authority]).await?;
+
+        self.mints.push(mint);
+        Ok(())
+    }
+
+    pub async fn with_reward_pool(mut self) -> Self {
+        let mint = Keypair::new();
+        self.create_mint(&mint, RATE_DECIMALS).await;
+
+        let authority = clone_keypair(&self.authority);
+        let ixs = [
+            create_associated_token_account(
+                &self.context.payer.pubkey(),
+                &authority.pubkey(),
+                &mint.pubkey(),
+            ),
+            instruction::create_reward_pool(&authority.pubkey(), &self.registrar, &mint.pubkey()),
+        ];
+        self.process(&ixs, &[&authority]).await.unwrap();
+
+        self.reward_mint = Some(mint);
+        self
+    }
+
+    /// Enable delegation, with a cooldown of `DELEGATION_COOLDOWN_SECS`
+    /// covering the max voting time of the only governance of the realm
+    pub async fn with_delegation(mut self) -> Self {
+        let cooldown = u32::try_from(DELEGATION_COOLDOWN_SECS).unwrap();
+        let governance = self.create_governance(cooldown);
+        self.set_delegation_cooldown(&[governance], cooldown)
+            .await
+            .unwrap();
+        self
+    }
+
+    /// Fake a governance of the realm whose proposals vote for up to `max_voting_time`
+    /// seconds, along with the realm account owned by `GOVERNANCE_PROGRAM_ID`
+    pub fn create_governance(&mut self, max_voting_time: u32) -> Pubkey {
+        let governance = GovernanceV2 {
+            account_type: GovernanceAccountType::GovernanceV2,
+            realm: self.realm,
+            governed_account: Pubkey::new_unique(),
+            proposals_count: 0,
+            config: GovernanceConfig {
+                vote_threshold_percentage: VoteThresholdPercentage::YesVote(60),
+                min_community_weight_to_create_proposal: 1,
+                min_transaction_hold_up_time: 0,
+                max_voting_time,
+                vote_tipping: VoteTipping::Strict,
+                proposal_cool_off_time: 0,
+                min_council_weight_to_create_proposal: 1,
+            },
+            reserved: [0; 6],
+            voting_proposal_count: 0,
+            reserved_v2: [0; 128],
+        };
+        let governance_address = Pubkey::new_unique();
+        for (address, data) in [
+            (self.realm, Vec::new()),
+            (governance_address, governance.try_to_vec().unwrap()),
+        ] {
+            let account = SolanaAccount {
+                lamports: LAMPORTS_PER_SOL,
+                data,
+                owner: GOVERNANCE_PROGRAM_ID,
+                executable: false,
+                rent_epoch: 0,
+            };
+            self.context
+                .set_account(&address, &AccountSharedData::from(account));
+        }
+
+        governance_address
+    }
+
+    /// Create the voter `name`, funded with `INITIAL_BALANCE` of every rate mint.
+    /// Add the rates first
+    pub async fn voter(mut self, name: &'static str) -> Self {
+        let authority = Keypair::new();
+        let payer = self.context.payer.pubkey();
+
+        // the voter authority pays for its voter account and voting token ATAs
+        let mut ixs = vec![
+            system_instruction::transfer(&payer, &authority.pubkey(), LAMPORTS_PER_SOL),
+            instruction::create_voter(&payer, &self.registrar, &authority.pubkey()),
+        ];
+        for mint in &self.mints {
+            ixs.push(create_associated_token_account(
+                &payer,
+                &authority.pubkey(),
+                &mint.pubkey(),
+            ));
+            ixs.push(
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &get_associated_token_address(&authority.pubkey(), &mint.pubkey()),
+                    &self.authority.pubkey(),
+                    &[],
+                    INITIAL_BALANCE,
+                )
+                .unwrap(),
+            );
+        }
+        let mint_authority = clone_keypair(&self.authority);
+        self.process(&ixs, &[&authority, &mint_authority])
+            .await
+            .unwrap();
+
+        self.voters.insert(name, authority);
+        self
+    }
+
+    // ------- addresses -------
+
+    pub fn mint(&self, rate_idx: usize) -> Pubkey {
+        self.mints[rate_idx].pubkey()
+    }
+
+    pub fn authority_of(&self, name: &str) -> &Keypair {
+        self.voters
+            .get(name)
+            .unwrap_or_else(|| panic!("unknown voter {name}"))
+    }
+
+    pub fn voter_address(&self, name: &str) -> Pubkey {
+        pda::voter_address(&self.registrar, &self.authority_of(name).pubkey())
+    }
+
+    /// ATA of the voter for the deposit mint of the rate
+    pub fn token_address(&self, name: &str, rate_idx: usize) -> Pubkey {
+        get_associated_token_address(&self.authority_of(name).pubkey(), &self.mint(rate_idx))
+    }
+
+    /// ATA of the voter for the voting mint of the rate
+    pub fn voting_token_address(&self, name: &str, rate_idx: usize) -> Pubkey {
+        pda::voting_token_address(
+            &self.registrar,
+            &self.authority_of(name).pubkey(),
+            &self.mint(rate_idx),
+        )
+    }
+
+    pub fn reward_mint(&self) -> Pubkey {
+        self.reward_mint
+            .as_ref()
+            .expect("no reward pool, call `with_reward_pool`")
+            .pubkey()
+    }
+
+    /// ATA of the voter for the fee mint of the reward pool
+    pub fn reward_token_address(&self, name: &str) -> Pubkey {
+        get_associated_token_address(&self.authority_of(name).pubkey(), &self.reward_mint())
+    }
+
+    pub fn exchange_vault(&self, rate_idx: usize) -> Pubkey {
+        pda::exchange_vault_address(&self.registrar, &self.mint(rate_idx))
+    }
+
+    // ------- instructions -------
+
+    pub async fn deposit(
+        &mut self,
+        name: &str,
+        rate_idx: usize,
+        amount: u64,
+        kind: LockupKind,
+        days: i32,
+    ) -> Result<(), TransportError> {
+        let authority = clone_keypair(self.authority_of(name));
+        let ix = instruction::deposit(
+            &self.registrar,
+            &authority.pubkey(),
+            &self.mint(rate_idx),
+            amount,
+            DepositLockup { kind, days },
+        );
+        self.process(&[ix], &[&authority]).await
+    }
+
+    /// `depositor` funds a new deposit of `name` from its own tokens, both signing
+    pub async fn deposit_for(
+        &mut self,
+        depositor: &str,
+        name: &str,
+        rate_idx: usize,
+        amount: u64,
+        kind: LockupKind,
+        days: i32,
+    ) -> Result<(), TransportError> {
+        let depositor_authority = clone_keypair(self.authority_of(depositor));
+        let authority = clone_keypair(self.authority_of(name));
+        let ix = instruction::deposit_for(
+            &self.registrar,
+            &depositor_authority.pubkey(),
+            &self.authority_of(name).pubkey(),
+            &self.mint(rate_idx),
+            &self.token_address(depositor, rate_idx),
+            amount,
+            DepositLockup { kind, days },
+        );
+        self.process(&[ix], &[&depositor_authority, &authority])
+            .await
+    }
+
+    pub async fn update_deposit(
+        &mut self,
+        name: &str,
+        rate_idx: usize,
+        deposit_idx: u8,
+        amount: u64,
+    ) -> Result<(), TransportError> {
+        let authority = clone_keypair(self.authority_of(name));
+        let ix = instruction::update_deposit(
+            &self.registrar,
+            &authority.pubkey(),
+            &self.mint(rate_idx),
+            deposit_idx,
+            amount,
+        );
+        self.process(&[ix], &[&authority]).await
+    }
+
+    pub async fn withdraw(
+        &mut self,
+        name: &str,
+        rate_idx: usize,
+        deposit_idx: u8,
+        amount: u64,
+    ) -> Result<(), TransportError> {
+        let authority = clone_keypair(self.authority_of(name));
+        let ix = instruction::withdraw(
+            &self.registrar,
+            &authority.pubkey(),
+            &self.mint(rate_idx),
+            deposit_idx,
+            amount,
+        );
+        self.process(&[ix], &[&authority]).await
+    }
+
+    /// Withdraw of `name` to the ATA of `destination`,
+    /// burning the voting tokens `holder` was handed
+    pub async fn withdraw_from(
+        &mut self,
+        name: &str,
+        holder: &str,
+        destination: &str,
+        rate_idx: usize,
+        deposit_idx: u8,
+        amount: u64,
+    ) -> Result<(), TransportError> {
+        let authority = clone_keypair(self.authority_of(name));
+        let mint = self.mint(rate_idx);
+        let mut keys = instruction::withdraw_keys(
+            &self.registrar,
+            &authority.pubkey(),
+            &mint,
+            &get_associated_token_address(&self.authority_of(destination).pubkey(), &mint),
+        );
+        keys.voting_token = self.voting_token_address(holder, rate_idx);
+        let ix = program::instruction::withdraw(&keys, deposit_idx, amount);
+        self.process(&[ix], &[&authority]).await
+    }
+
+    /// Transfer voting tokens of `from` to the voting token ATA of `to`,
+    /// which approves `from` as delegate of them
+    pub async fn hand_over_voting_tokens(
+        &mut self,
+        from: &str,
+        to: &str,
+        rate_idx: usize,
+        amount: u64,
+    ) -> Result<(), TransportError> {
+        let from_token = self.voting_token_address(from, rate_idx);
+        let to_token = self.voting_token_address(to, rate_idx);
+        let voting_mint = pda::voting_mint_address(&self.registrar, &self.mint(rate_idx));
+        let from = clone_keypair(self.authority_of(from));
+        let to = clone_keypair(self.authority_of(to));
+        let ixs = [
+            create_associated_token_account(
+                &self.context.payer.pubkey(),
+                &to.pubkey(),
+                &voting_mint,
+            ),
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                &from_token,
+                &to_token,
+                &from.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+            spl_token::instruction::approve(
+                &spl_token::id(),
+                &to_token,
+                &from.pubkey(),
+                &to.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+        ];
+        self.process(&ixs, &[&from, &to]).await
+    }
+
+    pub async fn start_unlock(
+        &mut self,
+        name: &str,
+        deposit_idx: u8,
+    ) -> Result<(), TransportError> {
+        let authority = clone_keypair(self.authority_of(name));
+        let ix = instruction::start_unlock(&self.registrar, &authority.pubkey(), deposit_idx);
+        self.process(&[ix], &[&authority]).await
+    }
+
+    /// Send early unlock penalties to the ATAs of the voter authority `treasury`,
+    /// or disable early unlocks with `None`
+    pub async fn set_early_unlock_policy(
+        &mut self,
+        penalty_bps: u16,
+        treasury: Option<&str>,
+    ) -> Result<(), TransportError> {
+        let authority = clone_keypair(&self.authority);
+        let treasury = treasury.map_or_else(Pubkey::default, |treasury| {
+            self.authority_of(treasury).pubkey()
+        });
+        let ix = instruction::set_early_unlock_policy(
+            &authority.pubkey(),
+            &self.registrar,
+            penalty_bps,
+            &treasury,
+        );
+        self.process(&[ix], &[&authority]).await
+    }
+
+    /// Early unlock paying the penalty to the current treasury of the registrar
+    pub async fn early_unlock(
+        &mut self,
+        name: &str,
+        rate_idx: usize,
+        deposit_idx: u8,
+    ) -> Result<(), TransportError> {
+        let authority = clone_keypair(self.authority_of(name));
+        let treasury = self.registrar_state().await.treasury;
+        let ix = instruction::early_unlock(
+            &self.registrar,
+            &authority.pubkey(),
+            &self.mint(rate_idx),
+            &treasury,
+            deposit_idx,
+        );
+        self.process(&[ix], &[&authority]).await
+    }
+
+    /// Delegate the weight of `name` to `delegate`, or take it back with `None`
+    pub async fn set_delegate(
+        &mut self,
+        name: &str,
+        delegate: Option<&str>,
+    ) -> Result<(), TransportError> {
+        let authority = clone_keypair(self.authority_of(name));
+        let delegate = delegate.map_or_else(Pubkey::default, |delegate| {
+            self.authority_of(delegate).pubkey()
+        });
+        let ix = instruction::set_delegate(&self.registrar, &authority.pubkey(), &delegate);
+        self.process(&[ix], &[&authority]).await
+    }
+
+    pub async fn update_voter_weight_record(
+        &mut self,
+        name: &str,
+        delegators: &[&str],
+    ) -> Result<(), TransportError> {
+        let delegators: Vec<Pubkey> = delegators
+            .iter()
+            .map(|delegator| self.authority_of(delegator).pubkey())
+            .collect();
+        let ix = instruction::update_voter_weight_record(
+            &self.registrar,
+            &self.authority_of(name).pubkey(),
+            &delegators,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    /// Mint `amount` fees to the registrar authority and fund the reward pool with them
+    pub async fn fund_rewards(&mut self, amount: u64) -> Result<(), TransportError> {
+        let authority = clone_keypair(&self.authority);
+        let mint = self.reward_mint();
+        let source = get_associated_token_address(&authority.pubkey(), &mint);
+        let ixs = [
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint,
+                &source,
+                &authority.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+            instruction::fund_rewards(&authority.pubkey(), &self.registrar, &mint, &source, amount),
+        ];
+        self.process(&ixs, &[&authority]).await
+    }
+
+    /// Claim the rewards of `name` to its ATA of the fee mint, created when missing
+    pub async fn claim_rewards(&mut self, name: &str) -> Result<(), TransportError> {
+        let authority = clone_keypair(self.authority_of(name));
+        let mint = self.reward_mint();
+        let mut ixs = Vec::new();
+        if self
+            .context
+            .banks_client
+            .get_account(self.reward_token_address(name))
+            .await
+            .unwrap()
+            .is_none()
+        {
+            ixs.push(create_associated_token_account(
+                &self.context.payer.pubkey(),
+                &authority.pubkey(),
+                &mint,
+            ));
+        }
+        ixs.push(instruction::claim_rewards(
+            &self.registrar,
+            &authority.pubkey(),
+            &mint,
+        ));
+        self.process(&ixs, &[&authority]).await
+    }
+
+    /// Stake the current weight of `name` in the reward pool, sent by the payer
+    pub async fn refresh_reward_weight(&mut self, name: &str) -> Result<(), TransportError> {
+        let ix =
+            instruction::refresh_reward_weight(&self.registrar, &self.authority_of(name).pubkey());
+        self.process(&[ix], &[]).await
+    }
+
+    pub async fn set_lockup_policy(
+        &mut self,
+        lockup_policy: LockupPolicy,
+    ) -> Result<(), TransportError> {
+        let authority = clone_keypair(&self.authority);
+        let ix =
+            instruction::set_lockup_policy(&authority.pubkey(), &self.registrar, lockup_policy);
+        self.process(&[ix], &[&authority]).await
+    }
+
+    pub async fn set_delegation_cooldown(
+        &mut self,
+        governances: &[Pubkey],
+        delegation_cooldown_secs: u32,
+    ) -> Result<(), TransportError> {
+        let authority = clone_keypair(&self.authority);
+        let ix = instruction::set_delegation_cooldown(
+            &authority.pubkey(),
+            &self.registrar,
+            &self.realm,
+            governances,
+            delegation_cooldown_secs,
+        );
+        self.process(&[ix], &[&authority]).await
+    }
+
+    /// Open the next snapshot epoch of the registrar at the current time
+    pub async fn create_snapshot(&mut self) -> Result<(), TransportError> {
+        let authority = clone_keypair(&self.authority);
+        let epoch = self.registrar_state().await.snapshot_epoch + 1;
+        let ix = instruction::create_snapshot(&authority.pubkey(), &self.registrar, epoch);
+        self.process(&[ix], &[&authority]).await
+    }
+
+    /// Count the voter weight as of the snapshot `epoch`, or live again with 0
+    pub async fn set_governing_snapshot(&mut self, epoch: u64) -> Result<(), TransportError> {
+        let authority = clone_keypair(&self.authority);
+        let ix = instruction::set_governing_snapshot(&authority.pubkey(), &self.registrar, epoch);
+        self.process(&[ix], &[&authority]).await
+    }
+
+    pub async fn update_snapshot_voter_weight_record(
+        &mut self,
+        name: &str,
+        epoch: u64,
+        delegators: &[&str],
+    ) -> Result<(), TransportError> {
+        let delegators: Vec<Pubkey> = delegators
+            .iter()
+            .map(|delegator| self.authority_of(delegator).pubkey())
+            .collect();
+        let ix = instruction::update_snapshot_voter_weight_record(
+            &self.registrar,
+            &self.authority_of(name).pubkey(),
+            epoch,
+            &delegators,
+        );
+        self.process(&[ix], &[]).await
+    }
+
+    // ------- clock -------
+
+    pub async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar().await.unwrap()
+    }
+
+    pub async fn now(&mut self) -> i64 {
+        self.clock().await.unix_timestamp
+    }
+
+    /// Move the clock of the bank forward by `days`
+    pub async fn advance_days(&mut self, days: i64) {
+        self.advance_secs(days * SECS_PER_DAY).await;
+    }
+
+    pub async fn advance_secs(&mut self, secs: i64) {
+        let mut clock = self.clock().await;
+        clock.unix_timestamp += secs;
+        self.context.set_sysvar(&clock);
+    }
+
+    // ------- state -------
+
+    pub async fn registrar_state(&mut self) -> Registrar {
+        self.context
+            .banks_client
+            .get_account_data_with_borsh(self.registrar)
+            .await
+            .unwrap()
+    }
+
+    pub async fn voter_state(&mut self, name: &str) -> Voter {
+        let voter = self.voter_address(name);
+        self.context
+            .banks_client
+            .get_account_data_with_borsh(voter)
+            .await
+            .unwrap()
+    }
+
+    /// Weight last written by `UpdateVoterWeightRecord`
+    pub async fn voter_weight(&mut self, name: &str) -> u64 {
+        let record =
+            pda::voter_weight_record_address(&self.registrar, &self.authority_of(name).pubkey());
+        let account = self
+            .context
+            .banks_client
+            .get_account(record)
+            .await
+            .unwrap()
+            .unwrap();
+        try_from_slice_unchecked::<VoterWeightRecord>(&account.data)
+            .unwrap()
+            .voter_weight
+    }
+
+    /// Return data of a simulated `GetVoterWeight`. The runtime only logs it
+    /// for the BPF build: run under `cargo test-bpf`
+    pub async fn get_voter_weight(
+        &mut self,
+        name: &str,
+        delegators: &[&str],
+        at_ts: i64,
+    ) -> VoterWeight {
+        let delegators: Vec<Pubkey> = delegators
+            .iter()
+            .map(|delegator| self.authority_of(delegator).pubkey())
+            .collect();
+        let ix = instruction::get_voter_weight(
+            &self.registrar,
+            &self.authority_of(name).pubkey(),
+            &delegators,
+            at_ts,
+        );
+        let logs = self.simulate(ix, &[]).await;
+        VoterWeight::from_logs(&logs).expect("no return data in the logs")
+    }
+
+    /// Token amount of a token account, zero when it does not exist
+    pub async fn balance(&mut self, token: Pubkey) -> u64 {
+        match self.context.banks_client.get_account(token).await.unwrap() {
+            Some(account) => Account::unpack(&account.data).unwrap().amount,
+            None => 0,
+        }
+    }
+
+    /// Assert the deposit mint balance of the voter
+    pub async fn assert_balance(&mut self, name: &str, rate_idx: usize, expected: u64) {
+        let token = self.token_address(name, rate_idx);
+        assert_eq!(
+            self.balance(token).await,
+            expected,
+            "{name} balance of rate {rate_idx}"
+        );
+    }
+
+    /// Assert the voting token balance of the voter
+    pub async fn assert_voting_balance(&mut self, name: &str, rate_idx: usize, expected: u64) {
+        let token = self.voting_token_address(name, rate_idx);
+        assert_eq!(
+            self.balance(token).await,
+            expected,
+            "{name} voting balance of rate {rate_idx}"
+        );
+    }
+
+    pub async fn assert_vault_balance(&mut self, rate_idx: usize, expected: u64) {
+        let vault = self.exchange_vault(rate_idx);
+        assert_eq!(
+            self.balance(vault).await,
+            expected,
+            "vault balance of rate {rate_idx}"
+        );
+    }
+
+    pub async fn reward_pool_state(&mut self) -> RewardPool {
+        self.context
+            .banks_client
+            .get_account_data_with_borsh(pda::reward_pool_address(&self.registrar))
+            .await
+            .unwrap()
+    }
+
+    /// Assert the fee balance of the voter
+    pub async fn assert_reward_balance(&mut self, name: &str, expected: u64) {
+        let token = self.reward_token_address(name);
+        assert_eq!(self.balance(token).await, expected, "{name} reward balance");
+    }
+
+    // ------- transactions -------
+
+    /// Sign with the payer and `signers`, on a fresh blockhash
+    /// so that identical transactions are not deduplicated
+    pub async fn process(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransportError> {
+        let tx = self.sign(ixs, signers).await;
+        self.context.banks_client.process_transaction(tx).await?;
+        Ok(())
+    }
+
+    /// Process `ix`, returning the compute units the program consumed.
+    /// The units are only logged by the BPF loader: run under `cargo test-bpf`
+    pub async fn process_measured(&mut self, ix: Instruction, signers: &[&Keypair]) -> u64 {
+        let logs = self.simulate(ix.clone(), signers).await;
+        let units = consumed_units(&logs)
+            .expect("no compute units in the logs, is the program loaded from its BPF build?");
+
+        self.process(&[ix], signers).await.unwrap();
+        units
+    }
+
+    /// Logs of `ix` simulated without being processed.
+    /// The banks server only returns the logs of a failed simulation, so `ix`
+    /// is followed by an invalid instruction
+    pub async fn simulate(&mut self, ix: Instruction, signers: &[&Keypair]) -> Vec<String> {
+        let invalid = Instruction::new_with_bytes(program::id(), &[u8::MAX], vec![]);
+        let tx = self.sign(&[ix, invalid], signers).await;
+        match self
+            .context
+            .banks_client
+            .process_transaction_with_preflight(tx)
+            .await
+        {
+            Err(BanksClientError::SimulationError {
+                err: TransactionError::InstructionError(1, _),
+                logs,
+                ..
+            }) => logs,
+            other => panic!("expected the invalid instruction to fail, got {other:?}"),
+        }
+    }
+
+    async fn sign(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> Transaction {
+        let blockhash = self.new_blockhash().await;
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        )
+    }
+
+    async fn new_blockhash(&mut self) -> Hash {
+        loop {
+            let blockhash = self
+                .context
+                .banks_client
+                .get_latest_blockhash()
+                .await
+                .unwrap();
+            if blockhash != self.last_blockhash {
+                self.last_blockhash = blockhash;
+                return blockhash;
+            }
+        }
+    }
+
+    async fn create_mint(&mut self, mint: &Keypair, decimals: u8) {
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let payer = self.context.payer.pubkey();
+        let ixs = [
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &self.authority.pubkey(),
+                None,
+                decimals,
+            )
+            .unwrap(),
+        ];
+        self.process(&ixs, &[mint]).await.unwrap();
+    }
+}
+
+/// Units of the first top level invocation of the program, CPIs included
+fn consumed_units(logs: &[String]) -> Option<u64> {
+    let prefix = format!("Program {} consumed ", program::id());
+    logs.iter()
+        .find_map(|log| log.strip_prefix(&prefix))
+        .and_then(|rest| rest.split(' ').next())
+        .and_then(|units| units.parse().ok())
+}
+
+pub fn clone_keypair(keypair: &Keypair) -> Keypair {
+    Keypair::from_bytes(&keypair.to_bytes()).unwrap()
+}
+
+/// Assert the transaction failed with `error` of the program
+pub fn assert_gov_error(result: Result<(), TransportError>, error: GovError) {
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        ))) => assert_eq!(code, error as u32, "expected {error:?}"),
+        other => panic!("expected {error:?}, got {other:?}"),
+    }
+}
+
+/// Assert the transaction failed with the given instruction error
+pub fn assert_instruction_error(result: Result<(), TransportError>, error: InstructionError) {
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(_, err))) => {
+            assert_eq!(err, error);
+        }
+        other => panic!("expected {error:?}, got {other:?}"),
+    }
+}
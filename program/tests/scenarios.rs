@@ -0,0 +1,1074 @@
+//! Deposit, withdrawal, lockup, delegation, reward and snapshot scenarios on a `TestRealm`
+mod realm;
+
+use program::{
+    client::instruction::{self, DepositLockup},
+    error::GovError,
+    state::{
+        ExchangeRateEntry, LockupKind, LockupPolicy, VotingTokenPolicy, MAX_DAYS_LOCKED,
+        RATE_SCALE, SCALED_FACTOR_BASE, SECS_PER_DAY, SNAPSHOT_HISTORY,
+    },
+};
+use realm::{
+    assert_gov_error, assert_instruction_error, TestRealm, DELEGATION_COOLDOWN_SECS,
+    INITIAL_BALANCE,
+};
+use solana_program_test::tokio;
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const FULL_LOCKUP: i32 = MAX_DAYS_LOCKED as i32;
+
+async fn realm_with_alice() -> TestRealm {
+    TestRealm::new()
+        .await
+        .with_rate(6, 1)
+        .await
+        .voter("alice")
+        .await
+}
+
+#[tokio::test]
+async fn deposit_fills_vault_and_mints_voting_tokens() {
+    let mut realm = realm_with_alice().await;
+
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+
+    realm
+        .assert_balance("alice", 0, INITIAL_BALANCE - 100)
+        .await;
+    realm.assert_vault_balance(0, 100).await;
+    realm.assert_voting_balance("alice", 0, 100).await;
+
+    let d = realm.voter_state("alice").await.deposits[0];
+    assert!(d.is_used);
+    assert_eq!((d.amount_deposited, d.amount_scaled), (100, 100));
+    assert_eq!(d.lockup.end_ts - d.lockup.start_ts, 10 * 86_400);
+}
+
+#[tokio::test]
+async fn voters_register_only_with_the_registrar_pda() {
+    let mut realm = realm_with_alice().await;
+    // a copy of the registrar at an address that is not its PDA
+    let copy = Pubkey::new_unique();
+    let registrar = realm
+        .context
+        .banks_client
+        .get_account(realm.registrar)
+        .await
+        .unwrap()
+        .unwrap();
+    realm.context.set_account(&copy, &registrar.into());
+
+    let authority = Keypair::new();
+    let payer = realm.context.payer.pubkey();
+    let ix = instruction::create_voter(&payer, &copy, &authority.pubkey());
+    let result = realm.process(&[ix], &[&authority]).await;
+
+    assert_gov_error(result, GovError::PdaMismatch);
+}
+
+#[tokio::test]
+async fn deposit_scales_by_rate_and_decimals() {
+    // 2 voting units per token of a 0 decimals mint, converted to 6 decimals
+    let mut realm = TestRealm::new()
+        .await
+        .with_rate(0, 2)
+        .await
+        .voter("alice")
+        .await;
+
+    realm
+        .deposit("alice", 0, 7, LockupKind::Daily, 30)
+        .await
+        .unwrap();
+
+    let d = realm.voter_state("alice").await.deposits[0];
+    assert_eq!(d.amount_scaled, 14_000_000);
+}
+
+#[tokio::test]
+async fn update_deposit_adds_to_the_entry() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+
+    realm.update_deposit("alice", 0, 0, 50).await.unwrap();
+
+    let d = realm.voter_state("alice").await.deposits[0];
+    assert_eq!((d.amount_deposited, d.amount_scaled), (150, 150));
+    realm.assert_vault_balance(0, 150).await;
+    realm.assert_voting_balance("alice", 0, 150).await;
+}
+
+#[tokio::test]
+async fn update_of_a_vesting_deposit_fails() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Daily, 10)
+        .await
+        .unwrap();
+    realm.advance_days(5).await;
+
+    // half of the top-up would vest at once
+    let result = realm.update_deposit("alice", 0, 0, 100).await;
+
+    assert_gov_error(result, GovError::TopUpNotAllowed);
+}
+
+#[tokio::test]
+async fn update_of_an_expired_deposit_fails() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+    realm.advance_days(10).await;
+
+    let result = realm.update_deposit("alice", 0, 0, 100).await;
+
+    assert_gov_error(result, GovError::TopUpNotAllowed);
+}
+
+#[tokio::test]
+async fn update_must_stay_locked_the_minimum_days() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 30)
+        .await
+        .unwrap();
+    realm
+        .set_lockup_policy(LockupPolicy {
+            min_lockup_days: 30,
+            ..LockupPolicy::default()
+        })
+        .await
+        .unwrap();
+    realm.advance_days(1).await;
+
+    let result = realm.update_deposit("alice", 0, 0, 100).await;
+
+    assert_gov_error(result, GovError::TopUpNotAllowed);
+}
+
+#[tokio::test]
+async fn update_of_unused_entry_fails() {
+    let mut realm = realm_with_alice().await;
+
+    let result = realm.update_deposit("alice", 0, 3, 50).await;
+
+    assert_gov_error(result, GovError::InvalidDepositId);
+}
+
+#[tokio::test]
+async fn lockup_longer_than_max_days_fails() {
+    let mut realm = realm_with_alice().await;
+
+    let result = realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, FULL_LOCKUP + 1)
+        .await;
+
+    assert_gov_error(result, GovError::InvalidLockupDays);
+}
+
+#[tokio::test]
+async fn lockup_policy_bounds_new_deposits() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .set_lockup_policy(LockupPolicy {
+            min_lockup_days: 30,
+            max_lockup_days: 365,
+            allowed_kinds: LockupKind::Cliff.mask() | LockupKind::Monthly.mask(),
+        })
+        .await
+        .unwrap();
+
+    let result = realm.deposit("alice", 0, 100, LockupKind::Cliff, 29).await;
+    assert_gov_error(result, GovError::InvalidLockupDays);
+    let result = realm.deposit("alice", 0, 100, LockupKind::Cliff, 366).await;
+    assert_gov_error(result, GovError::InvalidLockupDays);
+    let result = realm
+        .deposit("alice", 0, 100, LockupKind::Constant, 30)
+        .await;
+    assert_gov_error(result, GovError::LockupKindNotAllowed);
+
+    realm
+        .deposit("alice", 0, 100, LockupKind::Monthly, 360)
+        .await
+        .unwrap();
+    assert!(realm.voter_state("alice").await.deposits[0].is_used);
+}
+
+#[tokio::test]
+async fn lockup_policy_needs_an_allowed_kind() {
+    let mut realm = realm_with_alice().await;
+    let result = realm
+        .set_lockup_policy(LockupPolicy {
+            allowed_kinds: 0,
+            ..LockupPolicy::default()
+        })
+        .await;
+
+    assert_gov_error(result, GovError::InvalidLockupPolicy);
+}
+
+#[tokio::test]
+async fn eleventh_deposit_fails() {
+    let mut realm = realm_with_alice().await;
+    for _ in 0..10 {
+        realm
+            .deposit("alice", 0, 1, LockupKind::Cliff, 1)
+            .await
+            .unwrap();
+    }
+
+    let result = realm.deposit("alice", 0, 1, LockupKind::Cliff, 1).await;
+
+    assert_gov_error(result, GovError::DepositEntryFull);
+}
+
+#[tokio::test]
+async fn withdraw_before_expiry_fails() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+    realm.advance_days(9).await;
+
+    let result = realm.withdraw("alice", 0, 0, 1).await;
+
+    assert_gov_error(result, GovError::InsufficientUnlockedTokens);
+    realm.assert_vault_balance(0, 100).await;
+}
+
+#[tokio::test]
+async fn withdraw_after_expiry_returns_tokens_and_burns_voting_tokens() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+    realm.advance_days(10).await;
+
+    realm.withdraw("alice", 0, 0, 40).await.unwrap();
+
+    realm.assert_balance("alice", 0, INITIAL_BALANCE - 60).await;
+    realm.assert_vault_balance(0, 60).await;
+    realm.assert_voting_balance("alice", 0, 60).await;
+    let d = realm.voter_state("alice").await.deposits[0];
+    assert_eq!((d.amount_withdrawn, d.amount_scaled), (40, 60));
+
+    realm.withdraw("alice", 0, 0, 60).await.unwrap();
+
+    realm.assert_balance("alice", 0, INITIAL_BALANCE).await;
+    realm.assert_voting_balance("alice", 0, 0).await;
+    assert!(!realm.voter_state("alice").await.deposits[0].is_used);
+}
+
+#[tokio::test]
+async fn withdraw_more_than_deposited_fails() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 1)
+        .await
+        .unwrap();
+    realm.advance_days(1).await;
+
+    let result = realm.withdraw("alice", 0, 0, 101).await;
+
+    assert_gov_error(result, GovError::InsufficientUnlockedTokens);
+}
+
+#[tokio::test]
+async fn withdraw_with_the_wrong_mint_fails() {
+    let mut realm = TestRealm::new()
+        .await
+        .with_rate(6, 1)
+        .await
+        .with_rate(6, 1)
+        .await
+        .voter("alice")
+        .await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 1)
+        .await
+        .unwrap();
+    realm.advance_days(1).await;
+
+    let result = realm.withdraw("alice", 1, 0, 100).await;
+
+    assert_gov_error(result, GovError::TokenMintMismatch);
+}
+
+#[tokio::test]
+async fn deposit_for_funds_another_voter() {
+    let mut realm = realm_with_alice().await.voter("bob").await;
+
+    realm
+        .deposit_for("bob", "alice", 0, 100, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+
+    realm.assert_balance("bob", 0, INITIAL_BALANCE - 100).await;
+    realm.assert_balance("alice", 0, INITIAL_BALANCE).await;
+    realm.assert_voting_balance("alice", 0, 100).await;
+    assert!(realm.voter_state("alice").await.deposits[0].is_used);
+    assert!(!realm.voter_state("bob").await.deposits[0].is_used);
+}
+
+#[tokio::test]
+async fn deposit_for_needs_the_voter_signature() {
+    let mut realm = realm_with_alice().await.voter("bob").await;
+    let bob = realm::clone_keypair(realm.authority_of("bob"));
+    let mut ix = instruction::deposit_for(
+        &realm.registrar,
+        &bob.pubkey(),
+        &realm.authority_of("alice").pubkey(),
+        &realm.mint(0),
+        &realm.token_address("bob", 0),
+        100,
+        DepositLockup {
+            kind: LockupKind::Cliff,
+            days: FULL_LOCKUP,
+        },
+    );
+    // bob would lock alice's entries at will
+    ix.accounts.last_mut().unwrap().is_signer = false;
+
+    let result = realm.process(&[ix], &[&bob]).await;
+
+    assert_instruction_error(result, InstructionError::MissingRequiredSignature);
+    assert!(!realm.voter_state("alice").await.deposits[0].is_used);
+}
+
+#[tokio::test]
+async fn cliff_weight_decays_daily() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 1_095_000, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+
+    realm
+        .update_voter_weight_record("alice", &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 1_095_000);
+
+    realm.advance_days(95).await;
+    realm
+        .update_voter_weight_record("alice", &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 1_000_000);
+
+    realm.advance_days(1_000).await;
+    realm
+        .update_voter_weight_record("alice", &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 0);
+}
+
+// the return data is only logged for the BPF build
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn get_voter_weight_returns_the_weight_now_and_later() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 1_095_000, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+
+    let at_ts = realm.now().await + 95 * SECS_PER_DAY;
+    let weight = realm.get_voter_weight("alice", &[], at_ts).await;
+
+    assert_eq!(weight.voter, realm.voter_address("alice"));
+    assert_eq!(weight.at_ts, at_ts);
+    assert_eq!((weight.weight, weight.weight_at), (1_095_000, 1_000_000));
+    assert_eq!(weight.deposits.len(), 1);
+    assert_eq!(weight.deposits[0].voting_power_at, 1_000_000);
+}
+
+#[tokio::test]
+async fn lp_rate_earns_a_higher_lockup_bonus() {
+    // a tenth of a vote unlocked, three once locked for a year or more
+    let lp = ExchangeRateEntry {
+        rate: RATE_SCALE,
+        decimals: 6,
+        baseline_vote_weight_scaled_factor: SCALED_FACTOR_BASE / 10,
+        max_extra_lockup_vote_weight_scaled_factor: 2 * SCALED_FACTOR_BASE,
+        lockup_saturation_secs: 365 * SECS_PER_DAY.unsigned_abs(),
+        ..ExchangeRateEntry::default()
+    };
+    let mut realm = TestRealm::new()
+        .await
+        .with_rate(6, 1)
+        .await
+        .with_rate_entry(lp)
+        .await
+        .voter("alice")
+        .await
+        .voter("bob")
+        .await;
+    realm
+        .deposit("alice", 0, 730_000, LockupKind::Cliff, 365)
+        .await
+        .unwrap();
+    realm
+        .deposit("bob", 1, 730_000, LockupKind::Cliff, 365)
+        .await
+        .unwrap();
+
+    for name in ["alice", "bob"] {
+        realm.update_voter_weight_record(name, &[]).await.unwrap();
+    }
+    assert_eq!(realm.voter_weight("alice").await, 243_333);
+    assert_eq!(realm.voter_weight("bob").await, 73_000 + 1_460_000);
+
+    // the baseline outlives the lockup
+    realm.advance_days(365).await;
+    for name in ["alice", "bob"] {
+        realm.update_voter_weight_record(name, &[]).await.unwrap();
+    }
+    assert_eq!(realm.voter_weight("alice").await, 0);
+    assert_eq!(realm.voter_weight("bob").await, 73_000);
+}
+
+#[tokio::test]
+async fn exchange_rate_needs_the_decimals_of_its_mint() {
+    let mut realm = TestRealm::new().await;
+    let result = realm
+        .add_rate_over_mint(
+            9,
+            ExchangeRateEntry {
+                rate: RATE_SCALE,
+                decimals: 6,
+                ..ExchangeRateEntry::default()
+            },
+        )
+        .await;
+
+    assert_gov_error(result, GovError::InvalidDecimals);
+}
+
+#[tokio::test]
+async fn exchange_rate_needs_a_lockup_saturation() {
+    let mut realm = TestRealm::new().await;
+    let result = realm
+        .add_rate(ExchangeRateEntry {
+            rate: RATE_SCALE,
+            decimals: 6,
+            lockup_saturation_secs: 0,
+            ..ExchangeRateEntry::default()
+        })
+        .await;
+
+    assert_gov_error(result, GovError::InvalidVoteWeightFactors);
+}
+
+#[tokio::test]
+async fn delegated_weight_moves_to_the_delegate() {
+    let mut realm = realm_with_alice()
+        .await
+        .with_delegation()
+        .await
+        .voter("bob")
+        .await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Constant, FULL_LOCKUP)
+        .await
+        .unwrap();
+    realm
+        .deposit("bob", 0, 2_190, LockupKind::Constant, FULL_LOCKUP)
+        .await
+        .unwrap();
+
+    // the delegated weight counts for nobody during the cooldown
+    realm.set_delegate("alice", Some("bob")).await.unwrap();
+    realm
+        .update_voter_weight_record("bob", &["alice"])
+        .await
+        .unwrap();
+    realm
+        .update_voter_weight_record("alice", &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("bob").await, 2_190);
+    assert_eq!(realm.voter_weight("alice").await, 0);
+
+    realm.advance_secs(DELEGATION_COOLDOWN_SECS).await;
+    realm
+        .update_voter_weight_record("bob", &["alice"])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("bob").await, 3_285);
+
+    // only voters delegating to bob may be counted
+    realm.set_delegate("alice", None).await.unwrap();
+    let result = realm.update_voter_weight_record("bob", &["alice"]).await;
+    assert_gov_error(result, GovError::DelegateMismatch);
+    realm
+        .update_voter_weight_record("alice", &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 0);
+}
+
+#[tokio::test]
+async fn delegation_cannot_change_during_the_cooldown() {
+    let mut realm = realm_with_alice()
+        .await
+        .with_delegation()
+        .await
+        .voter("bob")
+        .await
+        .voter("carol")
+        .await;
+    realm.set_delegate("alice", Some("bob")).await.unwrap();
+
+    let result = realm.set_delegate("alice", Some("carol")).await;
+    assert_gov_error(result, GovError::DelegationCooldown);
+    let result = realm.set_delegate("alice", None).await;
+    assert_gov_error(result, GovError::DelegationCooldown);
+
+    realm.advance_secs(DELEGATION_COOLDOWN_SECS).await;
+    realm.set_delegate("alice", Some("carol")).await.unwrap();
+    assert_eq!(
+        realm.voter_state("alice").await.delegate,
+        realm.authority_of("carol").pubkey()
+    );
+}
+
+#[tokio::test]
+async fn delegation_is_disabled_until_a_cooldown_is_set() {
+    let mut realm = realm_with_alice().await.voter("bob").await;
+
+    let result = realm.set_delegate("alice", Some("bob")).await;
+
+    assert_gov_error(result, GovError::DelegationDisabled);
+}
+
+#[tokio::test]
+async fn delegation_cooldown_must_cover_every_governance_voting_time() {
+    let mut realm = realm_with_alice().await;
+    let day = u32::try_from(SECS_PER_DAY).unwrap();
+    let short = realm.create_governance(day);
+    let long = realm.create_governance(7 * day);
+
+    let result = realm.set_delegation_cooldown(&[short, long], 3 * day).await;
+    assert_gov_error(result, GovError::InvalidDelegationCooldown);
+    let result = realm.set_delegation_cooldown(&[], 0).await;
+    assert_instruction_error(result, InstructionError::NotEnoughAccountKeys);
+
+    realm
+        .set_delegation_cooldown(&[short, long], 7 * day)
+        .await
+        .unwrap();
+    assert_eq!(
+        realm.registrar_state().await.delegation_cooldown_secs,
+        7 * day
+    );
+}
+
+#[tokio::test]
+async fn delegator_counted_twice_fails() {
+    let mut realm = realm_with_alice()
+        .await
+        .with_delegation()
+        .await
+        .voter("bob")
+        .await;
+    realm.set_delegate("alice", Some("bob")).await.unwrap();
+
+    let result = realm
+        .update_voter_weight_record("bob", &["alice", "alice"])
+        .await;
+
+    assert_gov_error(result, GovError::DuplicateDelegator);
+}
+
+#[tokio::test]
+async fn transferable_voting_tokens_carry_no_weight() {
+    let mut realm = TestRealm::new()
+        .await
+        .with_rate_policy(6, 1, VotingTokenPolicy::Transferable)
+        .await
+        .voter("alice")
+        .await;
+
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+    realm
+        .update_voter_weight_record("alice", &[])
+        .await
+        .unwrap();
+
+    realm.assert_voting_balance("alice", 0, 1_095).await;
+    assert_eq!(realm.voter_weight("alice").await, 0);
+}
+
+#[tokio::test]
+async fn transferable_voting_tokens_are_redeemed_from_their_holder() {
+    let mut realm = TestRealm::new()
+        .await
+        .with_rate_policy(6, 1, VotingTokenPolicy::Transferable)
+        .await
+        .voter("alice")
+        .await
+        .voter("bob")
+        .await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 1)
+        .await
+        .unwrap();
+    realm
+        .hand_over_voting_tokens("alice", "bob", 0, 100)
+        .await
+        .unwrap();
+    realm.advance_days(1).await;
+
+    let result = realm.withdraw("alice", 0, 0, 100).await;
+    assert!(result.is_err(), "alice no longer holds the receipts");
+    let result = realm
+        .withdraw_from("alice", "bob", "alice", 0, 0, 100)
+        .await;
+    assert_gov_error(result, GovError::TokenOwnerMismatch);
+
+    // bob holds the receipts, so bob is paid
+    realm
+        .withdraw_from("alice", "bob", "bob", 0, 0, 100)
+        .await
+        .unwrap();
+    realm
+        .assert_balance("alice", 0, INITIAL_BALANCE - 100)
+        .await;
+    realm.assert_balance("bob", 0, INITIAL_BALANCE + 100).await;
+    realm.assert_voting_balance("bob", 0, 0).await;
+}
+
+#[tokio::test]
+async fn disabled_voting_tokens_are_never_minted() {
+    let mut realm = TestRealm::new()
+        .await
+        .with_rate_policy(6, 1, VotingTokenPolicy::Disabled)
+        .await
+        .voter("alice")
+        .await;
+
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+    realm
+        .update_voter_weight_record("alice", &[])
+        .await
+        .unwrap();
+
+    realm.assert_voting_balance("alice", 0, 0).await;
+    assert_eq!(realm.voter_weight("alice").await, 1_095);
+}
+
+#[tokio::test]
+async fn withdraw_needs_the_voter_authority() {
+    let mut realm = realm_with_alice().await.voter("bob").await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 1)
+        .await
+        .unwrap();
+    realm.advance_days(1).await;
+
+    // bob signs for his own voter account, which has no such deposit
+    let result = realm.withdraw("bob", 0, 0, 100).await;
+
+    assert_gov_error(result, GovError::InvalidDepositId);
+    realm.assert_vault_balance(0, 100).await;
+}
+
+#[tokio::test]
+async fn constant_lockup_keeps_its_weight_until_unlocked() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Constant, FULL_LOCKUP)
+        .await
+        .unwrap();
+
+    realm.advance_days(2_000).await;
+    realm
+        .update_voter_weight_record("alice", &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 1_095);
+    let result = realm.withdraw("alice", 0, 0, 1_095).await;
+    assert_gov_error(result, GovError::InsufficientUnlockedTokens);
+
+    realm.start_unlock("alice", 0).await.unwrap();
+    realm.advance_days(95).await;
+    realm
+        .update_voter_weight_record("alice", &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 1_000);
+
+    let result = realm.start_unlock("alice", 0).await;
+    assert_gov_error(result, GovError::InvalidLockupKind);
+
+    realm.advance_days(1_000).await;
+    realm.withdraw("alice", 0, 0, 1_095).await.unwrap();
+    realm.assert_balance("alice", 0, INITIAL_BALANCE).await;
+}
+
+#[tokio::test]
+async fn only_constant_lockups_start_unlocking() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+
+    let result = realm.start_unlock("alice", 0).await;
+
+    assert_gov_error(result, GovError::InvalidLockupKind);
+}
+
+#[tokio::test]
+async fn monthly_lockup_unlocks_a_tranche_every_month() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 300, LockupKind::Monthly, 90)
+        .await
+        .unwrap();
+
+    realm.advance_days(29).await;
+    let result = realm.withdraw("alice", 0, 0, 1).await;
+    assert_gov_error(result, GovError::InsufficientUnlockedTokens);
+
+    realm.advance_days(1).await;
+    realm.withdraw("alice", 0, 0, 100).await.unwrap();
+    let result = realm.withdraw("alice", 0, 0, 1).await;
+    assert_gov_error(result, GovError::InsufficientUnlockedTokens);
+
+    realm.advance_days(60).await;
+    realm.withdraw("alice", 0, 0, 200).await.unwrap();
+    realm.assert_balance("alice", 0, INITIAL_BALANCE).await;
+    realm.assert_voting_balance("alice", 0, 0).await;
+}
+
+#[tokio::test]
+async fn monthly_lockup_needs_whole_months() {
+    let mut realm = realm_with_alice().await;
+
+    let result = realm
+        .deposit("alice", 0, 300, LockupKind::Monthly, 45)
+        .await;
+
+    assert_gov_error(result, GovError::InvalidLockupDays);
+}
+
+#[tokio::test]
+async fn daily_lockup_vests_every_whole_day() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 1_000, LockupKind::Daily, 10)
+        .await
+        .unwrap();
+
+    realm.advance_secs(2 * 86_400 + 43_200).await;
+    let result = realm.withdraw("alice", 0, 0, 201).await;
+    assert_gov_error(result, GovError::InsufficientUnlockedTokens);
+    realm.withdraw("alice", 0, 0, 200).await.unwrap();
+
+    let d = realm.voter_state("alice").await.deposits[0];
+    let now = realm.now().await;
+    assert_eq!((d.vested(now), d.withdrawable(now)), (200, 0));
+    realm
+        .assert_balance("alice", 0, INITIAL_BALANCE - 800)
+        .await;
+    realm.assert_voting_balance("alice", 0, 800).await;
+}
+
+#[tokio::test]
+async fn early_unlock_is_disabled_without_treasury() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 100, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+
+    let result = realm.early_unlock("alice", 0, 0).await;
+
+    assert_gov_error(result, GovError::EarlyUnlockDisabled);
+}
+
+#[tokio::test]
+async fn early_unlock_pays_the_penalty_to_the_treasury() {
+    let mut realm = realm_with_alice().await.voter("bob").await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+    let result = realm.set_early_unlock_policy(10_001, Some("bob")).await;
+    assert_gov_error(result, GovError::InvalidPenalty);
+    realm
+        .set_early_unlock_policy(5_000, Some("bob"))
+        .await
+        .unwrap();
+
+    realm.advance_days(876).await;
+    realm.early_unlock("alice", 0, 0).await.unwrap();
+
+    // 219 days left of 1_095: a fifth of the 50% penalty
+    realm
+        .assert_balance("alice", 0, INITIAL_BALANCE - 109)
+        .await;
+    realm.assert_balance("bob", 0, INITIAL_BALANCE + 109).await;
+    realm.assert_voting_balance("alice", 0, 0).await;
+    realm.assert_vault_balance(0, 0).await;
+    assert!(!realm.voter_state("alice").await.deposits[0].is_used);
+}
+
+#[tokio::test]
+async fn rewards_are_shared_by_voter_weight() {
+    let mut realm = realm_with_alice()
+        .await
+        .voter("bob")
+        .await
+        .with_reward_pool()
+        .await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+    realm
+        .deposit("bob", 0, 2_190, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+    assert_eq!(realm.reward_pool_state().await.total_weight, 3_285);
+
+    realm.fund_rewards(3_285).await.unwrap();
+    realm.claim_rewards("alice").await.unwrap();
+    realm.claim_rewards("bob").await.unwrap();
+
+    realm.assert_reward_balance("alice", 1_095).await;
+    realm.assert_reward_balance("bob", 2_190).await;
+    assert_eq!(realm.voter_state("alice").await.rewards_owed, 0);
+}
+
+#[tokio::test]
+async fn withdrawn_deposits_stop_earning() {
+    let mut realm = realm_with_alice()
+        .await
+        .voter("bob")
+        .await
+        .with_reward_pool()
+        .await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+    realm
+        .deposit("bob", 0, 1_095, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+
+    realm.advance_days(10).await;
+    realm.withdraw("alice", 0, 0, 1_095).await.unwrap();
+    realm.fund_rewards(1_095).await.unwrap();
+    realm.claim_rewards("alice").await.unwrap();
+    realm.claim_rewards("bob").await.unwrap();
+
+    realm.assert_reward_balance("alice", 0).await;
+    realm.assert_reward_balance("bob", 1_095).await;
+}
+
+#[tokio::test]
+async fn expired_lockups_stop_earning_once_refreshed() {
+    let mut realm = realm_with_alice()
+        .await
+        .voter("bob")
+        .await
+        .with_reward_pool()
+        .await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Cliff, 10)
+        .await
+        .unwrap();
+    realm
+        .deposit("bob", 0, 1_095, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+
+    // anyone refreshes alice's expired lockup, bob's weight decayed to 1_085
+    realm.advance_days(10).await;
+    realm.refresh_reward_weight("alice").await.unwrap();
+    assert_eq!(realm.voter_state("alice").await.reward_weight, 0);
+    realm.fund_rewards(1_095).await.unwrap();
+    realm.claim_rewards("alice").await.unwrap();
+    realm.claim_rewards("bob").await.unwrap();
+
+    realm.assert_reward_balance("alice", 0).await;
+    realm.assert_reward_balance("bob", 1_085).await;
+    assert_eq!(realm.reward_pool_state().await.undistributed, 10);
+}
+
+#[tokio::test]
+async fn voters_from_before_the_pool_earn_once_refreshed() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Constant, FULL_LOCKUP)
+        .await
+        .unwrap();
+    let mut realm = realm.with_reward_pool().await;
+
+    realm.fund_rewards(1_095).await.unwrap();
+    assert_eq!(realm.reward_pool_state().await.total_weight, 0);
+    realm.refresh_reward_weight("alice").await.unwrap();
+    realm.fund_rewards(1_095).await.unwrap();
+    realm.claim_rewards("alice").await.unwrap();
+
+    realm.assert_reward_balance("alice", 2_190).await;
+}
+
+#[tokio::test]
+async fn deposits_after_a_snapshot_do_not_count_for_it() {
+    let mut realm = realm_with_alice().await.voter("bob").await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+    realm
+        .deposit("bob", 0, 2_190, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+
+    realm.create_snapshot().await.unwrap();
+    realm.update_deposit("alice", 0, 0, 2_190).await.unwrap();
+
+    realm.set_governing_snapshot(1).await.unwrap();
+    realm
+        .update_snapshot_voter_weight_record("alice", 1, &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 1_095);
+    realm
+        .update_snapshot_voter_weight_record("bob", 1, &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("bob").await, 2_190);
+
+    realm.set_governing_snapshot(0).await.unwrap();
+    realm
+        .update_voter_weight_record("alice", &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 3_285);
+}
+
+#[tokio::test]
+async fn only_the_latest_snapshots_are_checkpointed() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+    for _ in 0..=SNAPSHOT_HISTORY {
+        realm.create_snapshot().await.unwrap();
+    }
+    realm.update_deposit("alice", 0, 0, 1_095).await.unwrap();
+
+    let latest = realm.registrar_state().await.snapshot_epoch;
+    realm.set_governing_snapshot(latest).await.unwrap();
+    realm
+        .update_snapshot_voter_weight_record("alice", latest, &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 1_095);
+
+    let result = realm.set_governing_snapshot(1).await;
+    assert_gov_error(result, GovError::SnapshotNotKept);
+}
+
+#[tokio::test]
+async fn only_the_governing_snapshot_counts() {
+    let mut realm = realm_with_alice().await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Cliff, FULL_LOCKUP)
+        .await
+        .unwrap();
+    realm.create_snapshot().await.unwrap();
+    realm.create_snapshot().await.unwrap();
+
+    // the live weight counts until a snapshot governs
+    let result = realm
+        .update_snapshot_voter_weight_record("alice", 1, &[])
+        .await;
+    assert_gov_error(result, GovError::SnapshotNotGoverning);
+    realm.set_governing_snapshot(1).await.unwrap();
+    let result = realm.update_voter_weight_record("alice", &[]).await;
+    assert_gov_error(result, GovError::SnapshotGoverns);
+    let result = realm
+        .update_snapshot_voter_weight_record("alice", 2, &[])
+        .await;
+    assert_gov_error(result, GovError::SnapshotNotGoverning);
+    realm
+        .update_snapshot_voter_weight_record("alice", 1, &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 1_095);
+
+    // the governing snapshot is never dropped from the kept ones
+    for _ in 2..SNAPSHOT_HISTORY {
+        realm.create_snapshot().await.unwrap();
+    }
+    let result = realm.create_snapshot().await;
+    assert_gov_error(result, GovError::SnapshotGoverns);
+}
+
+#[tokio::test]
+async fn delegations_changed_after_a_snapshot_do_not_count_for_it() {
+    let mut realm = realm_with_alice()
+        .await
+        .with_delegation()
+        .await
+        .voter("bob")
+        .await;
+    realm
+        .deposit("alice", 0, 1_095, LockupKind::Constant, FULL_LOCKUP)
+        .await
+        .unwrap();
+    realm.set_delegate("alice", Some("bob")).await.unwrap();
+    realm.advance_secs(DELEGATION_COOLDOWN_SECS).await;
+
+    realm.create_snapshot().await.unwrap();
+    realm.set_delegate("alice", None).await.unwrap();
+    realm.advance_secs(DELEGATION_COOLDOWN_SECS).await;
+    realm.set_governing_snapshot(1).await.unwrap();
+
+    // bob held her weight as of the snapshot, alice did not
+    realm
+        .update_snapshot_voter_weight_record("alice", 1, &[])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("alice").await, 0);
+    realm
+        .update_snapshot_voter_weight_record("bob", 1, &["alice"])
+        .await
+        .unwrap();
+    assert_eq!(realm.voter_weight("bob").await, 1_095);
+
+    let result = realm
+        .update_snapshot_voter_weight_record("alice", 1, &["bob"])
+        .await;
+    assert_gov_error(result, GovError::DelegateMismatch);
+}
@@ -4,7 +4,11 @@ mod action;
 mod spl_token_action;
 use solana_program_test::*;
 
-use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, transaction::Transaction};
+use solana_sdk::{
+    borsh::try_from_slice_unchecked, clock::Clock, pubkey::Pubkey, signature::Keypair,
+    signer::Signer, transaction::Transaction,
+};
+use spl_governance_addin_api::voter_weight::VoterWeightRecord;
 
 use program::{entrypoint::process_instruction, state};
 
@@ -114,8 +118,10 @@ async fn test() {
 
     let er_a = program::state::ExchangeRateEntry {
         mint: mint_a.pubkey(),
-        rate: 1,
+        rate: state::RATE_SCALE,
         decimals: 6,
+        voting_token: state::VotingTokenPolicy::Frozen,
+        ..program::state::ExchangeRateEntry::default()
     };
     action::create_exchange_rate(
         &mut banks_client,
@@ -140,8 +146,10 @@ async fn test() {
     );
     let er_b = program::state::ExchangeRateEntry {
         mint: mint_b.pubkey(),
-        rate: 1_000_000,
+        rate: 1_000_000 * state::RATE_SCALE,
         decimals: 0,
+        voting_token: state::VotingTokenPolicy::Frozen,
+        ..program::state::ExchangeRateEntry::default()
     };
     action::create_exchange_rate(
         &mut banks_client,
@@ -186,6 +194,41 @@ async fn test() {
     .await
     .unwrap();
 
+    // ------ create_voter of another authority ------
+    let other = Keypair::new();
+    let (other_voter_pda, other_voter_bump) = Pubkey::find_program_address(
+        &[&registrar_pda.to_bytes(), &other.pubkey().to_bytes()],
+        &program::id(),
+    );
+    let seeds: &[&[_]] = &[
+        &program::processor::create_voter::VOTER_WEIGHT_RECORD,
+        &registrar_pda.to_bytes(),
+        &other.pubkey().to_bytes(),
+    ];
+    let (other_voter_weight_record, other_voter_weight_record_bump) =
+        Pubkey::find_program_address(seeds, &program::id());
+
+    action::create_voter_for(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &other,
+        &registrar_pda,
+        &other_voter_pda,
+        other_voter_bump,
+        &other_voter_weight_record,
+        other_voter_weight_record_bump,
+    )
+    .await
+    .expect("create_voter_for");
+
+    let other_voter: state::Voter = banks_client
+        .get_account_data_with_borsh(other_voter_pda)
+        .await
+        .unwrap();
+    assert_eq!(other_voter.authority, other.pubkey());
+    assert_eq!(other_voter.registrar, registrar_pda);
+
     // ------ create_deposit ------
     let voting_token_pda = spl_associated_token_account::get_associated_token_address(
         &payer.pubkey(),
@@ -288,4 +331,132 @@ async fn test() {
 
     assert_eq!(d_er.amount_deposited, before_deposit + amount);
     assert_eq!(d_er.amount_scaled, before_scaled_deposit + convert_q);
+
+    // ------ update deposit on behalf of the voter ------
+    let depositor = Keypair::new();
+    let depositor_token = Keypair::new();
+    spl_token_action::create_token_account(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &depositor_token,
+        &mint_a.pubkey(),
+        &depositor.pubkey(),
+    )
+    .await
+    .unwrap();
+    spl_token_action::mint(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &mint_a.pubkey(),
+        &depositor_token.pubkey(),
+        &payer,
+        amount,
+    )
+    .await
+    .unwrap();
+
+    action::update_deposit_for(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &depositor,
+        &registrar_pda,
+        &payer.pubkey(),
+        &mint_a.pubkey(),
+        &depositor_token.pubkey(),
+        update_idx,
+        amount,
+    )
+    .await
+    .expect("update_deposit_for");
+
+    let voter: state::Voter = banks_client
+        .get_account_data_with_borsh(voter_pda)
+        .await
+        .unwrap();
+    assert_eq!(
+        voter.deposits[update_idx as usize].amount_deposited,
+        before_deposit + 2 * amount
+    );
+
+    // ------ update voter_weight_record ------
+    action::update_voter_weight_record(
+        &mut banks_client,
+        &payer,
+        recent_blockhash,
+        &registrar_pda,
+        &voter_pda,
+        &voter_weight_record,
+        &[],
+    )
+    .await
+    .expect("update_voter_weight_record");
+
+    let clock: Clock = banks_client.get_sysvar().await.unwrap();
+    let record: VoterWeightRecord = try_from_slice_unchecked(
+        &banks_client
+            .get_account(voter_weight_record)
+            .await
+            .unwrap()
+            .unwrap()
+            .data,
+    )
+    .unwrap();
+
+    assert_eq!(
+        record.voter_weight,
+        voter.weight(&registrar, clock.unix_timestamp).unwrap()
+    );
+    assert_eq!(record.voter_weight_expiry, Some(clock.slot));
+
+    // ------ set time offset ------
+    // the 2 days cliff expires once the registrar clock is shifted past it
+    #[cfg(feature = "time-offset")]
+    {
+        let deposited = voter.deposits[update_idx as usize].amount_deposited;
+        action::withdraw(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &registrar_pda,
+            &mint_a.pubkey(),
+            &vault_a.pubkey(),
+            update_idx,
+            1,
+        )
+        .await
+        .expect_err("withdraw before the cliff expires");
+
+        let time_offset = 3 * state::SECS_PER_DAY;
+        action::set_time_offset(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &registrar_pda,
+            time_offset,
+        )
+        .await
+        .expect("set_time_offset");
+
+        let registrar: state::Registrar = banks_client
+            .get_account_data_with_borsh(registrar_pda)
+            .await
+            .unwrap();
+        assert_eq!(registrar.time_offset, time_offset);
+
+        action::withdraw(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &registrar_pda,
+            &mint_a.pubkey(),
+            &vault_a.pubkey(),
+            update_idx,
+            deposited,
+        )
+        .await
+        .expect("withdraw after the offset");
+    }
 }